@@ -0,0 +1,1215 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use octocrab::models::AuthorAssociation;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use shared::{GithubHandle, Provider};
+use tracing::{error, info, instrument};
+
+use crate::events::{actions::Action, pr_commands::BotScored, Event, EventType};
+
+use super::{
+    current_labels, pr_command_events, prometheus::PrometheusClient,
+    scores_from_comments_and_labels, CommentRepr, CommitStatus, LabelChange, LabelRepr,
+    Notification, PrMetadata, ProviderClient, RepoInfo, User,
+};
+
+const PER_PAGE: usize = 100;
+
+#[derive(Debug, Clone, Deserialize)]
+struct GitlabUser {
+    id: u64,
+    username: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Project {
+    path_with_namespace: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TodoTarget {
+    iid: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Todo {
+    id: u64,
+    action_name: String,
+    target_type: String,
+    project: Project,
+    target: TodoTarget,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProjectDetails {
+    default_branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BranchCommit {
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Branch {
+    commit: BranchCommit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    state: String,
+    description: Option<String>,
+    author: GitlabUser,
+    merged_by: Option<GitlabUser>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    merged_at: Option<chrono::DateTime<chrono::Utc>>,
+    sha: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MergeRequestReferences {
+    // Project path with the merge request number, e.g. `group/project!7`
+    full: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MergeRequestItem {
+    references: MergeRequestReferences,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LabelName {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LabelEvent {
+    user: GitlabUser,
+    created_at: chrono::DateTime<chrono::Utc>,
+    // Deleted labels are returned as null
+    label: Option<LabelName>,
+    action: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Note {
+    id: u64,
+    body: String,
+    author: GitlabUser,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    system: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SourceMergeRequest {
+    iid: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct StateEvent {
+    state: String,
+    source_merge_request: Option<SourceMergeRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Member {
+    access_level: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Approver {
+    user: GitlabUser,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Approvals {
+    #[serde(default)]
+    approved_by: Vec<Approver>,
+}
+
+impl GitlabUser {
+    // GitLab users are namespaced in the contract, so they can't take credit of the GitHub users
+    fn handle(&self) -> GithubHandle {
+        Provider::GitLab.user(&self.username)
+    }
+}
+
+impl Note {
+    fn into_comment(self, contributor_type: AuthorAssociation) -> CommentRepr {
+        CommentRepr {
+            id: self.id,
+            user: User::new(self.author.handle(), contributor_type),
+            timestamp: self.updated_at.unwrap_or(self.created_at),
+            text: self.body,
+            comment_id: Some(self.id),
+            edited: self
+                .updated_at
+                .is_some_and(|updated_at| updated_at > self.created_at),
+        }
+    }
+}
+
+/// GitLab merge requests support.
+/// Mentions are received from the to-do list, and the project and the users are namespaced as `gitlab:<name>` in the contract
+pub struct GitlabClient {
+    client: reqwest::Client,
+    url: String,
+    token: String,
+    prometheus: Arc<PrometheusClient>,
+    user_handle: String,
+    user_handles: BTreeSet<String>,
+}
+
+impl GitlabClient {
+    pub async fn new(
+        url: String,
+        token: String,
+        prometheus: Arc<PrometheusClient>,
+    ) -> anyhow::Result<Self> {
+        let mut client = Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            token,
+            prometheus,
+            user_handle: Default::default(),
+            user_handles: Default::default(),
+        };
+
+        let user: GitlabUser = client.get("/user").await?;
+        client.user_handle = user.handle();
+        client.user_handles.insert(user.handle());
+
+        Ok(client)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let text = self
+            .client
+            .get(format!("{}/api/v4{path}", self.url))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    async fn get_all<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<Vec<T>> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut result = vec![];
+        for page in 1.. {
+            let items: Vec<T> = self
+                .get(&format!("{path}{separator}per_page={PER_PAGE}&page={page}"))
+                .await?;
+            let last = items.len() < PER_PAGE;
+            result.extend(items);
+            if last {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        self.prometheus.add_write_request();
+        let text = self
+            .client
+            .request(method, format!("{}/api/v4{path}", self.url))
+            .header("PRIVATE-TOKEN", &self.token)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    // Returns None if the resource doesn't exist
+    async fn get_raw(&self, path: &str) -> anyhow::Result<Option<String>> {
+        let response = self
+            .client
+            .get(format!("{}/api/v4{path}", self.url))
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.text().await?))
+    }
+
+    // Project id in the URL is the url-encoded path of the project
+    fn project_path(repo_info: &RepoInfo) -> String {
+        let project = format!("{}/{}", repo_info.organization_name(), repo_info.repo);
+        format!("/projects/{}", project.replace('/', "%2F"))
+    }
+
+    // Messages mention the namespaced handles, but GitLab knows the users by their usernames
+    fn strip_mentions(text: &str) -> String {
+        text.replace(&format!("@{}", Provider::GITLAB_PREFIX), "@")
+    }
+
+    fn merge_request_path(repo_info: &RepoInfo) -> String {
+        format!(
+            "{}/merge_requests/{}",
+            Self::project_path(repo_info),
+            repo_info.number
+        )
+    }
+
+    fn repo_info(project: &Project, number: u64) -> Option<RepoInfo> {
+        let (group, repo) = project.path_with_namespace.rsplit_once('/')?;
+        let owner = Provider::GitLab.organization(group);
+        Some(RepoInfo {
+            full_id: format!("{owner}/{repo}/{number}"),
+            owner,
+            repo: repo.to_string(),
+            number,
+        })
+    }
+
+    // Open merge requests of the GitLab projects among the given ones
+    async fn get_merge_requests_in_projects(
+        &self,
+        repos: &[(String, String)],
+        filter: &str,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let mut result = vec![];
+        for (organization, repo) in repos {
+            let Some((Provider::GitLab, group)) = Provider::from_organization(organization) else {
+                continue;
+            };
+            let project = format!("{group}/{repo}").replace('/', "%2F");
+            let merge_requests: Vec<MergeRequestItem> = self
+                .get_all(&format!(
+                    "/projects/{project}/merge_requests?state=opened&{filter}"
+                ))
+                .await?;
+            result.extend(merge_requests.iter().filter_map(Self::item_repo_info));
+        }
+        Ok(result)
+    }
+
+    fn item_repo_info(merge_request: &MergeRequestItem) -> Option<RepoInfo> {
+        let (path, number) = merge_request.references.full.rsplit_once('!')?;
+        let project = Project {
+            path_with_namespace: path.to_string(),
+        };
+        Self::repo_info(&project, number.parse().ok()?)
+    }
+
+    // Maps project roles to the GitHub author associations that the commands expect
+    async fn contributor_type(&self, repo_info: &RepoInfo, user_id: u64) -> AuthorAssociation {
+        let member: anyhow::Result<Member> = self
+            .get(&format!(
+                "{}/members/all/{user_id}",
+                Self::project_path(repo_info)
+            ))
+            .await;
+        match member.map(|member| member.access_level) {
+            Ok(50..) => AuthorAssociation::Owner,
+            Ok(40..) => AuthorAssociation::Member,
+            Ok(30..) => AuthorAssociation::Collaborator,
+            Ok(_) => AuthorAssociation::Contributor,
+            Err(_) => AuthorAssociation::None,
+        }
+    }
+
+    async fn cached_contributor_type(
+        &self,
+        cache: &mut HashMap<u64, AuthorAssociation>,
+        repo_info: &RepoInfo,
+        user_id: u64,
+    ) -> AuthorAssociation {
+        if let Some(contributor_type) = cache.get(&user_id) {
+            return contributor_type.clone();
+        }
+        let contributor_type = self.contributor_type(repo_info, user_id).await;
+        cache.insert(user_id, contributor_type.clone());
+        contributor_type
+    }
+
+    async fn merge_request(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<(PrMetadata, Option<GithubHandle>)> {
+        let merge_request: MergeRequest = self.get(&Self::merge_request_path(repo_info)).await?;
+        let contributor_type = self
+            .contributor_type(repo_info, merge_request.author.id)
+            .await;
+
+        let pr_metadata = PrMetadata {
+            repo_info: RepoInfo {
+                number: merge_request.iid,
+                ..repo_info.clone()
+            },
+            author: User::new(merge_request.author.handle(), contributor_type),
+            created: merge_request.created_at,
+            merged: merge_request.merged_at,
+            updated_at: merge_request.updated_at,
+            body: merge_request.description.unwrap_or_default(),
+            closed: merge_request.state != "opened",
+            head_sha: merge_request.sha,
+            labels: Some(merge_request.labels),
+            draft: merge_request.draft,
+        };
+        Ok((
+            pr_metadata,
+            merge_request.merged_by.map(|user| user.handle()),
+        ))
+    }
+
+    // Comments in chronological order without the system notes
+    async fn comments(&self, repo_info: &RepoInfo) -> anyhow::Result<Vec<CommentRepr>> {
+        let notes: Vec<Note> = self
+            .get_all(&format!(
+                "{}/notes?sort=asc&order_by=created_at",
+                Self::merge_request_path(repo_info)
+            ))
+            .await?;
+
+        let mut contributor_types: HashMap<u64, AuthorAssociation> = HashMap::new();
+        let mut comments = vec![];
+        for note in notes.into_iter().filter(|note| !note.system) {
+            let contributor_type = self
+                .cached_contributor_type(&mut contributor_types, repo_info, note.author.id)
+                .await;
+            comments.push(note.into_comment(contributor_type));
+        }
+        comments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(comments)
+    }
+
+    async fn parse_todo(&self, todo: Todo) -> Option<Vec<Event>> {
+        let notification = Notification {
+            id: todo.id,
+            read_client_id: 0,
+        };
+
+        let repo_info = match Self::repo_info(&todo.project, todo.target.iid) {
+            Some(repo_info) if todo.target_type == "MergeRequest" => repo_info,
+            _ => {
+                info!(
+                    "Skipping to-do: {} with action {}",
+                    todo.target_type, todo.action_name
+                );
+                if let Err(e) = self.mark_notification_as_read(notification).await {
+                    error!("Failed to mark to-do {} as done: {e:?}", todo.id);
+                }
+                return None;
+            }
+        };
+
+        let (pr_metadata, merged_by) = match self.merge_request(&repo_info).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to get merge request: {:?}", e);
+                return None;
+            }
+        };
+
+        if pr_metadata.merged.is_none() && pr_metadata.closed {
+            info!("Merge request is closed: {}", pr_metadata.repo_info.full_id);
+            if let Err(e) = self.mark_notification_as_read(notification).await {
+                error!("Failed to mark to-do {} as done: {e:?}", todo.id);
+            }
+
+            return Some(vec![Event {
+                event: EventType::Action {
+                    action: Action::stale(),
+                    pr: pr_metadata.clone(),
+                },
+                comment: None,
+                event_time: pr_metadata.updated_at,
+            }]);
+        }
+
+        let comments = match self.comments(&repo_info).await {
+            Ok(comments) => comments,
+            Err(e) => {
+                error!("Failed to get notes: {:?}", e);
+                return None;
+            }
+        };
+        let labels = match self.get_labels(&repo_info).await {
+            Ok(labels) => labels,
+            Err(e) => {
+                error!("Failed to get label events: {:?}", e);
+                return None;
+            }
+        };
+        let first_bot_comment = comments
+            .iter()
+            .find(|c| c.user.login == self.user_handle)
+            .cloned();
+
+        let (mut results, rescore) = pr_command_events(
+            &self.user_handles,
+            &pr_metadata,
+            comments,
+            &labels,
+            first_bot_comment.clone(),
+            notification,
+        );
+
+        if results.is_empty() {
+            info!(
+                "No commands found in merge request: {}",
+                pr_metadata.repo_info.full_id
+            );
+            if let Err(e) = self.mark_notification_as_read(notification).await {
+                error!("Failed to mark to-do {} as done: {e:?}", todo.id);
+            }
+        }
+        results.extend(rescore);
+
+        if let Some(merged) = pr_metadata.merged {
+            let reviewers = self
+                .get_positive_or_pending_review(&repo_info)
+                .await
+                .unwrap_or_default();
+            let merged_by = merged_by.unwrap_or_else(|| pr_metadata.author.login.clone());
+
+            results.push(Event {
+                event: EventType::Action {
+                    action: Action::merge(merged_by, reviewers),
+                    pr: pr_metadata,
+                },
+                comment: first_bot_comment,
+                event_time: merged,
+            });
+        }
+
+        Some(results)
+    }
+}
+
+#[async_trait]
+impl ProviderClient for GitlabClient {
+    #[instrument(skip(self))]
+    async fn get_events(&self) -> anyhow::Result<Vec<Event>> {
+        let todos: Vec<Todo> = self.get_all("/todos?state=pending").await?;
+
+        let futures = todos.into_iter().map(|todo| self.parse_todo(todo));
+
+        Ok(join_all(futures)
+            .await
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect())
+    }
+
+    async fn get_pull_request_metadata(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<(PrMetadata, Option<GithubHandle>)> {
+        self.merge_request(repo_info).await
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_closed_issues(
+        &self,
+        repo_info: &RepoInfo,
+        issues: &[u64],
+    ) -> anyhow::Result<Vec<u64>> {
+        let mut result = vec![];
+        for issue in issues {
+            let events: Vec<StateEvent> = self
+                .get_all(&format!(
+                    "{}/issues/{issue}/resource_state_events",
+                    Self::project_path(repo_info)
+                ))
+                .await?;
+            if events.into_iter().any(|event| {
+                event.state == "closed"
+                    && event
+                        .source_merge_request
+                        .is_some_and(|merge_request| merge_request.iid == repo_info.number)
+            }) {
+                result.push(*issue);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn get_positive_or_pending_review(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<Vec<GithubHandle>> {
+        let approvals: Approvals = self
+            .get(&format!(
+                "{}/approvals",
+                Self::merge_request_path(repo_info)
+            ))
+            .await?;
+        Ok(approvals
+            .approved_by
+            .into_iter()
+            .map(|approver| approver.user.handle())
+            .collect())
+    }
+
+    async fn get_scores_and_active_pr_status(
+        &self,
+        pr_metadata: &PrMetadata,
+    ) -> anyhow::Result<(Vec<(BotScored, User)>, bool)> {
+        let comments = self.comments(&pr_metadata.repo_info).await?;
+        let labels = self.get_labels(&pr_metadata.repo_info).await?;
+        let scores =
+            scores_from_comments_and_labels(&self.user_handles, pr_metadata, &comments, &labels);
+
+        let active = comments
+            .iter()
+            .filter(|c| {
+                !self.user_handles.contains(&c.user.login)
+                    && c.user.login != pr_metadata.author.login
+            })
+            .count()
+            >= 2;
+
+        Ok((scores, active))
+    }
+
+    #[instrument(skip(self, text), fields(pr = repo_info.full_id))]
+    async fn reply(&self, repo_info: &RepoInfo, text: &str) -> anyhow::Result<CommentRepr> {
+        let note: Note = self
+            .send(
+                reqwest::Method::POST,
+                &format!("{}/notes", Self::merge_request_path(repo_info)),
+                json!({ "body": Self::strip_mentions(text) }),
+            )
+            .await?;
+        Ok(note.into_comment(AuthorAssociation::None))
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn like_comment(&self, repo_info: &RepoInfo, comment_id: u64) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::POST,
+                &format!(
+                    "{}/notes/{comment_id}/award_emoji",
+                    Self::merge_request_path(repo_info)
+                ),
+                json!({ "name": "thumbsup" }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self, text), fields(pr = repo_info.full_id))]
+    async fn edit_comment(
+        &self,
+        repo_info: &RepoInfo,
+        comment_id: u64,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::PUT,
+                &format!("{}/notes/{comment_id}", Self::merge_request_path(repo_info)),
+                json!({ "body": Self::strip_mentions(text) }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_bot_comment(&self, repo_info: &RepoInfo) -> anyhow::Result<Option<CommentRepr>> {
+        let notes: Vec<Note> = self
+            .get_all(&format!(
+                "{}/notes?sort=asc&order_by=created_at",
+                Self::merge_request_path(repo_info)
+            ))
+            .await?;
+        Ok(notes
+            .into_iter()
+            .find(|note| note.author.handle() == self.user_handle)
+            .map(|note| note.into_comment(AuthorAssociation::None)))
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_comments_since(
+        &self,
+        repo_info: &RepoInfo,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<CommentRepr>> {
+        let notes: Vec<Note> = self
+            .get_all(&format!(
+                "{}/notes?sort=asc&order_by=updated_at",
+                Self::merge_request_path(repo_info)
+            ))
+            .await?;
+        Ok(notes
+            .into_iter()
+            .filter(|note| !note.system)
+            .map(|note| note.into_comment(AuthorAssociation::None))
+            .filter(|comment| comment.timestamp >= since)
+            .collect())
+    }
+
+    // GitLab users are tracked by the handle only
+    async fn get_user_id(&self, _login: &str) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
+    async fn mark_notification_as_read(&self, notification: Notification) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::POST,
+                &format!("/todos/{}/mark_as_done", notification.id),
+                json!({}),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_default_branch_sha(&self, repo_info: &RepoInfo) -> anyhow::Result<String> {
+        let project_path = Self::project_path(repo_info);
+        let project: ProjectDetails = self.get(&project_path).await?;
+        let default_branch = project
+            .default_branch
+            .ok_or_else(|| anyhow::anyhow!("Project {} has no default branch", repo_info.repo))?;
+        let branch: Branch = self
+            .get(&format!(
+                "{project_path}/repository/branches/{}",
+                default_branch.replace('/', "%2F")
+            ))
+            .await?;
+        Ok(branch.commit.id)
+    }
+
+    async fn get_file(
+        &self,
+        repo_info: &RepoInfo,
+        path: &str,
+        sha: &str,
+    ) -> anyhow::Result<Option<String>> {
+        self.get_raw(&format!(
+            "{}/repository/files/{}/raw?ref={sha}",
+            Self::project_path(repo_info),
+            path.replace('/', "%2F")
+        ))
+        .await
+    }
+
+    #[instrument(skip(self, status), fields(pr = repo_info.full_id))]
+    async fn set_commit_status(
+        &self,
+        repo_info: &RepoInfo,
+        sha: &str,
+        status: &CommitStatus,
+    ) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::POST,
+                &format!("{}/statuses/{sha}", Self::project_path(repo_info)),
+                json!({
+                    "state": "success",
+                    "name": CommitStatus::CONTEXT,
+                    "description": status.description,
+                    "target_url": status.target_url,
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_labels(&self, repo_info: &RepoInfo) -> anyhow::Result<Vec<LabelRepr>> {
+        let events: Vec<LabelEvent> = self
+            .get_all(&format!(
+                "{}/resource_label_events",
+                Self::merge_request_path(repo_info)
+            ))
+            .await?;
+
+        let mut contributor_types = HashMap::new();
+        let mut changes = vec![];
+        for event in events {
+            let Some(label) = event.label else {
+                continue;
+            };
+            let contributor_type = self
+                .cached_contributor_type(&mut contributor_types, repo_info, event.user.id)
+                .await;
+            changes.push(LabelChange {
+                label: LabelRepr {
+                    name: label.name,
+                    user: User::new(event.user.handle(), contributor_type),
+                    timestamp: event.created_at,
+                },
+                added: event.action == "add",
+            });
+        }
+        changes.sort_by(|a, b| a.label.timestamp.cmp(&b.label.timestamp));
+        Ok(current_labels(changes.into_iter()))
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn add_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::PUT,
+                &Self::merge_request_path(repo_info),
+                json!({ "add_labels": label }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn remove_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()> {
+        let _: serde_json::Value = self
+            .send(
+                reqwest::Method::PUT,
+                &Self::merge_request_path(repo_info),
+                json!({ "remove_labels": label }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_labeled_pull_requests(
+        &self,
+        label: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let merge_requests: Vec<MergeRequestItem> = self
+            .get_all(&format!(
+                "/merge_requests?scope=all&state=opened&labels={label}&updated_after={}",
+                since.format("%Y-%m-%dT%H:%M:%SZ")
+            ))
+            .await?;
+
+        Ok(merge_requests
+            .iter()
+            .filter_map(Self::item_repo_info)
+            .collect())
+    }
+
+    async fn get_new_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let filter = format!("created_after={}", since.format("%Y-%m-%dT%H:%M:%SZ"));
+        self.get_merge_requests_in_projects(repos, &filter).await
+    }
+
+    async fn get_ready_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let filter = format!(
+            "wip=no&updated_after={}",
+            since.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        self.get_merge_requests_in_projects(repos, &filter).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::events::{actions::Action, pr_commands::Command, EventType};
+
+    use super::{CommitStatus, GitlabClient, ProviderClient, RepoInfo};
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    // Serves canned JSON responses by the method and path without query and records the requests
+    async fn mock_server(
+        routes: Vec<(&'static str, &'static str, &'static str)>,
+    ) -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Requests = Default::default();
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 16 * 1024];
+                let mut read = 0;
+                while !String::from_utf8_lossy(&buffer[..read]).contains("\r\n\r\n") {
+                    read += socket.read(&mut buffer[read..]).await.unwrap();
+                }
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let mut line = request.lines().next().unwrap().split_whitespace();
+                let method = line.next().unwrap().to_string();
+                let path = line.next().unwrap().split('?').next().unwrap().to_string();
+                recorded.lock().unwrap().push(format!("{method} {path}"));
+
+                let (status, body) = routes
+                    .iter()
+                    .find(|(m, p, _)| *m == method && *p == path)
+                    .map(|(_, _, body)| ("200 OK", *body))
+                    .unwrap_or(("404 Not Found", "{}"));
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    const MR: &str = "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7";
+
+    fn routes() -> Vec<(&'static str, &'static str, &'static str)> {
+        vec![
+            ("GET", "/api/v4/user", r#"{"id": 1, "username": "sloth"}"#),
+            (
+                "GET",
+                "/api/v4/todos",
+                r#"[{"id": 3, "action_name": "mentioned", "target_type": "MergeRequest",
+                     "project": {"path_with_namespace": "near/sub/bot"}, "target": {"iid": 7}}]"#,
+            ),
+            (
+                "GET",
+                MR,
+                r#"{"iid": 7, "state": "merged", "description": "Fixes the bot",
+                    "author": {"id": 2, "username": "author"}, "merged_by": {"id": 4, "username": "maintainer"},
+                    "created_at": "2024-10-01T10:00:00Z", "updated_at": "2024-10-02T10:00:00Z",
+                    "merged_at": "2024-10-02T10:00:00Z", "sha": "abc"}"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7/notes",
+                r#"[{"id": 10, "body": "@sloth include", "author": {"id": 2, "username": "author"},
+                     "created_at": "2024-10-01T11:00:00Z", "updated_at": "2024-10-01T11:00:00Z", "system": false},
+                    {"id": 11, "body": "@sloth score 8", "author": {"id": 4, "username": "maintainer"},
+                     "created_at": "2024-10-01T12:00:00Z", "updated_at": "2024-10-01T12:00:00Z", "system": false},
+                    {"id": 12, "body": "approved this merge request", "author": {"id": 4, "username": "maintainer"},
+                     "created_at": "2024-10-01T13:00:00Z", "updated_at": "2024-10-01T13:00:00Z", "system": true}]"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7/resource_label_events",
+                r#"[{"user": {"id": 4, "username": "maintainer"}, "created_at": "2024-10-01T12:30:00Z",
+                     "label": {"name": "sloth-score:5"}, "action": "add"},
+                    {"user": {"id": 4, "username": "maintainer"}, "created_at": "2024-10-01T12:40:00Z",
+                     "label": {"name": "race-of-sloths"}, "action": "add"},
+                    {"user": {"id": 4, "username": "maintainer"}, "created_at": "2024-10-01T12:50:00Z",
+                     "label": {"name": "sloth-score:5"}, "action": "remove"}]"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/members/all/4",
+                r#"{"access_level": 40}"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7/approvals",
+                r#"{"approved_by": [{"user": {"id": 4, "username": "maintainer"}}]}"#,
+            ),
+            (
+                "POST",
+                "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7/notes/11/award_emoji",
+                r#"{"id": 1, "name": "thumbsup"}"#,
+            ),
+            (
+                "POST",
+                "/api/v4/projects/near%2Fsub%2Fbot/merge_requests/7/notes",
+                r#"{"id": 13, "body": "Hello", "author": {"id": 1, "username": "sloth"},
+                    "created_at": "2024-10-03T10:00:00Z", "updated_at": "2024-10-03T10:00:00Z"}"#,
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn merge_request_events() {
+        let (url, _) = mock_server(routes()).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+
+        let events = client.get_events().await.unwrap();
+        assert_eq!(events.len(), 3);
+
+        let EventType::PRCommand {
+            command: Command::Include(_),
+            pr,
+            ..
+        } = &events[0].event
+        else {
+            panic!("Expected include command")
+        };
+        assert_eq!(pr.repo_info.full_id, "gitlab:near/sub/bot/7");
+        assert_eq!(pr.repo_info.organization_name(), "near/sub");
+        assert_eq!(pr.author.login, "gitlab:author");
+        assert!(pr.author.is_participant());
+        assert_eq!(pr.head_sha.as_deref(), Some("abc"));
+        assert!(!pr.draft);
+
+        let EventType::PRCommand {
+            command: Command::Score(score),
+            sender,
+            ..
+        } = &events[1].event
+        else {
+            panic!("Expected score command")
+        };
+        assert_eq!(score.score(shared::ScoringScale::Fibonacci), (8, false));
+        assert_eq!(score.comment_id, Some(11));
+        assert!(sender.is_maintainer());
+
+        let EventType::Action {
+            action: Action::Merge(merge),
+            ..
+        } = &events[2].event
+        else {
+            panic!("Expected merge action")
+        };
+        assert_eq!(merge.merger, "gitlab:maintainer");
+        assert_eq!(merge.reviewers, vec!["gitlab:maintainer".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn replies_with_notes_and_emoji() {
+        let (url, requests) = mock_server(routes()).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+        let repo_info = RepoInfo {
+            owner: "gitlab:near/sub".to_string(),
+            repo: "bot".to_string(),
+            number: 7,
+            full_id: "gitlab:near/sub/bot/7".to_string(),
+        };
+
+        client.like_comment(&repo_info, 11).await.unwrap();
+        let comment = client.reply(&repo_info, "Hello").await.unwrap();
+        assert_eq!(comment.comment_id, Some(13));
+        assert_eq!(comment.user.login, "gitlab:sloth");
+
+        assert_eq!(
+            GitlabClient::strip_mentions("Welcome @gitlab:author and @gitlab:co-author"),
+            "Welcome @author and @co-author"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert!(requests.contains(&format!("POST {MR}/notes/11/award_emoji")));
+        assert!(requests.contains(&format!("POST {MR}/notes")));
+    }
+
+    #[tokio::test]
+    async fn publishes_commit_status() {
+        let mut routes = routes();
+        routes.push((
+            "POST",
+            "/api/v4/projects/near%2Fsub%2Fbot/statuses/abc",
+            r#"{"id": 1, "status": "success"}"#,
+        ));
+        let (url, requests) = mock_server(routes).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+        let repo_info = RepoInfo {
+            owner: "gitlab:near/sub".to_string(),
+            repo: "bot".to_string(),
+            number: 7,
+            full_id: "gitlab:near/sub/bot/7".to_string(),
+        };
+
+        let status = CommitStatus {
+            description: "waiting for scoring".to_string(),
+            target_url: "https://race-of-sloths.com".to_string(),
+        };
+        client
+            .set_commit_status(&repo_info, "abc", &status)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(
+            requests.contains(&"POST /api/v4/projects/near%2Fsub%2Fbot/statuses/abc".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn labels_and_labeled_merge_requests() {
+        let mut routes = routes();
+        routes.push((
+            "GET",
+            "/api/v4/merge_requests",
+            r#"[{"iid": 7, "references": {"full": "near/sub/bot!7"}}]"#,
+        ));
+        let (url, _) = mock_server(routes).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+
+        let prs = client
+            .get_labeled_pull_requests("race-of-sloths", chrono::Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].full_id, "gitlab:near/sub/bot/7");
+
+        let labels = client.get_labels(&prs[0]).await.unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name, "race-of-sloths");
+        assert!(labels[0].user.is_maintainer());
+    }
+
+    #[tokio::test]
+    async fn new_merge_requests_of_allowlisted_projects() {
+        let mut routes = routes();
+        routes.push((
+            "GET",
+            "/api/v4/projects/near%2Fsub%2Fbot/merge_requests",
+            r#"[{"iid": 8, "references": {"full": "near/sub/bot!8"}}]"#,
+        ));
+        let (url, requests) = mock_server(routes).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+
+        let repos = vec![
+            ("gitlab:near/sub".to_string(), "bot".to_string()),
+            ("near".to_string(), "github-repo".to_string()),
+        ];
+        let prs = client
+            .get_new_pull_requests(&repos, chrono::Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].full_id, "gitlab:near/sub/bot/8");
+
+        let prs = client
+            .get_ready_pull_requests(&repos, chrono::Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(prs.len(), 1);
+
+        // Repositories of other providers are skipped
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|request| request.contains("merge_requests"))
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_repository_files() {
+        let mut routes = routes();
+        routes.extend([
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot",
+                r#"{"default_branch": "main"}"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/repository/branches/main",
+                r#"{"commit": {"id": "abc"}}"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/repository/files/.gitlab%2Frace-of-sloths.toml/raw",
+                "stale_days = 3",
+            ),
+        ]);
+        let (url, _) = mock_server(routes).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+        let repo_info = RepoInfo {
+            owner: "gitlab:near/sub".to_string(),
+            repo: "bot".to_string(),
+            number: 7,
+            full_id: "gitlab:near/sub/bot/7".to_string(),
+        };
+
+        let sha = client.get_default_branch_sha(&repo_info).await.unwrap();
+        assert_eq!(sha, "abc");
+        let config = client
+            .get_file(&repo_info, ".gitlab/race-of-sloths.toml", &sha)
+            .await
+            .unwrap();
+        assert_eq!(config.as_deref(), Some("stale_days = 3"));
+        let missing = client.get_file(&repo_info, "missing.toml", &sha).await;
+        assert_eq!(missing.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn issues_closed_by_the_merge() {
+        let mut routes = routes();
+        routes.extend([
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/issues/3/resource_state_events",
+                r#"[{"state": "closed", "source_merge_request": {"iid": 7}}]"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/issues/4/resource_state_events",
+                r#"[{"state": "closed", "source_merge_request": null}]"#,
+            ),
+            (
+                "GET",
+                "/api/v4/projects/near%2Fsub%2Fbot/issues/5/resource_state_events",
+                r#"[{"state": "closed", "source_merge_request": {"iid": 8}}]"#,
+            ),
+        ]);
+        let (url, _) = mock_server(routes).await;
+        let client = GitlabClient::new(url, "token".to_string(), Default::default())
+            .await
+            .unwrap();
+        let repo_info = RepoInfo {
+            owner: "gitlab:near/sub".to_string(),
+            repo: "bot".to_string(),
+            number: 7,
+            full_id: "gitlab:near/sub/bot/7".to_string(),
+        };
+
+        // Issues closed manually or by another merge request can't be claimed
+        let closed = client
+            .get_closed_issues(&repo_info, &[3, 4, 5])
+            .await
+            .unwrap();
+        assert_eq!(closed, vec![3]);
+    }
+}
@@ -6,33 +6,156 @@ use std::{
     },
 };
 
+use async_trait::async_trait;
 use futures::future::join_all;
 use octocrab::models::{
     activity::Notification as GithubNotification,
     issues::{Comment, Issue},
     pulls::{PullRequest, Review, ReviewState},
-    AuthorAssociation, CommentId, NotificationId, RateLimit,
+    AuthorAssociation, CommentId, Event as IssueEvent, NotificationId, RateLimit, StatusState,
 };
 use shared::GithubHandle;
 use tracing::{error, info, instrument};
 
 use crate::events::{
     actions::Action,
-    issue_commands,
+    issue_commands, labels,
     pr_commands::{BotScored, Command},
-    Event, EventType,
+    Event, EventType, PAUSED_MARKER,
 };
 
 pub use shared::github::*;
 
+pub mod gitlab;
 pub mod prometheus;
 
+pub use gitlab::GitlabClient;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Notification {
-    pub id: NotificationId,
+    pub id: u64,
     pub read_client_id: usize,
 }
 
+/// Status of the PR published on its last commit, so it's visible next to the CI checks.
+/// We use commit statuses as check runs can be created only by GitHub Apps.
+/// The status is always successful, so it doesn't look like a running check or block the merge:
+/// the description tells where the PR is in the race
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitStatus {
+    pub description: String,
+    pub target_url: String,
+}
+
+impl CommitStatus {
+    pub const CONTEXT: &'static str = "Race of Sloths";
+}
+
+/// Code hosting provider the bot listens to and replies on
+#[async_trait]
+pub trait ProviderClient: Send + Sync {
+    async fn get_events(&self) -> anyhow::Result<Vec<Event>>;
+
+    // Returns the PR metadata and the merger
+    async fn get_pull_request_metadata(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<(PrMetadata, Option<GithubHandle>)>;
+
+    // Issues among the given ones that were closed by merging the PR.
+    // The PR description can be edited after the merge, so the references in it are not enough
+    async fn get_closed_issues(
+        &self,
+        repo_info: &RepoInfo,
+        issues: &[u64],
+    ) -> anyhow::Result<Vec<u64>>;
+
+    async fn get_positive_or_pending_review(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<Vec<GithubHandle>>;
+
+    /// Active PR is the PR where there are >2 messages from other users (exculding us and the author)
+    async fn get_scores_and_active_pr_status(
+        &self,
+        pr_metadata: &PrMetadata,
+    ) -> anyhow::Result<(Vec<(BotScored, User)>, bool)>;
+
+    async fn reply(&self, repo_info: &RepoInfo, text: &str) -> anyhow::Result<CommentRepr>;
+
+    async fn like_comment(&self, repo_info: &RepoInfo, comment_id: u64) -> anyhow::Result<()>;
+
+    async fn edit_comment(
+        &self,
+        repo_info: &RepoInfo,
+        comment_id: u64,
+        text: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn get_bot_comment(&self, repo_info: &RepoInfo) -> anyhow::Result<Option<CommentRepr>>;
+
+    // Comments created or edited after the given time
+    async fn get_comments_since(
+        &self,
+        repo_info: &RepoInfo,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<CommentRepr>>;
+
+    async fn mark_notification_as_read(&self, notification: Notification) -> anyhow::Result<()>;
+
+    // Id of the user that survives renames. None if the provider doesn't have one or the user doesn't exist
+    async fn get_user_id(&self, login: &str) -> anyhow::Result<Option<u64>>;
+
+    // Commit of the default branch, the repository configuration is cached by it
+    async fn get_default_branch_sha(&self, repo_info: &RepoInfo) -> anyhow::Result<String>;
+
+    // Returns None if the file doesn't exist at the given commit
+    async fn get_file(
+        &self,
+        repo_info: &RepoInfo,
+        path: &str,
+        sha: &str,
+    ) -> anyhow::Result<Option<String>>;
+
+    // Replaces the previous status of the bot on the commit
+    async fn set_commit_status(
+        &self,
+        repo_info: &RepoInfo,
+        sha: &str,
+        status: &CommitStatus,
+    ) -> anyhow::Result<()>;
+
+    // Labels that are on the PR now with the users who added them, in chronological order
+    async fn get_labels(&self, repo_info: &RepoInfo) -> anyhow::Result<Vec<LabelRepr>>;
+
+    async fn add_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()>;
+
+    // Succeeds if the PR doesn't have the label
+    async fn remove_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()>;
+
+    // Open PRs with the label that were updated after the given time
+    async fn get_labeled_pull_requests(
+        &self,
+        label: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>>;
+
+    // Open PRs of the given (organization, repo) pairs that were created after the given time
+    async fn get_new_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>>;
+
+    // Open PRs of the given repos that aren't drafts and were updated after the given time.
+    // It may skip the PRs without our comments
+    async fn get_ready_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>>;
+}
+
 pub struct GithubClient {
     event_clients: Vec<octocrab::Octocrab>,
     client: octocrab::Octocrab,
@@ -50,15 +173,35 @@ pub struct CommentRepr {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub text: String,
     pub comment_id: Option<u64>,
+    pub edited: bool,
+}
+
+/// Label on the PR with the user who added it
+#[derive(Debug, Clone)]
+pub struct LabelRepr {
+    pub name: String,
+    pub user: User,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+// Label added or removed from the PR
+#[derive(Debug, Clone)]
+pub(crate) struct LabelChange {
+    pub label: LabelRepr,
+    pub added: bool,
 }
 
 impl From<Comment> for CommentRepr {
     fn from(comment: Comment) -> Self {
         Self {
             id: comment.id.0,
-            user: User::new(comment.user.login, comment.author_association),
+            user: User::new(comment.user.login, comment.author_association)
+                .with_id(comment.user.id.0),
             timestamp: comment.updated_at.unwrap_or(comment.created_at),
             comment_id: Some(comment.id.0),
+            edited: comment
+                .updated_at
+                .is_some_and(|updated_at| updated_at > comment.created_at),
             text: comment
                 .body
                 .or(comment.body_html)
@@ -74,9 +217,10 @@ impl TryFrom<Review> for CommentRepr {
         let user = review.user.ok_or(())?;
         Ok(Self {
             id: review.id.0,
-            user: User::new(user.login, AuthorAssociation::Contributor),
+            user: User::new(user.login, AuthorAssociation::Contributor).with_id(user.id.0),
             timestamp: review.submitted_at.unwrap_or_else(chrono::Utc::now),
             comment_id: None,
+            edited: false,
             text: review
                 .body
                 .or(review.body_html)
@@ -126,75 +270,58 @@ impl GithubClient {
         &self.write_client_handle
     }
 
-    #[instrument(skip(self))]
-    pub async fn get_events(&self) -> anyhow::Result<Vec<Event>> {
-        let current_client_id =
-            self.atomic_read_counter.fetch_add(1, Ordering::SeqCst) % self.event_clients.len();
-        let client = &self.event_clients[current_client_id];
-        let page = client
-            .activity()
-            .notifications()
-            .list()
-            .all(false)
-            .participating(true)
-            .per_page(50)
-            .page(0)
-            .send()
-            .await?;
-
-        let events = client.all_pages(page).await?;
-
-        let fetch_pr_futures = events.into_iter().map(|event| async move {
-            if event.reason != "mention" && event.reason != "state_change" {
-                info!(
-                    "Skipping event: {} with reason {}",
-                    event.subject.r#type, event.reason
-                );
-                if let Err(e) = self
-                    .mark_notification_as_read(Notification {
-                        id: event.id,
-                        read_client_id: current_client_id,
-                    })
-                    .await
-                {
-                    error!(
-                        "Failed to mark notification as read for event: {:?}: {e:?}",
-                        event.id
-                    );
-                }
-                return None;
+    // The search query is limited by length, so the repositories are split into several queries
+    async fn search_pull_requests_in_repos(
+        &self,
+        base: String,
+        repos: &[(String, String)],
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        const MAX_QUERY_LENGTH: usize = 256;
+
+        let mut queries = vec![];
+        let mut query = base.clone();
+        for (owner, repo) in repos {
+            let qualifier = format!(" repo:{owner}/{repo}");
+            if query.len() + qualifier.len() > MAX_QUERY_LENGTH && query != base {
+                queries.push(std::mem::replace(&mut query, base.clone()));
             }
+            query.push_str(&qualifier);
+        }
+        if query != base {
+            queries.push(query);
+        }
 
-            if event.subject.r#type == "PullRequest" {
-                self.parse_pr_event(current_client_id, event).await
-            } else if event.subject.r#type == "Issue" {
-                self.parse_issue_event(current_client_id, event).await
-            } else {
-                info!(
-                    "Skipping event: {} with reason {}",
-                    event.subject.r#type, event.reason
-                );
-                if let Err(e) = self
-                    .mark_notification_as_read(Notification {
-                        id: event.id,
-                        read_client_id: current_client_id,
-                    })
-                    .await
-                {
-                    error!(
-                        "Failed to mark notification as read for event: {:?}: {e:?}",
-                        event.id
-                    );
-                }
-                return None;
-            }
-        });
+        let mut result = vec![];
+        for query in queries {
+            result.extend(self.search_pull_requests(&query).await?);
+        }
+        Ok(result)
+    }
 
-        Ok(join_all(fetch_pr_futures)
-            .await
+    async fn search_pull_requests(&self, query: &str) -> anyhow::Result<Vec<RepoInfo>> {
+        let page = self
+            .client
+            .search()
+            .issues_and_pull_requests(query)
+            .per_page(100)
+            .send()
+            .await?;
+        let issues = self.client.all_pages(page).await?;
+
+        Ok(issues
             .into_iter()
-            .flatten()
-            .flatten()
+            .filter_map(|issue| {
+                // The URL ends with `/repos/<owner>/<repo>`
+                let mut path = issue.repository_url.path_segments()?.rev();
+                let repo = path.next()?.to_string();
+                let owner = path.next()?.to_string();
+                Some(RepoInfo {
+                    full_id: format!("{owner}/{repo}/{}", issue.number),
+                    owner,
+                    repo,
+                    number: issue.number,
+                })
+            })
             .collect())
     }
 
@@ -204,7 +331,7 @@ impl GithubClient {
         event: GithubNotification,
     ) -> Option<Vec<Event>> {
         let notification = Notification {
-            id: event.id,
+            id: event.id.0,
             read_client_id: client_id,
         };
 
@@ -254,8 +381,11 @@ impl GithubClient {
         let mut results = Vec::new();
 
         for comment in comments.into_iter().map(CommentRepr::from).rev() {
-            // We have processed older messages
+            // We have processed older messages, unless they were answered while the contract was paused
             if self.user_handles.contains(&comment.user.login) {
+                if comment.text.contains(PAUSED_MARKER) {
+                    continue;
+                }
                 break;
             }
 
@@ -292,7 +422,7 @@ impl GithubClient {
         event: GithubNotification,
     ) -> Option<Vec<Event>> {
         let notification = Notification {
-            id: event.id,
+            id: event.id.0,
             read_client_id: client_id,
         };
 
@@ -376,6 +506,14 @@ impl GithubClient {
             }
         };
 
+        let labels = match self.get_labels(&pr_metadata.repo_info).await {
+            Ok(labels) => labels,
+            Err(e) => {
+                error!("Failed to get labels: {:?}", e);
+                return None;
+            }
+        };
+
         let mut comments = comments
             .into_iter()
             .map(CommentRepr::from)
@@ -383,46 +521,14 @@ impl GithubClient {
             .collect::<Vec<_>>();
         comments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
 
-        let mut results = Vec::new();
-
-        for comment in comments.into_iter().rev() {
-            // We have processed older messages
-            if self.user_handles.contains(&comment.user.login) {
-                break;
-            }
-
-            for handle in self.user_handles.iter() {
-                if let Some(command) = Command::parse_command(handle, &pr_metadata, &comment) {
-                    results.push(Event {
-                        event: EventType::PRCommand {
-                            command,
-                            notification,
-                            sender: comment.user.clone(),
-                            pr: pr_metadata.clone(),
-                        },
-                        comment: first_bot_comment.clone(),
-                        event_time: comment.timestamp,
-                    });
-                    break;
-                }
-            }
-        }
-
-        for handle in self.user_handles.iter() {
-            if let Some(command) = Command::parse_body(handle, &pr_metadata) {
-                results.push(Event {
-                    event: EventType::PRCommand {
-                        command,
-                        notification,
-                        sender: pr_metadata.author.clone(),
-                        pr: pr_metadata.clone(),
-                    },
-                    comment: first_bot_comment.clone(),
-                    event_time: pr_metadata.created,
-                });
-                break;
-            }
-        }
+        let (mut results, rescore) = pr_command_events(
+            &self.user_handles,
+            &pr_metadata,
+            comments,
+            &labels,
+            first_bot_comment.clone(),
+            notification,
+        );
 
         if results.is_empty() {
             info!("No commands found in PR: {}", pr_metadata.repo_info.number);
@@ -430,17 +536,11 @@ impl GithubClient {
                 error!("Failed to mark notification as read: {:?}", e);
             }
         }
-
-        // To keep the chronological order, we reverse the results
-        results.reverse();
+        results.extend(rescore);
 
         if pr_metadata.merged.is_some() {
             let reviewers = self
-                .get_positive_or_pending_review(
-                    &pr_metadata.repo_info.owner,
-                    &pr_metadata.repo_info.repo,
-                    pr_metadata.repo_info.number,
-                )
+                .get_positive_or_pending_review(&pr_metadata.repo_info)
                 .await
                 .unwrap_or_default();
             let merged_by = merged_by
@@ -460,28 +560,6 @@ impl GithubClient {
         Some(results)
     }
 
-    pub async fn get_positive_or_pending_review(
-        &self,
-        owner: &str,
-        repo: &str,
-        number: u64,
-    ) -> anyhow::Result<Vec<GithubHandle>> {
-        Ok(self
-            .client
-            .pulls(owner, repo)
-            .list_reviews(number)
-            .per_page(10)
-            .send()
-            .await?
-            .take_items()
-            .into_iter()
-            .flat_map(|e| match e.state {
-                Some(ReviewState::Pending) | Some(ReviewState::Approved) => e.user.map(|u| u.login),
-                _ => None,
-            })
-            .collect())
-    }
-
     #[instrument(skip(self), fields(notification = notification.id.0))]
     async fn get_pull_request_from_notification(
         &self,
@@ -538,32 +616,230 @@ impl GithubClient {
         Ok(pull_request)
     }
 
-    #[instrument(skip(self, text))]
-    pub async fn reply(
+    // Current login of the user, None if the account is deleted
+    #[instrument(skip(self))]
+    pub async fn get_login(&self, github_id: u64) -> anyhow::Result<Option<String>> {
+        let result: Result<octocrab::models::UserProfile, _> = self
+            .client
+            .get(format!("/user/{github_id}"), None::<&()>)
+            .await;
+        match result {
+            Ok(profile) => Ok(Some(profile.login)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn get_rate_limits(&self) -> anyhow::Result<RateLimit> {
+        Ok(self.client.ratelimit().get().await?)
+    }
+}
+
+#[async_trait]
+impl ProviderClient for GithubClient {
+    async fn get_pull_request_metadata(
         &self,
-        owner: &str,
-        repo: &str,
-        id: u64,
-        text: &str,
-    ) -> anyhow::Result<Comment> {
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<(PrMetadata, Option<GithubHandle>)> {
+        let pr = self
+            .get_pull_request(&repo_info.owner, &repo_info.repo, repo_info.number)
+            .await?;
+        let merged_by = pr.merged_by.clone().map(|user| user.login);
+        Ok((PrMetadata::try_from(pr)?, merged_by))
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_closed_issues(
+        &self,
+        repo_info: &RepoInfo,
+        issues: &[u64],
+    ) -> anyhow::Result<Vec<u64>> {
+        if issues.is_empty() {
+            return Ok(vec![]);
+        }
+        let pr = self
+            .get_pull_request(&repo_info.owner, &repo_info.repo, repo_info.number)
+            .await?;
+        let Some(merge_commit) = pr.merged_at.and(pr.merge_commit_sha) else {
+            return Ok(vec![]);
+        };
+
+        let mut result = vec![];
+        for issue in issues {
+            let page = self
+                .client
+                .issues(&repo_info.owner, &repo_info.repo)
+                .list_timeline_events(*issue)
+                .per_page(100)
+                .send()
+                .await?;
+            let events = self.client.all_pages(page).await?;
+            // Merging the PR closes the issue with the merge commit
+            if events.into_iter().any(|event| {
+                event.event == IssueEvent::Closed
+                    && event.commit_id.as_deref() == Some(merge_commit.as_str())
+            }) {
+                result.push(*issue);
+            }
+        }
+        Ok(result)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_events(&self) -> anyhow::Result<Vec<Event>> {
+        let current_client_id =
+            self.atomic_read_counter.fetch_add(1, Ordering::SeqCst) % self.event_clients.len();
+        let client = &self.event_clients[current_client_id];
+        let page = client
+            .activity()
+            .notifications()
+            .list()
+            .all(false)
+            .participating(true)
+            .per_page(50)
+            .page(0)
+            .send()
+            .await?;
+
+        let events = client.all_pages(page).await?;
+
+        let fetch_pr_futures = events.into_iter().map(|event| async move {
+            if event.reason != "mention" && event.reason != "state_change" {
+                info!(
+                    "Skipping event: {} with reason {}",
+                    event.subject.r#type, event.reason
+                );
+                if let Err(e) = self
+                    .mark_notification_as_read(Notification {
+                        id: event.id.0,
+                        read_client_id: current_client_id,
+                    })
+                    .await
+                {
+                    error!(
+                        "Failed to mark notification as read for event: {:?}: {e:?}",
+                        event.id
+                    );
+                }
+                return None;
+            }
+
+            if event.subject.r#type == "PullRequest" {
+                self.parse_pr_event(current_client_id, event).await
+            } else if event.subject.r#type == "Issue" {
+                self.parse_issue_event(current_client_id, event).await
+            } else {
+                info!(
+                    "Skipping event: {} with reason {}",
+                    event.subject.r#type, event.reason
+                );
+                if let Err(e) = self
+                    .mark_notification_as_read(Notification {
+                        id: event.id.0,
+                        read_client_id: current_client_id,
+                    })
+                    .await
+                {
+                    error!(
+                        "Failed to mark notification as read for event: {:?}: {e:?}",
+                        event.id
+                    );
+                }
+                return None;
+            }
+        });
+
+        Ok(join_all(fetch_pr_futures)
+            .await
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect())
+    }
+
+    async fn get_positive_or_pending_review(
+        &self,
+        repo_info: &RepoInfo,
+    ) -> anyhow::Result<Vec<GithubHandle>> {
+        Ok(self
+            .client
+            .pulls(&repo_info.owner, &repo_info.repo)
+            .list_reviews(repo_info.number)
+            .per_page(10)
+            .send()
+            .await?
+            .take_items()
+            .into_iter()
+            .flat_map(|e| match e.state {
+                Some(ReviewState::Pending) | Some(ReviewState::Approved) => e.user.map(|u| u.login),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn get_scores_and_active_pr_status(
+        &self,
+        pr_metadata: &PrMetadata,
+    ) -> anyhow::Result<(Vec<(BotScored, User)>, bool)> {
+        let comments = self
+            .client
+            .issues(&pr_metadata.repo_info.owner, &pr_metadata.repo_info.repo)
+            .list_comments(pr_metadata.repo_info.number)
+            .per_page(100)
+            .send()
+            .await?;
+
+        let comments = self.client.all_pages(comments).await?;
+
+        let reviews = self
+            .client
+            .pulls(&pr_metadata.repo_info.owner, &pr_metadata.repo_info.repo)
+            .list_reviews(pr_metadata.repo_info.number)
+            .per_page(100)
+            .send()
+            .await?;
+
+        let mut comments = comments
+            .into_iter()
+            .map(CommentRepr::from)
+            .chain(reviews.into_iter().flat_map(CommentRepr::try_from))
+            .collect::<Vec<_>>();
+        comments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+        let labels = self.get_labels(&pr_metadata.repo_info).await?;
+        let scores =
+            scores_from_comments_and_labels(&self.user_handles, pr_metadata, &comments, &labels);
+
+        let active = comments
+            .iter()
+            .filter(|c| {
+                !self.user_handles.contains(&c.user.login)
+                    && c.user.login != pr_metadata.author.login
+            })
+            .count()
+            >= 2;
+
+        Ok((scores, active))
+    }
+
+    #[instrument(skip(self, text))]
+    async fn reply(&self, repo_info: &RepoInfo, text: &str) -> anyhow::Result<CommentRepr> {
         self.prometheus.add_write_request();
         Ok(self
             .client
-            .issues(owner, repo)
-            .create_comment(id, text)
-            .await?)
+            .issues(&repo_info.owner, &repo_info.repo)
+            .create_comment(repo_info.number, text)
+            .await?
+            .into())
     }
 
     #[instrument(skip(self))]
-    pub async fn like_comment(
-        &self,
-        owner: &str,
-        repo: &str,
-        comment_id: u64,
-    ) -> anyhow::Result<()> {
+    async fn like_comment(&self, repo_info: &RepoInfo, comment_id: u64) -> anyhow::Result<()> {
         self.prometheus.add_write_request();
         self.client
-            .issues(owner, repo)
+            .issues(&repo_info.owner, &repo_info.repo)
             .create_comment_reaction(
                 comment_id,
                 octocrab::models::reactions::ReactionContent::PlusOne,
@@ -573,49 +849,28 @@ impl GithubClient {
         Ok(())
     }
 
-    pub async fn mark_notification_as_read(
-        &self,
-        notification: Notification,
-    ) -> anyhow::Result<()> {
-        self.prometheus.add_write_request();
-        self.event_clients
-            .get(notification.read_client_id)
-            .ok_or_else(|| anyhow::anyhow!("No matching client to makr as read. THIS IS A BUG"))?
-            .activity()
-            .notifications()
-            .mark_as_read(notification.id)
-            .await?;
-        Ok(())
-    }
-
     #[instrument(skip(self, text))]
-    pub async fn edit_comment(
+    async fn edit_comment(
         &self,
-        owner: &str,
-        repo: &str,
+        repo_info: &RepoInfo,
         comment_id: u64,
         text: &str,
     ) -> anyhow::Result<()> {
         self.prometheus.add_write_request();
 
         self.client
-            .issues(owner, repo)
+            .issues(&repo_info.owner, &repo_info.repo)
             .update_comment(CommentId(comment_id), text)
             .await?;
         Ok(())
     }
 
     #[instrument(skip(self,))]
-    pub async fn get_bot_comment(
-        &self,
-        owner: &str,
-        repo: &str,
-        pr_number: u64,
-    ) -> anyhow::Result<Option<CommentRepr>> {
+    async fn get_bot_comment(&self, repo_info: &RepoInfo) -> anyhow::Result<Option<CommentRepr>> {
         let mut page = self
             .client
-            .issues(owner, repo)
-            .list_comments(pr_number)
+            .issues(&repo_info.owner, &repo_info.repo)
+            .list_comments(repo_info.number)
             .per_page(100)
             .send()
             .await?;
@@ -636,60 +891,314 @@ impl GithubClient {
         }
     }
 
-    pub async fn get_rate_limits(&self) -> anyhow::Result<RateLimit> {
-        Ok(self.client.ratelimit().get().await?)
-    }
-
-    /// Active PR is the PR where there are >2 messages from other users (exculding us and the author)
-    pub async fn get_scores_and_active_pr_status(
+    #[instrument(skip(self))]
+    async fn get_comments_since(
         &self,
-        pr_metadata: &PrMetadata,
-    ) -> anyhow::Result<(Vec<(BotScored, User)>, bool)> {
-        let comments = self
+        repo_info: &RepoInfo,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<CommentRepr>> {
+        let page = self
             .client
-            .issues(&pr_metadata.repo_info.owner, &pr_metadata.repo_info.repo)
-            .list_comments(pr_metadata.repo_info.number)
+            .issues(&repo_info.owner, &repo_info.repo)
+            .list_comments(repo_info.number)
+            .since(since)
             .per_page(100)
             .send()
             .await?;
+        let comments = self.client.all_pages(page).await?;
+        Ok(comments.into_iter().map(Into::into).collect())
+    }
 
-        let comments = self.client.all_pages(comments).await?;
+    async fn get_user_id(&self, login: &str) -> anyhow::Result<Option<u64>> {
+        match self.client.users(login).profile().await {
+            Ok(profile) => Ok(Some(profile.id.0)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
 
-        let reviews = self
+    async fn mark_notification_as_read(&self, notification: Notification) -> anyhow::Result<()> {
+        self.prometheus.add_write_request();
+        self.event_clients
+            .get(notification.read_client_id)
+            .ok_or_else(|| anyhow::anyhow!("No matching client to makr as read. THIS IS A BUG"))?
+            .activity()
+            .notifications()
+            .mark_as_read(NotificationId(notification.id))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_default_branch_sha(&self, repo_info: &RepoInfo) -> anyhow::Result<String> {
+        // HEAD resolves to the default branch
+        let commit = self
             .client
-            .pulls(&pr_metadata.repo_info.owner, &pr_metadata.repo_info.repo)
-            .list_reviews(pr_metadata.repo_info.number)
+            .commits(&repo_info.owner, &repo_info.repo)
+            .get("HEAD")
+            .await?;
+        Ok(commit.sha)
+    }
+
+    async fn get_file(
+        &self,
+        repo_info: &RepoInfo,
+        path: &str,
+        sha: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let content = self
+            .client
+            .repos(&repo_info.owner, &repo_info.repo)
+            .get_content()
+            .path(path)
+            .r#ref(sha)
+            .send()
+            .await;
+
+        match content {
+            Ok(mut content) => Ok(content
+                .take_items()
+                .into_iter()
+                .next()
+                .and_then(|item| item.decoded_content())),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[instrument(skip(self, status), fields(pr = repo_info.full_id))]
+    async fn set_commit_status(
+        &self,
+        repo_info: &RepoInfo,
+        sha: &str,
+        status: &CommitStatus,
+    ) -> anyhow::Result<()> {
+        self.prometheus.add_write_request();
+        self.client
+            .repos(&repo_info.owner, &repo_info.repo)
+            .create_status(sha.to_string(), StatusState::Success)
+            .context(CommitStatus::CONTEXT.to_string())
+            .description(status.description.clone())
+            .target(status.target_url.clone())
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn get_labels(&self, repo_info: &RepoInfo) -> anyhow::Result<Vec<LabelRepr>> {
+        let page = self
+            .client
+            .issues(&repo_info.owner, &repo_info.repo)
+            .list_timeline_events(repo_info.number)
             .per_page(100)
             .send()
             .await?;
+        let events = self.client.all_pages(page).await?;
+
+        Ok(current_labels(events.into_iter().filter_map(|event| {
+            let added = match event.event {
+                IssueEvent::Labeled => true,
+                IssueEvent::Unlabeled => false,
+                _ => return None,
+            };
+            Some(LabelChange {
+                label: LabelRepr {
+                    name: event.label?.name,
+                    // Labels can be changed only by the users with the triage access
+                    user: User::new(event.actor?.login, AuthorAssociation::Collaborator),
+                    timestamp: event.created_at?,
+                },
+                added,
+            })
+        })))
+    }
 
-        let mut comments = comments
-            .into_iter()
-            .map(CommentRepr::from)
-            .chain(reviews.into_iter().flat_map(CommentRepr::try_from))
-            .collect::<Vec<_>>();
-        comments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn add_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()> {
+        self.prometheus.add_write_request();
+        self.client
+            .issues(&repo_info.owner, &repo_info.repo)
+            .add_labels(repo_info.number, &[label.to_string()])
+            .await?;
+        Ok(())
+    }
 
-        let mut scores = Vec::new();
-        for comment in comments.iter() {
-            for handle in self.user_handles.iter() {
-                if let Some(Command::Score(score)) =
-                    Command::parse_command(handle, pr_metadata, comment)
-                {
-                    scores.push((score, comment.user.clone()));
-                }
+    #[instrument(skip(self), fields(pr = repo_info.full_id))]
+    async fn remove_label(&self, repo_info: &RepoInfo, label: &str) -> anyhow::Result<()> {
+        self.prometheus.add_write_request();
+        let result = self
+            .client
+            .issues(&repo_info.owner, &repo_info.repo)
+            .remove_label(repo_info.number, label)
+            .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                Ok(())
             }
+            Err(e) => Err(e.into()),
         }
+    }
 
-        let active = comments
-            .iter()
-            .filter(|c| {
-                !self.user_handles.contains(&c.user.login)
-                    && c.user.login != pr_metadata.author.login
-            })
-            .count()
-            >= 2;
+    #[instrument(skip(self))]
+    async fn get_labeled_pull_requests(
+        &self,
+        label: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let query = format!(
+            "is:pr is:open label:\"{label}\" updated:>={}",
+            since.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        self.search_pull_requests(&query).await
+    }
 
-        Ok((scores, active))
+    async fn get_new_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let query = format!(
+            "is:pr is:open created:>={}",
+            since.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        self.search_pull_requests_in_repos(query, repos).await
+    }
+
+    async fn get_ready_pull_requests(
+        &self,
+        repos: &[(String, String)],
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RepoInfo>> {
+        let query = format!(
+            "is:pr is:open draft:false commenter:{} updated:>={}",
+            self.write_client_handle,
+            since.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        self.search_pull_requests_in_repos(query, repos).await
     }
 }
+
+// Replays the label changes in chronological order
+pub(crate) fn current_labels(changes: impl Iterator<Item = LabelChange>) -> Vec<LabelRepr> {
+    let mut labels: Vec<LabelRepr> = Vec::new();
+    for change in changes {
+        labels.retain(|label| label.name != change.label.name);
+        if change.added {
+            labels.push(change.label);
+        }
+    }
+    labels
+}
+
+/// Score commands in chronological order. Expects sorted comments
+pub(crate) fn scores_from_comments(
+    user_handles: &BTreeSet<String>,
+    pr_metadata: &PrMetadata,
+    comments: &[CommentRepr],
+) -> Vec<(BotScored, User)> {
+    let mut scores = Vec::new();
+    for comment in comments.iter() {
+        for handle in user_handles.iter() {
+            if let Some(Command::Score(score)) =
+                Command::parse_command(handle, pr_metadata, comment)
+            {
+                scores.push((score, comment.user.clone()));
+            }
+        }
+    }
+    scores
+}
+
+/// Score commands and score labels in chronological order. Expects sorted comments and labels
+pub(crate) fn scores_from_comments_and_labels(
+    user_handles: &BTreeSet<String>,
+    pr_metadata: &PrMetadata,
+    comments: &[CommentRepr],
+    labels: &[LabelRepr],
+) -> Vec<(BotScored, User)> {
+    let mut scores = scores_from_comments(user_handles, pr_metadata, comments);
+    scores.extend(labels::scores(labels));
+    scores.sort_by(|(a, _), (b, _)| a.timestamp.cmp(&b.timestamp));
+    scores
+}
+
+/// Parses commands from the comments left after our last reply and from the PR body.
+/// Returns them in chronological order and the rescore action if any of the comments was edited.
+/// Expects sorted comments
+pub(crate) fn pr_command_events(
+    user_handles: &BTreeSet<String>,
+    pr_metadata: &PrMetadata,
+    comments: Vec<CommentRepr>,
+    labels: &[LabelRepr],
+    first_bot_comment: Option<CommentRepr>,
+    notification: Notification,
+) -> (Vec<Event>, Option<Event>) {
+    let mut results = Vec::new();
+    // Votes from the labels are kept, so editing a comment doesn't retract them
+    let scores = scores_from_comments_and_labels(user_handles, pr_metadata, &comments, labels);
+    let mut edited = false;
+
+    for comment in comments.into_iter().rev() {
+        // We have processed older messages, unless they were answered while the contract was paused
+        if user_handles.contains(&comment.user.login) {
+            if comment.text.contains(PAUSED_MARKER) {
+                continue;
+            }
+            break;
+        }
+        edited |= comment.edited;
+
+        for handle in user_handles.iter() {
+            if let Some(command) = Command::parse_command(handle, pr_metadata, &comment) {
+                results.push(Event {
+                    event: EventType::PRCommand {
+                        command,
+                        notification: Some(notification),
+                        sender: comment.user.clone(),
+                        pr: pr_metadata.clone(),
+                    },
+                    comment: first_bot_comment.clone(),
+                    event_time: comment.timestamp,
+                });
+                break;
+            }
+        }
+    }
+
+    for handle in user_handles.iter() {
+        if let Some(command) = Command::parse_body(handle, pr_metadata) {
+            results.push(Event {
+                event: EventType::PRCommand {
+                    command,
+                    notification: Some(notification),
+                    sender: pr_metadata.author.clone(),
+                    pr: pr_metadata.clone(),
+                },
+                comment: first_bot_comment.clone(),
+                event_time: pr_metadata.created,
+            });
+            break;
+        }
+    }
+
+    // To keep the chronological order, we reverse the results
+    results.reverse();
+
+    // Edited comments might have changed or removed the votes
+    let rescore = edited.then(|| Event {
+        event: EventType::Action {
+            action: Action::rescore(scores),
+            pr: pr_metadata.clone(),
+        },
+        comment: first_bot_comment,
+        event_time: pr_metadata.updated_at,
+    });
+
+    (results, rescore)
+}
@@ -21,7 +21,16 @@ pub enum EventType {
     Merge,
     Finalize,
     Stale,
+    StaleWarning,
+    Unstale,
     Update,
+    Coauthors,
+    Bounty,
+    Help,
+    Status,
+    Rescore,
+    Lang,
+    AutoInclude,
 }
 
 impl From<&crate::events::EventType> for EventType {
@@ -35,14 +44,23 @@ impl From<&crate::events::EventType> for EventType {
                 crate::events::pr_commands::Command::Excluded(_) => EventType::Excluded,
                 crate::events::pr_commands::Command::Unknown(_) => EventType::Unknown,
                 crate::events::pr_commands::Command::Update(_) => EventType::Update,
+                crate::events::pr_commands::Command::Coauthors(_) => EventType::Coauthors,
+                crate::events::pr_commands::Command::Help(_) => EventType::Help,
+                crate::events::pr_commands::Command::Status(_) => EventType::Status,
+                crate::events::pr_commands::Command::Lang(_) => EventType::Lang,
+                crate::events::pr_commands::Command::AutoInclude(_) => EventType::AutoInclude,
             },
             crate::events::EventType::Action { action, .. } => match action {
                 crate::events::actions::Action::Merge(_) => EventType::Merge,
                 crate::events::actions::Action::Finalize(_) => EventType::Finalize,
                 crate::events::actions::Action::Stale(_) => EventType::Stale,
+                crate::events::actions::Action::StaleWarning(_) => EventType::StaleWarning,
+                crate::events::actions::Action::Unstale(_) => EventType::Unstale,
+                crate::events::actions::Action::Rescore(_) => EventType::Rescore,
             },
             crate::events::EventType::IssueCommand { command, .. } => match command {
                 crate::events::issue_commands::Command::Unpause(_) => EventType::Unpause,
+                crate::events::issue_commands::Command::Bounty(_) => EventType::Bounty,
             },
         }
     }
@@ -4,7 +4,7 @@ use shared::{github::PrMetadata, Event, PRInfo, Score};
 
 use crate::events::Context;
 
-use super::{EventResult, FinalMessageData};
+use super::{EventResult, FinalMessageData, PullRequestRescore};
 
 #[derive(Debug, Clone)]
 pub struct PullRequestFinalize {}
@@ -22,20 +22,48 @@ impl PullRequestFinalize {
             return Ok(EventResult::Skipped);
         }
 
+        let provider = context.provider(&pr.repo_info)?;
+        let scores_and_activity = provider.get_scores_and_active_pr_status(pr).await;
+        let is_active = match scores_and_activity {
+            Ok((scores, active)) => {
+                // Score comments could have been edited or deleted since the votes were sent
+                PullRequestRescore::new(scores)
+                    .execute(pr, context.clone(), info)
+                    .await?;
+                Some(active)
+            }
+            Err(e) => {
+                warn!("Failed to get scores for PR {}: {e}", pr.repo_info.full_id);
+                None
+            }
+        };
+
         let is_active_pr = if !info.votes.is_empty() {
             // We don't need to check if PR is active if we have votes
             None
         } else {
-            context
-                .github
-                .get_scores_and_active_pr_status(pr)
-                .await
-                .ok()
-                .map(|(_, active)| (active, context.bot_name.clone()))
+            is_active.map(|active| (active, context.bot_name.clone()))
         };
+        // PR metadata is restored from the contract, so we need to fetch the body.
+        // The references are only candidates: the provider has to confirm that the merge closed them
+        let closed_issues = match provider.get_pull_request_metadata(&pr.repo_info).await {
+            Ok((pull_request, _)) => {
+                provider
+                    .get_closed_issues(&pr.repo_info, &closing_issues(&pull_request.body))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let closed_issues = closed_issues.unwrap_or_else(|e| {
+            warn!(
+                "Failed to find issues closed by PR {}: {e}",
+                pr.repo_info.full_id
+            );
+            vec![]
+        });
         let events = context
             .near
-            .send_finalize(&pr.repo_info.full_id, is_active_pr)
+            .send_finalize(&pr.repo_info.full_id, is_active_pr, closed_issues)
             .await?;
         info.executed = true;
 
@@ -90,7 +118,23 @@ impl PullRequestFinalize {
                         score,
                     });
                 }
-                Event::NewSloth { .. } => {}
+                Event::BountyClaimed {
+                    issue_number,
+                    score,
+                } => {
+                    final_data.bounties.push((issue_number, score));
+                }
+                Event::CoauthorExecutedWithRating {
+                    github_handle,
+                    rating,
+                    ..
+                } => {
+                    final_data.coauthors.push((github_handle, rating));
+                }
+                Event::RankUp { rank } => {
+                    final_data.rank_up = Some(rank);
+                }
+                Event::NewSloth { .. } | Event::Revoked { .. } | Event::Archived { .. } => {}
             }
         }
 
@@ -100,3 +144,57 @@ impl PullRequestFinalize {
         Ok(())
     }
 }
+
+const CLOSING_KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+// Finds issues from the same repository that are closed by the PR with the keywords like `Fixes #1`
+// https://docs.github.com/en/issues/tracking-your-work-with-issues/linking-a-pull-request-to-an-issue
+pub fn closing_issues(body: &str) -> Vec<u64> {
+    let mut result = vec![];
+    let mut words = body.split_whitespace().peekable();
+
+    while let Some(word) = words.next() {
+        let keyword = word.trim_end_matches(':').to_lowercase();
+        if !CLOSING_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+
+        let issue = words
+            .peek()
+            .and_then(|next| next.strip_prefix('#'))
+            .map(|next| next.trim_end_matches(|c: char| !c.is_ascii_digit()))
+            .and_then(|next| next.parse::<u64>().ok());
+        if let Some(issue) = issue {
+            if !result.contains(&issue) {
+                result.push(issue);
+            }
+            words.next();
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::closing_issues;
+
+    #[test]
+    fn closing_issues_parsing() {
+        assert_eq!(closing_issues("Fixes #12"), vec![12]);
+        assert_eq!(
+            closing_issues("This PR closes #1, resolves: #2.\nAlso fixed #1"),
+            vec![1, 2]
+        );
+        assert_eq!(
+            closing_issues("Related to #3, fixes typo"),
+            Vec::<u64>::new()
+        );
+        assert_eq!(
+            closing_issues("Fixes NEAR-DevHub/other#4 and #5"),
+            Vec::<u64>::new()
+        );
+    }
+}
@@ -36,12 +36,13 @@ impl PullRequestMerge {
         }
 
         let (scores, is_active) = context
-            .github
+            .provider(&pr.repo_info)?
             .get_scores_and_active_pr_status(pr)
             .await
             .unwrap_or_default();
 
-        let autoscore = if is_active { 2 } else { 1 }.to_string();
+        let autoscore = info.scale.label(if is_active { 2 } else { 1 });
+        let valid_scores = info.scale.labels().join("/");
 
         if !scores.is_empty() {
             let scores_len = scores.len();
@@ -66,6 +67,7 @@ impl PullRequestMerge {
                     vec![
                         ("maintainer", self.merger.clone()),
                         ("potential_score", autoscore),
+                        ("scores", valid_scores.clone()),
                     ],
                 )
                 .await?;
@@ -78,6 +80,7 @@ impl PullRequestMerge {
                     vec![
                         ("maintainer", self.reviewers.join(" @")),
                         ("potential_score", autoscore),
+                        ("scores", valid_scores.clone()),
                     ],
                 )
                 .await?;
@@ -90,6 +93,7 @@ impl PullRequestMerge {
                     vec![
                         ("pr_author_username", pr.author.login.clone()),
                         ("potential_score", autoscore),
+                        ("scores", valid_scores.clone()),
                     ],
                 )
                 .await?;
@@ -1,11 +1,13 @@
 mod finalize;
 mod merge;
+mod rescore;
 mod stale;
 
 use super::*;
 
 pub use finalize::*;
 pub use merge::*;
+pub use rescore::*;
 use shared::GithubHandle;
 pub use stale::*;
 use tracing::error;
@@ -15,6 +17,9 @@ pub enum Action {
     Finalize(PullRequestFinalize),
     Merge(PullRequestMerge),
     Stale(PullRequestStale),
+    StaleWarning(PullRequestStaleWarning),
+    Unstale(PullRequestUnstale),
+    Rescore(PullRequestRescore),
 }
 
 impl std::fmt::Display for Action {
@@ -23,6 +28,9 @@ impl std::fmt::Display for Action {
             Action::Finalize(_) => write!(f, "Finalize"),
             Action::Merge(_) => write!(f, "Merge"),
             Action::Stale(_) => write!(f, "Stale"),
+            Action::StaleWarning(_) => write!(f, "StaleWarning"),
+            Action::Unstale(_) => write!(f, "Unstale"),
+            Action::Rescore(_) => write!(f, "Rescore"),
         }
     }
 }
@@ -37,7 +45,23 @@ impl Action {
     }
 
     pub fn stale() -> Self {
-        Self::Stale(PullRequestStale {})
+        Self::Stale(PullRequestStale { draft: false })
+    }
+
+    pub fn redraft() -> Self {
+        Self::Stale(PullRequestStale { draft: true })
+    }
+
+    pub fn stale_warning() -> Self {
+        Self::StaleWarning(PullRequestStaleWarning {})
+    }
+
+    pub fn unstale() -> Self {
+        Self::Unstale(PullRequestUnstale {})
+    }
+
+    pub fn rescore(scores: Vec<(pr_commands::BotScored, User)>) -> Self {
+        Self::Rescore(PullRequestRescore::new(scores))
     }
 
     pub async fn execute(
@@ -50,6 +74,10 @@ impl Action {
             error!("Shouldn't happening. PR({}) is excluded, so should be removed, but we tracked action for it...", pr.repo_info.full_id);
             return Ok(EventResult::Skipped);
         }
+        if let Action::Unstale(action) = self {
+            // Stale PRs don't exist in the race until they are restored
+            return action.execute(pr, context, check_info).await;
+        }
         if !check_info.exist {
             // Parsed notification but we weren't called before to include us
             return Ok(EventResult::Skipped);
@@ -59,6 +87,9 @@ impl Action {
             Action::Finalize(action) => action.execute(pr, context, check_info).await,
             Action::Merge(action) => action.execute(pr, context, check_info).await,
             Action::Stale(action) => action.execute(pr, context, check_info).await,
+            Action::StaleWarning(action) => action.execute(pr, context, check_info).await,
+            Action::Unstale(action) => action.execute(pr, context, check_info).await,
+            Action::Rescore(action) => action.execute(pr, context, check_info).await,
         }
     }
 }
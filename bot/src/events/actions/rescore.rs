@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use tracing::{debug, instrument, warn};
+
+use shared::{
+    github::{PrMetadata, User},
+    GithubHandle, PRInfo, Score, ScoringScale,
+};
+
+use crate::events::{pr_commands::BotScored, Context};
+
+use super::EventResult;
+
+/// Synchronizes the votes in the contract with the score comments on GitHub,
+/// so edited comments re-apply the vote and deleted ones retract it.
+#[derive(Debug, Clone)]
+pub struct PullRequestRescore {
+    // The latest score command of every user
+    scores: BTreeMap<GithubHandle, (BotScored, User)>,
+}
+
+impl PullRequestRescore {
+    // Expects scores in chronological order
+    pub fn new(scores: Vec<(BotScored, User)>) -> Self {
+        Self {
+            scores: scores
+                .into_iter()
+                .map(|(score, user)| (user.login.clone(), (score, user)))
+                .collect(),
+        }
+    }
+
+    // Votes that don't have a score comment anymore
+    pub fn retracted(&self, votes: &[Score], bot_name: &str) -> Vec<GithubHandle> {
+        votes
+            .iter()
+            .filter(|vote| vote.user != bot_name && !self.scores.contains_key(&vote.user))
+            .map(|vote| vote.user.clone())
+            .collect()
+    }
+
+    // Score comments that don't match the stored votes. Participants can't score their own PR
+    pub fn changed(
+        &self,
+        votes: &[Score],
+        participants: &[GithubHandle],
+        scale: ScoringScale,
+    ) -> Vec<&(BotScored, User)> {
+        self.scores
+            .values()
+            .filter(|(_, user)| !participants.contains(&user.login))
+            .filter(|(score, user)| {
+                votes
+                    .iter()
+                    .find(|vote| vote.user == user.login)
+                    .is_none_or(|vote| vote.score != score.score(scale).0)
+            })
+            .collect()
+    }
+
+    pub fn has_changes(
+        &self,
+        votes: &[Score],
+        participants: &[GithubHandle],
+        bot_name: &str,
+        scale: ScoringScale,
+    ) -> bool {
+        !self.retracted(votes, bot_name).is_empty()
+            || !self.changed(votes, participants, scale).is_empty()
+    }
+
+    #[instrument(skip(self, pr, context, info), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        info: &mut PRInfo,
+    ) -> anyhow::Result<EventResult> {
+        if info.executed {
+            warn!("PR {} is already finalized. Skipping", pr.repo_info.full_id);
+            return Ok(EventResult::Skipped);
+        }
+
+        let retracted = self.retracted(&info.votes, &context.bot_name);
+        let participants: Vec<GithubHandle> = std::iter::once(pr.author.login.clone())
+            .chain(info.coauthors.iter().cloned())
+            .collect();
+        let changed = self.changed(&info.votes, &participants, info.scale);
+        if retracted.is_empty() && changed.is_empty() {
+            debug!("Votes are up to date in {}", pr.repo_info.full_id);
+            return Ok(EventResult::Skipped);
+        }
+
+        for user in retracted {
+            context.near.send_unscored(pr, &user).await?;
+            info.votes.retain(|vote| vote.user != user);
+        }
+
+        for (score, sender) in changed {
+            score
+                .clone()
+                .muted()
+                .execute(pr, context.clone(), info, sender)
+                .await?;
+        }
+
+        Ok(EventResult::success(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{github::User, Score, ScoringScale::Fibonacci};
+
+    use crate::events::pr_commands::BotScored;
+
+    use super::PullRequestRescore;
+
+    fn score(user: &str, score: &str) -> (BotScored, User) {
+        (
+            BotScored::new(score.to_string(), chrono::Utc::now(), Some(1)),
+            User::new(
+                user.to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+        )
+    }
+
+    fn vote(user: &str, score: u32) -> Score {
+        Score {
+            user: user.to_string(),
+            score,
+        }
+    }
+
+    #[test]
+    fn deleted_comment_retracts_vote() {
+        let rescore = PullRequestRescore::new(vec![score("a", "5")]);
+        let votes = vec![vote("a", 5), vote("b", 8), vote("bot", 2)];
+
+        assert_eq!(rescore.retracted(&votes, "bot"), vec!["b".to_string()]);
+        assert!(rescore
+            .changed(&votes, &["author".to_string()], Fibonacci)
+            .is_empty());
+    }
+
+    #[test]
+    fn edited_comment_changes_vote() {
+        let rescore = PullRequestRescore::new(vec![
+            score("a", "5"),
+            score("b", "3"),
+            score("a", "9"),
+            score("author", "13"),
+            score("coauthor", "8"),
+        ]);
+        let votes = vec![vote("a", 5), vote("b", 3)];
+        let participants = ["author".to_string(), "coauthor".to_string()];
+
+        let changed = rescore.changed(&votes, &participants, Fibonacci);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].1.login, "a");
+        assert_eq!(changed[0].0.score(Fibonacci), (8, true));
+        assert!(rescore.retracted(&votes, "bot").is_empty());
+        assert!(rescore.has_changes(&votes, &participants, "bot", Fibonacci));
+        assert!(!rescore.has_changes(
+            &[vote("a", 8), vote("b", 3)],
+            &participants,
+            "bot",
+            Fibonacci
+        ));
+    }
+}
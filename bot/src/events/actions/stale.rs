@@ -1,13 +1,96 @@
+use chrono::{DateTime, Duration, Utc};
 use tracing::{instrument, warn};
 
 use shared::{github::PrMetadata, PRInfo};
 
-use crate::{events::Context, messages::MsgCategory};
+use crate::{api::CommentRepr, events::Context, messages::MsgCategory, repo_config::RepoConfig};
 
 use super::EventResult;
 
+/// Hidden markers of the replies, so we can tell whether the last update of the PR was ours
+pub const STALE_WARNING_MARKER: &str = "<!-- sloth:stale-warning -->";
+pub const STALE_MARKER: &str = "<!-- sloth:stale -->";
+
+/// Stale PRs are checked for updates that bring them back for this long
+pub const STALE_REVIVAL_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inactivity {
+    Active,
+    Warning,
+    Stale,
+}
+
+impl Inactivity {
+    // Our replies update the PR, so the last update is known only after checking the latest comments
+    pub fn needs_comments(pr: &PrMetadata, config: &RepoConfig, now: DateTime<Utc>) -> bool {
+        if pr.merged.is_some() || pr.closed {
+            return false;
+        }
+
+        let mut days = config.stale_days + 1;
+        if config.stale_warning_days < config.stale_days {
+            days =
+                (config.stale_days - config.stale_warning_days).min(config.stale_warning_days + 1);
+        }
+        now - pr.updated_at >= Duration::days(days)
+    }
+
+    // `warned_at` is set if our warning is the last update of the PR, then the remaining days are counted from it
+    pub fn of(
+        pr: &PrMetadata,
+        config: &RepoConfig,
+        warned_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        if pr.merged.is_some() {
+            return Self::Active;
+        }
+        if pr.closed {
+            return Self::Stale;
+        }
+
+        let warnings = config.stale_warning_days < config.stale_days;
+        if let Some(warned_at) = warned_at.filter(|_| warnings) {
+            let remaining = Duration::days(config.stale_days - config.stale_warning_days);
+            return if now - warned_at >= remaining {
+                Self::Stale
+            } else {
+                Self::Active
+            };
+        }
+
+        let inactive = (now - pr.updated_at).num_days();
+        if inactive > config.stale_days {
+            Self::Stale
+        } else if warnings && inactive > config.stale_warning_days {
+            Self::Warning
+        } else {
+            Self::Active
+        }
+    }
+}
+
+// Time of our reply with the marker if nothing happened on the PR after it
+pub fn marked_last_update(
+    pr: &PrMetadata,
+    comments: &[CommentRepr],
+    marker: &str,
+) -> Option<DateTime<Utc>> {
+    // The reply and the update of the PR it causes are a few seconds apart
+    let since = pr.updated_at - Duration::minutes(1);
+    comments
+        .iter()
+        .filter(|comment| comment.timestamp >= since && comment.text.contains(marker))
+        .map(|comment| comment.timestamp)
+        .max()
+}
+
 #[derive(Debug, Clone)]
-pub struct PullRequestStale {}
+pub struct PullRequestStale {
+    // Converted back to a draft, it's restored the same way once it's ready for review
+    pub draft: bool,
+}
 
 impl PullRequestStale {
     #[instrument(skip(self, context, check_info), fields(pr = pr.repo_info.full_id))]
@@ -26,6 +109,7 @@ impl PullRequestStale {
         *check_info = PRInfo {
             exist: false,
             votes: vec![],
+            coauthors: vec![],
             merged: false,
             executed: false,
             ..*check_info
@@ -39,9 +123,185 @@ impl PullRequestStale {
             return Ok(EventResult::success(true));
         }
 
+        let (category, args) = if self.draft {
+            (
+                MsgCategory::RedraftMessage,
+                vec![("pr_author_username", pr.author.login.clone())],
+            )
+        } else {
+            (MsgCategory::StaleMessage, vec![])
+        };
+        context
+            .reply_with_marker(&pr.repo_info, None, category, args, STALE_MARKER)
+            .await?;
+        Ok(EventResult::success(true))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PullRequestStaleWarning {}
+
+impl PullRequestStaleWarning {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: &mut PRInfo,
+    ) -> anyhow::Result<EventResult> {
+        if check_info.merged || check_info.paused_repo || check_info.blocked_repo {
+            return Ok(EventResult::Skipped);
+        }
+
+        let config = context.repo_config(&pr.repo_info).await;
+        let stale_at = Utc::now() + Duration::days(config.stale_days - config.stale_warning_days);
         context
-            .reply(&pr.repo_info, None, MsgCategory::StaleMessage, vec![])
+            .reply_with_marker(
+                &pr.repo_info,
+                None,
+                MsgCategory::StaleWarningMessage,
+                vec![
+                    ("pr_author_username", pr.author.login.clone()),
+                    ("date", stale_at.format("%b %d").to_string()),
+                ],
+                STALE_WARNING_MARKER,
+            )
             .await?;
+        Ok(EventResult::success(false))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PullRequestUnstale {}
+
+impl PullRequestUnstale {
+    #[instrument(skip(self, context, check_info), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        check_info: &mut PRInfo,
+    ) -> anyhow::Result<EventResult> {
+        if check_info.exist {
+            warn!("PR {} is already included. Skipping", pr.repo_info.full_id);
+            return Ok(EventResult::Skipped);
+        }
+
+        context.near.send_unstale(pr).await?;
+        *check_info = context.check_info(&pr.repo_info).await?;
         Ok(EventResult::success(true))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use octocrab::models::AuthorAssociation;
+    use shared::github::User;
+
+    use crate::{
+        api::CommentRepr, events::pr_commands::tests::default_pr_metadata, repo_config::RepoConfig,
+    };
+
+    use super::{marked_last_update, Inactivity, STALE_WARNING_MARKER};
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-10-30T12:00:00Z")
+            .unwrap()
+            .to_utc()
+    }
+
+    fn comment(text: &str, timestamp: DateTime<Utc>) -> CommentRepr {
+        CommentRepr {
+            id: 0,
+            user: User::new("sloth".to_string(), AuthorAssociation::None),
+            timestamp,
+            text: text.to_string(),
+            comment_id: Some(0),
+            edited: false,
+        }
+    }
+
+    #[test]
+    fn warning_comes_before_stale() {
+        let config = RepoConfig::default();
+        let mut pr = default_pr_metadata();
+
+        pr.updated_at = now() - Duration::days(3);
+        assert!(!Inactivity::needs_comments(&pr, &config, now()));
+        assert_eq!(
+            Inactivity::of(&pr, &config, None, now()),
+            Inactivity::Active
+        );
+
+        pr.updated_at = now() - Duration::days(11);
+        assert!(Inactivity::needs_comments(&pr, &config, now()));
+        assert_eq!(
+            Inactivity::of(&pr, &config, None, now()),
+            Inactivity::Warning
+        );
+
+        pr.updated_at = now() - Duration::days(15);
+        assert_eq!(Inactivity::of(&pr, &config, None, now()), Inactivity::Stale);
+
+        pr.closed = true;
+        pr.updated_at = now();
+        assert_eq!(Inactivity::of(&pr, &config, None, now()), Inactivity::Stale);
+    }
+
+    #[test]
+    fn stale_counts_from_the_warning() {
+        let config = RepoConfig::default();
+        let mut pr = default_pr_metadata();
+
+        let warned_at = now() - Duration::days(3);
+        pr.updated_at = warned_at;
+        assert_eq!(
+            Inactivity::of(&pr, &config, Some(warned_at), now()),
+            Inactivity::Active
+        );
+
+        let warned_at = now() - Duration::days(4);
+        pr.updated_at = warned_at;
+        assert!(Inactivity::needs_comments(&pr, &config, now()));
+        assert_eq!(
+            Inactivity::of(&pr, &config, Some(warned_at), now()),
+            Inactivity::Stale
+        );
+    }
+
+    #[test]
+    fn warning_is_disabled() {
+        let config = RepoConfig {
+            stale_warning_days: 14,
+            ..Default::default()
+        };
+        let mut pr = default_pr_metadata();
+
+        pr.updated_at = now() - Duration::days(12);
+        assert!(!Inactivity::needs_comments(&pr, &config, now()));
+        assert_eq!(
+            Inactivity::of(&pr, &config, None, now()),
+            Inactivity::Active
+        );
+    }
+
+    #[test]
+    fn updates_after_the_warning_are_noticed() {
+        let mut pr = default_pr_metadata();
+        let warning = comment(&format!("Going stale\n{STALE_WARNING_MARKER}"), now());
+
+        pr.updated_at = now() + Duration::seconds(2);
+        assert_eq!(
+            marked_last_update(&pr, &[warning.clone()], STALE_WARNING_MARKER),
+            Some(now())
+        );
+
+        pr.updated_at = now() + Duration::hours(1);
+        let reply = comment("I'm on it", pr.updated_at);
+        assert_eq!(
+            marked_last_update(&pr, &[warning, reply], STALE_WARNING_MARKER),
+            None
+        );
+    }
+}
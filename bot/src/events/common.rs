@@ -1,14 +1,110 @@
-use shared::PRInfo;
+use shared::{PRInfo, Provider};
 use std::collections::HashMap;
-use tracing::trace;
+use tracing::{trace, warn};
 
-use crate::messages::MsgCategory;
+use crate::{
+    messages::{MessageLoader, MsgCategory},
+    repo_config::RepoConfig,
+};
 
-use self::api::CommentRepr;
+use self::api::{CommentRepr, ProviderClient};
 
 use super::*;
 
 impl Context {
+    pub fn provider(&self, repo_info: &RepoInfo) -> anyhow::Result<&dyn ProviderClient> {
+        match repo_info.provider() {
+            Provider::GitHub => Ok(self.github.as_ref()),
+            Provider::GitLab => self
+                .gitlab
+                .as_deref()
+                .map(|gitlab| gitlab as &dyn ProviderClient)
+                .ok_or_else(|| anyhow::anyhow!("GitLab is not configured")),
+        }
+    }
+
+    pub fn messages(&self) -> &MessageLoader {
+        self.messages.get(self.locale.as_deref())
+    }
+
+    // Takes the latest messages, so all events of the PR use the same ones even if they are reloaded meanwhile.
+    // The user preference wins over the repository one. English is used if neither is supported
+    pub async fn localized(mut self, repo_info: &RepoInfo, user: &str) -> Self {
+        self.messages = self.message_source.current();
+        let user_locale = self.near.user_locale(user).await.unwrap_or_else(|e| {
+            warn!("Failed to get locale of {user}: {e}");
+            None
+        });
+        let locale = match user_locale {
+            Some(locale) => Some(locale),
+            None => self.repo_config(repo_info).await.locale,
+        };
+        self.locale = locale.filter(|locale| self.messages.is_supported(locale));
+        self
+    }
+
+    fn base_url(&self, provider: Provider) -> &str {
+        match (provider, &self.gitlab) {
+            (Provider::GitLab, Some(gitlab)) => gitlab.url(),
+            _ => provider.default_url(),
+        }
+    }
+
+    pub fn pull_request_url(&self, repo_info: &RepoInfo) -> String {
+        let provider = repo_info.provider();
+        provider.pull_request_url(
+            self.base_url(provider),
+            repo_info.organization_name(),
+            &repo_info.repo,
+            repo_info.number,
+        )
+    }
+
+    // Profile of the user on the provider of the PR, the handle can be namespaced
+    pub fn user_url(&self, repo_info: &RepoInfo, handle: &str) -> String {
+        let provider = repo_info.provider();
+        provider.user_url(self.base_url(provider), handle)
+    }
+
+    // Falls back to the default config if the repository config can't be loaded
+    pub async fn repo_config(&self, repo_info: &RepoInfo) -> RepoConfig {
+        match self.load_repo_config(repo_info).await {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load config for {}: {e}", repo_info.full_id);
+                RepoConfig::default()
+            }
+        }
+    }
+
+    async fn load_repo_config(&self, repo_info: &RepoInfo) -> anyhow::Result<RepoConfig> {
+        let provider = self.provider(repo_info)?;
+        let repo = format!("{}/{}", repo_info.owner, repo_info.repo);
+        let sha = match self.repo_configs.sha(&repo) {
+            Some(sha) => sha,
+            None => {
+                let sha = provider.get_default_branch_sha(repo_info).await?;
+                self.repo_configs.insert_sha(repo.clone(), sha.clone());
+                sha
+            }
+        };
+        if let Some(config) = self.repo_configs.get(&repo, &sha) {
+            return Ok(config);
+        }
+
+        let path = RepoConfig::path(repo_info.provider());
+        let config = match provider.get_file(repo_info, path, &sha).await? {
+            // Broken config is cached as well, so we don't refetch it until the next commit
+            Some(text) => RepoConfig::parse(&text).unwrap_or_else(|e| {
+                warn!("Invalid {path} in {repo}: {e}");
+                RepoConfig::default()
+            }),
+            None => RepoConfig::default(),
+        };
+        self.repo_configs.insert(repo, sha, config.clone());
+        Ok(config)
+    }
+
     pub async fn check_info(&self, repo_info: &RepoInfo) -> anyhow::Result<PRInfo> {
         self.near
             .check_info(&repo_info.owner, &repo_info.repo, repo_info.number)
@@ -19,11 +115,9 @@ impl Context {
         let result = self.near.add_repo(&repo_info.owner, &repo_info.repo).await;
         info!("Added repo {repo_info:?} to near");
         let message = format!(
-            "New repo in the [{}](https://github.com/{}/{}/pull/{}) was {}",
+            "New repo in the [{}]({}) was {}",
             repo_info.full_id,
-            repo_info.owner,
-            repo_info.repo,
-            repo_info.number,
+            self.pull_request_url(repo_info),
             result.as_ref().map(|_| "added").unwrap_or("failed")
         );
         self.telegram.send_to_telegram(&message, &Level::INFO);
@@ -37,16 +131,12 @@ impl Context {
         comment_id: Option<u64>,
         text: &str,
     ) -> anyhow::Result<CommentRepr> {
+        let provider = self.provider(repo_info)?;
         if let Some(comment_id) = comment_id {
-            self.github
-                .like_comment(&repo_info.owner, &repo_info.repo, comment_id)
-                .await?;
+            provider.like_comment(repo_info, comment_id).await?;
         }
 
-        self.github
-            .reply(&repo_info.owner, &repo_info.repo, repo_info.number, text)
-            .await
-            .map(Into::into)
+        provider.reply(repo_info, text).await
     }
 
     pub async fn reply(
@@ -56,13 +146,29 @@ impl Context {
         msg: MsgCategory,
         args: Vec<(&'static str, String)>,
     ) -> anyhow::Result<CommentRepr> {
-        let text = self.messages.get_message(msg);
+        let text = self.messages().get_message(msg);
 
         let text = text.format(args.into_iter().collect::<HashMap<_, _>>())?;
 
         self.reply_with_text(repo_info, comment_id, &text).await
     }
 
+    // Appends a hidden marker, so the reply can be found among the comments later
+    pub async fn reply_with_marker(
+        &self,
+        repo_info: &RepoInfo,
+        comment_id: Option<u64>,
+        msg: MsgCategory,
+        args: Vec<(&'static str, String)>,
+        marker: &str,
+    ) -> anyhow::Result<CommentRepr> {
+        let text = self.messages().get_message(msg);
+        let text = text.format(args.into_iter().collect::<HashMap<_, _>>())?;
+
+        self.reply_with_text(repo_info, comment_id, &format!("{text}\n{marker}"))
+            .await
+    }
+
     // It does the same, but maybe later we will add some additional logic
     // And it makes visual separation between different types of replies
     pub async fn reply_with_error(
@@ -81,7 +187,7 @@ pub fn extract_command_with_args(
     bot_name: &str,
     comment: &CommentRepr,
 ) -> Option<(String, String)> {
-    let bot_name = format!("@{}", bot_name);
+    let bot_name = format!("@{}", Provider::login(bot_name));
 
     for command in comment.text.lines() {
         let command = command.trim();
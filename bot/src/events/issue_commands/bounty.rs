@@ -0,0 +1,137 @@
+use shared::{
+    github::{RepoInfo, User},
+    PRInfo,
+};
+use tracing::{debug, instrument};
+
+use crate::{
+    api::CommentRepr,
+    events::{Context, EventResult},
+    messages::MsgCategory,
+};
+
+#[derive(Debug, Clone)]
+pub struct BotBounty {
+    pub score: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotBounty {
+    #[instrument(skip(self, repo_info, context, info, sender), fields(issue = repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        repo_info: &RepoInfo,
+        context: Context,
+        info: &mut PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<EventResult> {
+        if !sender.is_maintainer() {
+            debug!(
+                "Tried to set a bounty by not maintainer: {}. Skipping",
+                repo_info.full_id
+            );
+            context
+                .reply_with_error(
+                    repo_info,
+                    self.comment_id,
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        if info.new_repo {
+            context.add_repo(repo_info).await?;
+            info.new_repo = false;
+        }
+
+        if info.paused_repo {
+            context
+                .reply_with_error(
+                    repo_info,
+                    self.comment_id,
+                    MsgCategory::ErrorPausedMessage,
+                    vec![("user", sender.login.clone())],
+                )
+                .await?;
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        // Issues are not stored in the contract, so the info has the default scale
+        let scale = context
+            .repo_config(repo_info)
+            .await
+            .scale
+            .unwrap_or(info.scale);
+        // Linear and t-shirt scales don't have zero, so the cancellation is checked before parsing
+        let score = if self.is_cancel() {
+            0
+        } else {
+            scale.parse(&self.score).0
+        };
+        context
+            .near
+            .send_bounty(repo_info, &sender.login, score, scale)
+            .await?;
+
+        if score == 0 {
+            context
+                .reply(
+                    repo_info,
+                    self.comment_id,
+                    MsgCategory::BountyCancelledMessage,
+                    vec![],
+                )
+                .await?;
+        } else {
+            context
+                .reply(
+                    repo_info,
+                    self.comment_id,
+                    MsgCategory::BountyMessage,
+                    vec![("score", scale.label(score))],
+                )
+                .await?;
+        }
+
+        Ok(EventResult::success(false))
+    }
+
+    fn is_cancel(&self) -> bool {
+        matches!(
+            self.score.split_whitespace().next(),
+            Some(arg) if arg == "0" || arg.eq_ignore_ascii_case("cancel")
+        )
+    }
+
+    pub fn construct(comment: &CommentRepr, args: String) -> super::Command {
+        super::Command::Bounty(BotBounty {
+            score: args,
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BotBounty;
+
+    fn bounty(args: &str) -> BotBounty {
+        BotBounty {
+            score: args.to_string(),
+            timestamp: chrono::Utc::now(),
+            comment_id: None,
+        }
+    }
+
+    #[test]
+    fn cancellation_is_not_parsed_as_score() {
+        assert!(bounty("0").is_cancel());
+        assert!(bounty("Cancel please").is_cancel());
+        assert!(!bounty("10").is_cancel());
+        assert!(!bounty("").is_cancel());
+    }
+}
@@ -8,14 +8,19 @@ use crate::{api::CommentRepr, messages::MsgCategory};
 
 use super::{common, pr_commands::BotUnpaused, Context, EventResult};
 
+mod bounty;
+
+pub use bounty::*;
+
 #[derive(Debug, Clone)]
 pub enum Command {
     Unpause(BotUnpaused),
+    Bounty(BotBounty),
 }
 
 impl Command {
     pub fn parse_command(bot_name: &str, comment: &CommentRepr) -> Option<Command> {
-        let (command, _args) = common::extract_command_with_args(bot_name, comment)?;
+        let (command, args) = common::extract_command_with_args(bot_name, comment)?;
 
         Some(match command.as_str() {
             "yes" | "approve" | "add" | "accept" => Command::Unpause(BotUnpaused {
@@ -23,6 +28,7 @@ impl Command {
                 comment_id: comment.comment_id,
                 from_issue: true,
             }),
+            "bounty" => BotBounty::construct(comment, args),
             _ => return None,
         })
     }
@@ -30,6 +36,7 @@ impl Command {
     pub fn timestamp(&self) -> &chrono::DateTime<chrono::Utc> {
         match self {
             Command::Unpause(event) => &event.timestamp,
+            Command::Bounty(event) => &event.timestamp,
         }
     }
 
@@ -65,6 +72,7 @@ impl Command {
 
         match self {
             Command::Unpause(event) => event.execute(repo_info, context, check_info, sender).await,
+            Command::Bounty(event) => event.execute(repo_info, context, check_info, sender).await,
         }
     }
 }
@@ -75,6 +83,7 @@ impl std::fmt::Display for Command {
             Command::Unpause(_) => {
                 write!(f, "Repository approved")
             }
+            Command::Bounty(_) => write!(f, "Bounty"),
         }
     }
 }
@@ -0,0 +1,216 @@
+use tracing::error;
+
+use shared::{
+    github::{PrMetadata, User},
+    PRInfo,
+};
+
+use crate::api::LabelRepr;
+
+use super::{
+    pr_commands::{BotIncluded, BotScored, Command},
+    Context, Event, EventType,
+};
+
+/// Includes the PR in the race the same way as the `include` command
+pub const INCLUDE_LABEL: &str = "race-of-sloths";
+/// Scores the PR on behalf of the labeler, e.g. `sloth-score:5`
+pub const SCORE_LABEL_PREFIX: &str = "sloth-score:";
+/// Status labels that the bot keeps in sync with the contract if the repository enables them
+pub const INCLUDED_LABEL: &str = "sloth:included";
+pub const FINALIZED_LABEL: &str = "sloth:finalized";
+
+const STATUS_LABELS: [&str; 2] = [INCLUDED_LABEL, FINALIZED_LABEL];
+
+pub fn is_score_label(name: &str) -> bool {
+    name.starts_with(SCORE_LABEL_PREFIX)
+}
+
+pub fn command(label: &LabelRepr) -> Option<Command> {
+    if label.name == INCLUDE_LABEL {
+        return Some(Command::Include(BotIncluded::new(label.timestamp, None)));
+    }
+
+    let score = label.name.strip_prefix(SCORE_LABEL_PREFIX)?;
+    Some(Command::Score(BotScored::new(
+        score.to_string(),
+        label.timestamp,
+        None,
+    )))
+}
+
+// Votes of the score labels, so they aren't retracted as votes without a score comment
+pub fn scores(labels: &[LabelRepr]) -> Vec<(BotScored, User)> {
+    labels
+        .iter()
+        .filter_map(|label| match command(label)? {
+            Command::Score(score) => Some((score, label.user.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Commands of the labels added after the given time. Labels don't notify us,
+/// so the labeled PRs are scanned periodically.
+pub fn label_events(
+    pr: &PrMetadata,
+    labels: &[LabelRepr],
+    since: chrono::DateTime<chrono::Utc>,
+) -> Vec<Event> {
+    labels
+        .iter()
+        .filter(|label| label.timestamp >= since)
+        .filter_map(|label| {
+            Some(Event {
+                event: EventType::PRCommand {
+                    command: command(label)?,
+                    sender: label.user.clone(),
+                    notification: None,
+                    pr: pr.clone(),
+                },
+                comment: None,
+                event_time: label.timestamp,
+            })
+        })
+        .collect()
+}
+
+pub fn status_label(info: &PRInfo) -> Option<&'static str> {
+    if info.executed {
+        Some(FINALIZED_LABEL)
+    } else if info.exist && !info.excluded {
+        Some(INCLUDED_LABEL)
+    } else {
+        None
+    }
+}
+
+impl Context {
+    // Adds the label of the current status and removes the other ones. Failures are only logged
+    pub async fn sync_status_labels(&self, pr: &PrMetadata, info: &PRInfo) {
+        let provider = match self.provider(&pr.repo_info) {
+            Ok(provider) => provider,
+            Err(e) => {
+                error!("Failed to sync labels for {}: {e}", pr.repo_info.full_id);
+                return;
+            }
+        };
+
+        let status = status_label(info);
+        for label in STATUS_LABELS {
+            // Labels of the PRs restored from the contract are unknown, so we don't skip anything
+            let present = pr
+                .labels
+                .as_ref()
+                .map(|labels| labels.iter().any(|name| name == label));
+            let result = match (status == Some(label), present) {
+                (true, Some(true)) | (false, Some(false)) => continue,
+                (true, _) => provider.add_label(&pr.repo_info, label).await,
+                (false, _) => provider.remove_label(&pr.repo_info, label).await,
+            };
+
+            if let Err(e) = result {
+                error!(
+                    "Failed to sync label {label} for {}: {e}",
+                    pr.repo_info.full_id
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use octocrab::models::AuthorAssociation;
+    use shared::{github::User, PRInfo, ScoringScale};
+
+    use crate::{
+        api::{current_labels, LabelChange, LabelRepr},
+        events::{pr_commands::Command, EventType},
+    };
+
+    use super::{label_events, scores, status_label, FINALIZED_LABEL, INCLUDED_LABEL};
+
+    fn label(name: &str, user: &str, hour: u32) -> LabelRepr {
+        LabelRepr {
+            name: name.to_string(),
+            user: User::new(user.to_string(), AuthorAssociation::Collaborator),
+            timestamp: chrono::DateTime::parse_from_rfc3339(&format!(
+                "2024-10-01T{hour:02}:00:00Z"
+            ))
+            .unwrap()
+            .to_utc(),
+        }
+    }
+
+    fn change(name: &str, user: &str, hour: u32, added: bool) -> LabelChange {
+        LabelChange {
+            label: label(name, user, hour),
+            added,
+        }
+    }
+
+    #[test]
+    fn removed_labels_are_dropped() {
+        let labels = current_labels(
+            vec![
+                change("race-of-sloths", "a", 1, true),
+                change("sloth-score:5", "a", 2, true),
+                change("sloth-score:5", "a", 3, false),
+                change("sloth-score:8", "b", 4, true),
+                change("race-of-sloths", "c", 5, true),
+            ]
+            .into_iter(),
+        );
+
+        let names = labels
+            .iter()
+            .map(|label| (label.name.as_str(), label.user.login.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("sloth-score:8", "b"), ("race-of-sloths", "c")]);
+    }
+
+    #[test]
+    fn labels_to_commands() {
+        let labels = vec![
+            label("race-of-sloths", "a", 1),
+            label("sloth-score:L", "b", 2),
+            label("bug", "b", 3),
+            label("sloth-score:3", "c", 4),
+        ];
+
+        let scores = scores(&labels);
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].0.score(ScoringScale::TShirt), (8, false));
+        assert_eq!(scores[0].1.login, "b");
+
+        let pr = crate::events::pr_commands::tests::default_pr_metadata();
+        let events = label_events(&pr, &labels, labels[1].timestamp);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0].event,
+            EventType::PRCommand {
+                command: Command::Score(_),
+                notification: None,
+                sender,
+                ..
+            } if sender.login == "b"
+        ));
+    }
+
+    #[test]
+    fn status_label_follows_the_race() {
+        let mut info = PRInfo::default();
+        assert_eq!(status_label(&info), None);
+
+        info.exist = true;
+        assert_eq!(status_label(&info), Some(INCLUDED_LABEL));
+
+        info.excluded = true;
+        assert_eq!(status_label(&info), None);
+
+        info.excluded = false;
+        info.executed = true;
+        assert_eq!(status_label(&info), Some(FINALIZED_LABEL));
+    }
+}
@@ -4,15 +4,16 @@ use chrono::Utc;
 use tracing::{info, instrument, Level};
 
 use crate::{
-    api::{self, CommentRepr},
-    messages::{FinalMessageData, MessageLoader},
+    api::{self, CommentRepr, CommitStatus},
+    messages::{FinalMessageData, LocalizedMessages, MessageSource, MsgCategory},
+    repo_config::{RepoConfig, RepoConfigCache},
 };
 
 use shared::{
     github::{PrMetadata, RepoInfo, User},
     near::NearClient,
     telegram::TelegramSubscriber,
-    PRInfo, TimePeriod,
+    PRInfo, Provider, TimePeriod,
 };
 
 use self::{actions::Action, pr_commands::Command};
@@ -20,16 +21,23 @@ use self::{actions::Action, pr_commands::Command};
 pub mod actions;
 pub(crate) mod common;
 pub mod issue_commands;
+pub mod labels;
 pub mod pr_commands;
 
 #[derive(Clone)]
 pub struct Context {
     pub github: Arc<api::GithubClient>,
+    pub gitlab: Option<Arc<api::GitlabClient>>,
     pub bot_name: String,
     pub near: Arc<NearClient>,
-    pub messages: Arc<MessageLoader>,
+    // Snapshot of the messages used by the current events, taken from the source that is reloaded in background
+    pub messages: Arc<LocalizedMessages>,
+    pub message_source: Arc<MessageSource>,
+    // Locale of the replies, resolved per PR before executing its events
+    pub locale: Option<String>,
     pub prometheus: Arc<api::prometheus::PrometheusClient>,
     pub telegram: Arc<TelegramSubscriber>,
+    pub repo_configs: Arc<RepoConfigCache>,
 }
 
 pub enum EventResult {
@@ -62,13 +70,30 @@ impl Context {
         info: PRInfo,
         final_data: Option<FinalMessageData>,
     ) {
+        let config = self.repo_config(&pr.repo_info).await;
+        if config.status.checks() {
+            self.commit_status(pr, &info, final_data.as_ref()).await;
+        }
+        if config.status_labels {
+            self.sync_status_labels(pr, &info).await;
+        }
+        if !config.status.comment() {
+            return;
+        }
+
+        let provider = match self.provider(&pr.repo_info) {
+            Ok(provider) => provider,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to update status comment for {}: {e}",
+                    pr.repo_info.full_id
+                );
+                return;
+            }
+        };
+
         if comment.is_none() {
-            comment = self
-                .github
-                .get_bot_comment(&pr.repo_info.owner, &pr.repo_info.repo, pr.repo_info.number)
-                .await
-                .ok()
-                .flatten();
+            comment = provider.get_bot_comment(&pr.repo_info).await.ok().flatten();
         }
 
         let result = match comment {
@@ -79,7 +104,10 @@ impl Context {
                     msg
                 } else {
                     // Couldn't update the message, it probabl means we try to overwrite some other message (as example, that repo is paused)
-                    match self.new_status_message(pr, &info, final_data).await {
+                    match self
+                        .new_status_message(pr, &info, final_data, &config)
+                        .await
+                    {
                         Ok(msg) => msg,
                         Err(e) => {
                             tracing::error!(
@@ -91,23 +119,15 @@ impl Context {
                     }
                 };
 
-                self.github
-                    .edit_comment(&pr.repo_info.owner, &pr.repo_info.repo, comment.id, &msg)
-                    .await
+                provider.edit_comment(&pr.repo_info, comment.id, &msg).await
             }
             None => {
                 // No comment found, create a new one
-                match self.new_status_message(pr, &info, final_data).await {
-                    Ok(msg) => self
-                        .github
-                        .reply(
-                            &pr.repo_info.owner,
-                            &pr.repo_info.repo,
-                            pr.repo_info.number,
-                            &msg,
-                        )
-                        .await
-                        .map(|_| ()),
+                match self
+                    .new_status_message(pr, &info, final_data, &config)
+                    .await
+                {
+                    Ok(msg) => provider.reply(&pr.repo_info, &msg).await.map(|_| ()),
                     Err(e) => {
                         tracing::error!(
                             "Failed to get new status message for {}: {e}",
@@ -132,6 +152,7 @@ impl Context {
         pr: &PrMetadata,
         info: &PRInfo,
         final_data: Option<FinalMessageData>,
+        config: &RepoConfig,
     ) -> anyhow::Result<String> {
         let timestamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
         let user = self
@@ -146,9 +167,57 @@ impl Context {
             )
             .await?;
 
-        Ok(self
-            .messages
-            .include_message_text(&self.bot_name, info, pr, user, final_data))
+        Ok(self.messages().include_message_text(
+            &self.bot_name,
+            info,
+            pr,
+            user,
+            final_data,
+            config.intro.as_deref(),
+        ))
+    }
+
+    // Publishes the status on the last commit of the PR. Failures are logged the same way as for the status comment
+    pub async fn commit_status(
+        &self,
+        pr: &PrMetadata,
+        info: &PRInfo,
+        final_data: Option<&FinalMessageData>,
+    ) {
+        if let Err(e) = self.try_commit_status(pr, info, final_data).await {
+            tracing::error!(
+                "Failed to update commit status for {}: {e}",
+                pr.repo_info.full_id
+            );
+        }
+    }
+
+    async fn try_commit_status(
+        &self,
+        pr: &PrMetadata,
+        info: &PRInfo,
+        final_data: Option<&FinalMessageData>,
+    ) -> anyhow::Result<()> {
+        let provider = self.provider(&pr.repo_info)?;
+        let sha = match &pr.head_sha {
+            Some(sha) => sha.clone(),
+            // PRs restored from the contract don't know their commits
+            None => provider
+                .get_pull_request_metadata(&pr.repo_info)
+                .await?
+                .0
+                .head_sha
+                .ok_or_else(|| anyhow::anyhow!("PR has no head commit"))?,
+        };
+
+        let status = CommitStatus {
+            description: self.messages().check_status_message(info, pr, final_data)?,
+            target_url: self.messages().link.clone(),
+        };
+
+        provider
+            .set_commit_status(&pr.repo_info, &sha, &status)
+            .await
     }
 
     fn try_update_message(
@@ -159,7 +228,7 @@ impl Context {
         final_data: Option<FinalMessageData>,
     ) -> Option<String> {
         let status = self
-            .messages
+            .messages()
             .status_message(&self.bot_name, info, pr, final_data)
             .unwrap_or_else(|err| {
                 tracing::error!(
@@ -169,7 +238,7 @@ impl Context {
                 String::default()
             });
 
-        self.messages.update_pr_status_message(text, status)
+        self.messages().update_pr_status_message(text, status)
     }
 }
 
@@ -185,7 +254,44 @@ impl Event {
         context: Context,
         check_info: &mut PRInfo,
     ) -> anyhow::Result<EventResult> {
-        let result = match &self.event {
+        let result = if check_info.paused_contract || check_info.migrating_contract {
+            self.reply_paused_contract(&context).await
+        } else {
+            self.execute_unpaused(context.clone(), check_info).await
+        };
+
+        match &self.event {
+            EventType::PRCommand { pr, .. } | EventType::Action { pr, .. } => {
+                context.prometheus.record_pr(
+                    &self.event,
+                    &pr.repo_info,
+                    Some(&pr.author),
+                    &result,
+                    self.event_time,
+                );
+            }
+            EventType::IssueCommand { repo_info, .. } => {
+                context.prometheus.record_pr(
+                    &self.event,
+                    repo_info,
+                    None,
+                    &result,
+                    self.event_time,
+                );
+            }
+        }
+
+        send_event_to_telegram(&context, self, &result);
+
+        result
+    }
+
+    async fn execute_unpaused(
+        &self,
+        context: Context,
+        check_info: &mut PRInfo,
+    ) -> anyhow::Result<EventResult> {
+        match &self.event {
             EventType::PRCommand {
                 command,
                 sender,
@@ -201,9 +307,10 @@ impl Event {
                         self.comment.is_none(),
                     )
                     .await;
-                if should_update.is_ok() {
+                // Commands from the labels don't come with a notification
+                if let (Ok(_), Some(notification)) = (&should_update, notification) {
                     context
-                        .github
+                        .provider(&pr.repo_info)?
                         .mark_notification_as_read(*notification)
                         .await?;
                 }
@@ -229,47 +336,84 @@ impl Event {
                     .await;
                 if result.is_ok() {
                     context
-                        .github
+                        .provider(repo_info)?
                         .mark_notification_as_read(*notification)
                         .await?;
                 }
                 result
             }
-        };
-
-        match &self.event {
-            EventType::PRCommand { pr, .. } | EventType::Action { pr, .. } => {
-                context.prometheus.record_pr(
-                    &self.event,
-                    &pr.repo_info,
-                    Some(&pr.author),
-                    &result,
-                    self.event_time,
-                );
-            }
-            EventType::IssueCommand { repo_info, .. } => {
-                context.prometheus.record_pr(
-                    &self.event,
-                    repo_info,
-                    None,
-                    &result,
-                    self.event_time,
-                );
-            }
         }
+    }
 
-        send_event_to_telegram(&context.telegram, self, &result);
+    // Commands of the users are answered with the maintenance message, the rest waits until the contract is unpaused.
+    // Notifications stay unread, so the commands are run after the maintenance. Until then they come every round,
+    // so a command is answered only if there is no maintenance message after it yet
+    async fn reply_paused_contract(&self, context: &Context) -> anyhow::Result<EventResult> {
+        let (repo_info, sender, notification) = match &self.event {
+            EventType::PRCommand {
+                pr,
+                sender,
+                notification,
+                ..
+            } => (&pr.repo_info, sender, *notification),
+            EventType::IssueCommand {
+                repo_info,
+                sender,
+                notification,
+                ..
+            } => (repo_info, sender, Some(*notification)),
+            EventType::Action { .. } => return Ok(EventResult::Skipped),
+        };
+        // Commands made up by the bot itself, e.g. the auto-include or labels, have nobody to answer
+        if notification.is_none() && self.comment.is_none() {
+            return Ok(EventResult::Skipped);
+        }
+        let comments = context
+            .provider(repo_info)?
+            .get_comments_since(repo_info, self.event_time)
+            .await?;
+        if answered_while_paused(&comments, self.event_time) {
+            return Ok(EventResult::Skipped);
+        }
+        info!(
+            "Sloth called for {} while the contract is paused",
+            repo_info.full_id
+        );
 
-        result
+        context
+            .reply_with_marker(
+                repo_info,
+                None,
+                MsgCategory::ErrorContractPausedMessage,
+                vec![("user", sender.login.clone())],
+                PAUSED_MARKER,
+            )
+            .await?;
+        Ok(EventResult::RepliedWithError)
     }
 }
 
+/// Hidden marker of the maintenance message. The commands before it are still pending,
+/// so the bot looks past it for the commands once the contract is unpaused
+pub const PAUSED_MARKER: &str = "<!-- sloth:paused -->";
+
+pub(crate) fn answered_while_paused(
+    comments: &[CommentRepr],
+    command_time: chrono::DateTime<Utc>,
+) -> bool {
+    comments
+        .iter()
+        .any(|comment| comment.text.contains(PAUSED_MARKER) && comment.timestamp >= command_time)
+}
+
+// Events are short-lived, so boxing the commands isn't worth it
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum EventType {
     PRCommand {
         command: Command,
         sender: User,
-        notification: crate::api::Notification,
+        notification: Option<crate::api::Notification>,
         pr: PrMetadata,
     },
     Action {
@@ -295,6 +439,14 @@ impl EventType {
         }
     }
 
+    // User the bot talks to, whose language is used for the replies
+    pub fn recipient(&self) -> &str {
+        match self {
+            EventType::PRCommand { pr, .. } | EventType::Action { pr, .. } => &pr.author.login,
+            EventType::IssueCommand { sender, .. } => &sender.login,
+        }
+    }
+
     pub fn same_event(&self, other: &Self) -> bool {
         match (self, other) {
             (
@@ -323,25 +475,28 @@ impl EventType {
     }
 }
 
-impl std::fmt::Display for EventType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl EventType {
+    // Links the sender to their profile on the provider of the PR
+    fn description(&self, context: &Context) -> String {
         match self {
             EventType::PRCommand {
-                command, sender, ..
-            } => write!(
-                f,
-                "PR Command `{}` send by [{name}](https://github.com/{name})",
                 command,
-                name = sender.login
+                sender,
+                pr,
+                ..
+            } => format!(
+                "PR Command `{command}` send by [{}]({})",
+                Provider::login(&sender.login),
+                context.user_url(&pr.repo_info, &sender.login)
             ),
-            EventType::Action { action, .. } => write!(f, "Action `{action}`",),
-            EventType::IssueCommand { command, .. } => write!(f, "Issue Command `{command}`",),
+            EventType::Action { action, .. } => format!("Action `{action}`"),
+            EventType::IssueCommand { command, .. } => format!("Issue Command `{command}`"),
         }
     }
 }
 
 fn send_event_to_telegram(
-    telegram: &Arc<TelegramSubscriber>,
+    context: &Context,
     event: &crate::events::Event,
     result: &anyhow::Result<EventResult>,
 ) {
@@ -353,8 +508,73 @@ fn send_event_to_telegram(
 
     let repo_info = event.event.repo_info();
     let message = format!(
-        "{} in the [{}](https://github.com/{}/{}/pull/{}) was {}",
-        event.event, repo_info.full_id, repo_info.owner, repo_info.repo, repo_info.number, text
+        "{} in the [{}]({}) was {}",
+        event.event.description(context),
+        repo_info.full_id,
+        context.pull_request_url(repo_info),
+        text
     );
-    telegram.send_to_telegram(&message, &Level::INFO);
+    context.telegram.send_to_telegram(&message, &Level::INFO);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use chrono::{DateTime, Duration, Utc};
+    use octocrab::models::AuthorAssociation;
+    use shared::github::User;
+
+    use crate::{
+        api::{pr_command_events, CommentRepr, Notification},
+        events::{pr_commands::tests::default_pr_metadata, EventType},
+    };
+
+    use super::{answered_while_paused, PAUSED_MARKER};
+
+    fn comment(login: &str, text: &str, minutes: i64) -> CommentRepr {
+        let timestamp: DateTime<Utc> = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        CommentRepr {
+            id: 0,
+            user: User::new(login.to_string(), AuthorAssociation::Contributor),
+            timestamp: timestamp + Duration::minutes(minutes),
+            text: text.to_string(),
+            comment_id: Some(0),
+            edited: false,
+        }
+    }
+
+    #[test]
+    fn commands_answered_while_paused_are_pending() {
+        let pr = default_pr_metadata();
+        let handles: BTreeSet<String> = ["sloth".to_string()].into_iter().collect();
+        let notification = Notification {
+            id: 0,
+            read_client_id: 0,
+        };
+        let command = comment("a-u", "@sloth exclude", 1);
+        let paused = comment("sloth", &format!("Maintenance\n{PAUSED_MARKER}"), 2);
+        let comments = vec![comment("sloth", "Status", 0), command.clone(), paused];
+
+        let (events, _) = pr_command_events(&handles, &pr, comments, &[], None, notification);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0].event, EventType::PRCommand { .. }));
+
+        // Other replies of the bot still mean the commands before them were processed
+        let comments = vec![command.clone(), comment("sloth", "Excluded", 2)];
+        let (events, _) = pr_command_events(&handles, &pr, comments, &[], None, notification);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn paused_command_is_answered_once() {
+        let command = comment("a-u", "@sloth exclude", 1);
+        assert!(!answered_while_paused(&[], command.timestamp));
+
+        let earlier = comment("sloth", PAUSED_MARKER, 0);
+        assert!(!answered_while_paused(&[earlier], command.timestamp));
+
+        let reply = comment("sloth", PAUSED_MARKER, 2);
+        assert!(answered_while_paused(&[reply], command.timestamp));
+    }
 }
@@ -0,0 +1,63 @@
+use tracing::instrument;
+
+use crate::messages::MsgCategory;
+
+use shared::github::User;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotAutoInclude {
+    pub enabled: bool,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotAutoInclude {
+    #[instrument(skip(self, pr, context, sender), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        sender: &User,
+    ) -> anyhow::Result<EventResult> {
+        context.near.send_auto_include(sender, self.enabled).await?;
+
+        context
+            .reply(
+                &pr.repo_info,
+                self.comment_id,
+                MsgCategory::AutoIncludeMessage,
+                vec![
+                    ("user", sender.login.clone()),
+                    // Templates treat an empty string as false
+                    (
+                        "enabled",
+                        self.enabled.then(|| "true".to_string()).unwrap_or_default(),
+                    ),
+                ],
+            )
+            .await?;
+        Ok(EventResult::success(false))
+    }
+
+    pub fn construct(comment: &CommentRepr, command: String, args: String) -> Command {
+        let enabled = match args
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "on" | "yes" | "enable" => true,
+            "off" | "no" | "disable" => false,
+            _ => return UnknownCommand::construct(comment, command, args),
+        };
+
+        Command::AutoInclude(BotAutoInclude {
+            enabled,
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use tracing::{debug, instrument};
+
+use crate::messages::{format_handles, MsgCategory};
+
+use shared::{github::User, PRInfo, Provider, MAX_COAUTHORS};
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotCoauthors {
+    pub coauthors: Vec<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotCoauthors {
+    #[instrument(skip(self, pr, context, info, sender), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        info: &mut PRInfo,
+        sender: &User,
+    ) -> anyhow::Result<EventResult> {
+        if !info.exist {
+            BotIncluded::new(self.timestamp, self.comment_id)
+                .execute(pr, context.clone(), info, sender)
+                .await?;
+        }
+
+        // Info is updated in the previous call
+        if !info.exist {
+            debug!(
+                "Sloth is not included in {}. Skipping.",
+                pr.repo_info.full_id
+            );
+            return Ok(EventResult::Skipped);
+        }
+
+        if sender.login != pr.author.login && !sender.is_maintainer() {
+            debug!(
+                "Tried to set co-authors by not author or maintainer in {}. Skipping",
+                pr.repo_info.full_id
+            );
+            context
+                .reply_with_error(
+                    &pr.repo_info,
+                    self.comment_id,
+                    MsgCategory::ErrorRightsViolationMessage,
+                    vec![],
+                )
+                .await?;
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        let coauthors =
+            self.coauthors(pr.repo_info.provider(), &pr.author.login, &context.bot_name);
+        // Scorers would rate their own PR
+        let scorer_included = coauthors
+            .iter()
+            .any(|coauthor| info.votes.iter().any(|vote| &vote.user == coauthor));
+        if info.merged || coauthors.len() > MAX_COAUTHORS || scorer_included {
+            debug!(
+                "Co-authors can't be set for {}. Skipping",
+                pr.repo_info.full_id
+            );
+            context
+                .reply_with_error(
+                    &pr.repo_info,
+                    self.comment_id,
+                    MsgCategory::ErrorCoauthorsMessage,
+                    vec![("max_coauthors", MAX_COAUTHORS.to_string())],
+                )
+                .await?;
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        // Handles can be taken by other users after a rename, so the accounts are linked by the ids
+        let provider = context.provider(&pr.repo_info)?;
+        let mut github_ids = vec![];
+        for coauthor in &coauthors {
+            github_ids.push(provider.get_user_id(coauthor).await?);
+        }
+        context
+            .near
+            .send_coauthors(pr, &coauthors, &github_ids)
+            .await?;
+        info.coauthors = coauthors;
+
+        context
+            .reply(
+                &pr.repo_info,
+                self.comment_id,
+                MsgCategory::CoauthorsMessage,
+                vec![("coauthors", format_handles(&info.coauthors))],
+            )
+            .await?;
+        Ok(EventResult::success(true))
+    }
+
+    // Namespaces the handles by the provider and removes duplicates, the PR author and the bot itself
+    pub fn coauthors(&self, provider: Provider, author: &str, bot_name: &str) -> Vec<String> {
+        let mut result: Vec<String> = vec![];
+        for coauthor in &self.coauthors {
+            let coauthor = provider.user(coauthor);
+            if coauthor != author && coauthor != bot_name && !result.contains(&coauthor) {
+                result.push(coauthor);
+            }
+        }
+        result
+    }
+
+    pub fn construct(comment: &CommentRepr, args: String) -> Command {
+        let coauthors = args
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter_map(|handle| handle.strip_prefix('@'))
+            .filter(|handle| {
+                !handle.is_empty() && handle.chars().all(|c| c.is_alphanumeric() || c == '-')
+            })
+            .map(ToString::to_string)
+            .collect();
+
+        Command::Coauthors(BotCoauthors {
+            coauthors,
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
@@ -43,6 +43,7 @@ impl BotExcluded {
         *check_info = PRInfo {
             exist: false,
             votes: vec![],
+            coauthors: vec![],
             merged: false,
             executed: false,
             excluded: true,
@@ -0,0 +1,33 @@
+use tracing::instrument;
+
+use crate::messages::MsgCategory;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotHelp {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotHelp {
+    #[instrument(skip(self, pr, context), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(&self, pr: &PrMetadata, context: Context) -> anyhow::Result<EventResult> {
+        context
+            .reply(
+                &pr.repo_info,
+                self.comment_id,
+                MsgCategory::HelpMessage,
+                vec![],
+            )
+            .await?;
+        Ok(EventResult::success(false))
+    }
+
+    pub fn construct(comment: &CommentRepr) -> Command {
+        Command::Help(BotHelp {
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
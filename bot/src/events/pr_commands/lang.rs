@@ -0,0 +1,84 @@
+use tracing::{debug, instrument};
+
+use crate::messages::MsgCategory;
+
+use shared::github::User;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotLang {
+    // Empty locale resets the preference to the repository one
+    pub locale: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotLang {
+    #[instrument(skip(self, pr, context, sender), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        mut context: Context,
+        sender: &User,
+    ) -> anyhow::Result<EventResult> {
+        if !self.locale.is_empty() && !context.messages.is_supported(&self.locale) {
+            debug!(
+                "Unknown locale {} requested in {}",
+                self.locale, pr.repo_info.full_id
+            );
+            context
+                .reply_with_error(
+                    &pr.repo_info,
+                    self.comment_id,
+                    MsgCategory::ErrorUnknownLocaleMessage,
+                    vec![
+                        ("locale", self.locale.clone()),
+                        ("locales", context.messages.locales().join(", ")),
+                    ],
+                )
+                .await?;
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        let locale = (!self.locale.is_empty()).then_some(self.locale.as_str());
+        context.near.send_locale(sender, locale).await?;
+
+        // Confirm in the language that was just chosen
+        context.locale = match locale {
+            Some(locale) => Some(locale.to_string()),
+            None => context
+                .repo_config(&pr.repo_info)
+                .await
+                .locale
+                .filter(|locale| context.messages.is_supported(locale)),
+        };
+        context
+            .reply(
+                &pr.repo_info,
+                self.comment_id,
+                MsgCategory::LangMessage,
+                vec![("user", sender.login.clone())],
+            )
+            .await?;
+        Ok(EventResult::success(false))
+    }
+
+    pub fn construct(comment: &CommentRepr, args: String) -> Command {
+        let locale = args
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let locale = match locale.as_str() {
+            "default" | "reset" => String::new(),
+            _ => locale,
+        };
+
+        Command::Lang(BotLang {
+            locale,
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
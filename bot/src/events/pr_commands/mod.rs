@@ -5,15 +5,23 @@ use crate::messages::MsgCategory;
 
 use super::*;
 
+pub mod auto_include;
+pub mod coauthors;
 pub mod exclude;
+pub mod help;
+pub mod lang;
 pub mod pause;
 pub mod score;
 pub mod start;
+pub mod status;
 pub mod unknown;
 pub mod update;
 
 use self::api::CommentRepr;
-pub use self::{exclude::*, pause::*, score::*, start::*, unknown::*};
+pub use self::{
+    auto_include::*, coauthors::*, exclude::*, help::*, lang::*, pause::*, score::*, start::*,
+    status::*, unknown::*,
+};
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -24,6 +32,11 @@ pub enum Command {
     Excluded(BotExcluded),
     Unknown(UnknownCommand),
     Update(BotUpdated),
+    Coauthors(BotCoauthors),
+    Help(BotHelp),
+    Status(BotStatus),
+    Lang(BotLang),
+    AutoInclude(BotAutoInclude),
 }
 
 impl Command {
@@ -41,6 +54,15 @@ impl Command {
             "exclude" | "leave" => BotExcluded::construct(comment),
             "include" | "in" | "start" | "join" | "invite" | "" => BotIncluded::construct(comment),
             "update" => BotUpdated::construct(comment),
+            "help" | "commands" => BotHelp::construct(comment),
+            "status" | "info" => BotStatus::construct(comment),
+            "lang" | "language" | "locale" => BotLang::construct(comment, args),
+            "autoinclude" | "auto-include" | "auto" => {
+                BotAutoInclude::construct(comment, command, args)
+            }
+            "coauthors" | "coauthor" | "co-authors" | "pair" => {
+                BotCoauthors::construct(comment, args)
+            }
             _ if command.chars().all(char::is_numeric) && !command.is_empty() => {
                 BotScored::construct(comment, command)
             }
@@ -67,6 +89,11 @@ impl Command {
             Command::Excluded(event) => &event.timestamp,
             Command::Unknown(event) => &event.timestamp,
             Command::Update(event) => &event.timestamp,
+            Command::Coauthors(event) => &event.timestamp,
+            Command::Help(event) => &event.timestamp,
+            Command::Status(event) => &event.timestamp,
+            Command::Lang(event) => &event.timestamp,
+            Command::AutoInclude(event) => &event.timestamp,
         }
     }
 
@@ -104,7 +131,16 @@ impl Command {
             return Ok(EventResult::Skipped);
         }
 
-        if check_info.paused_repo && !matches!(self, Command::Unpause(_) | Command::Pause(_)) {
+        if check_info.paused_repo
+            && !matches!(
+                self,
+                Command::Unpause(_)
+                    | Command::Pause(_)
+                    | Command::Help(_)
+                    | Command::Lang(_)
+                    | Command::AutoInclude(_)
+            )
+        {
             info!(
                 "Sloth called for a PR from paused repo: {}. Skipping",
                 pr.repo_info.full_id
@@ -125,7 +161,12 @@ impl Command {
             return Ok(EventResult::Skipped);
         }
 
-        if check_info.executed {
+        if check_info.executed
+            && !matches!(
+                self,
+                Command::Help(_) | Command::Status(_) | Command::Lang(_) | Command::AutoInclude(_)
+            )
+        {
             info!(
                 "Sloth called for a PR that is already executed: {}. Skipping",
                 pr.repo_info.full_id
@@ -144,7 +185,16 @@ impl Command {
             return Ok(EventResult::RepliedWithError);
         }
 
-        if check_info.excluded && !matches!(self, Command::Include(_)) {
+        if check_info.excluded
+            && !matches!(
+                self,
+                Command::Include(_)
+                    | Command::Help(_)
+                    | Command::Status(_)
+                    | Command::Lang(_)
+                    | Command::AutoInclude(_)
+            )
+        {
             info!(
                 "Sloth called for a PR from excluded PR: {}. Skipping",
                 pr.repo_info.full_id
@@ -165,6 +215,11 @@ impl Command {
             Command::Excluded(event) => event.execute(pr, context, check_info).await,
             Command::Unknown(event) => event.execute(pr, context, check_info, sender).await,
             Command::Update(event) => event.execute(pr, context, check_info, sender).await,
+            Command::Coauthors(event) => event.execute(pr, context, check_info, sender).await,
+            Command::Help(event) => event.execute(pr, context).await,
+            Command::Status(event) => event.execute(pr, context, check_info).await,
+            Command::Lang(event) => event.execute(pr, context, sender).await,
+            Command::AutoInclude(event) => event.execute(pr, context, sender).await,
         }
     }
 }
@@ -179,13 +234,21 @@ impl std::fmt::Display for Command {
             Command::Excluded(_) => write!(f, "Excluded"),
             Command::Unknown(_) => write!(f, "Unknown"),
             Command::Update(_) => write!(f, "Update"),
+            Command::Coauthors(_) => write!(f, "Coauthors"),
+            Command::Help(_) => write!(f, "Help"),
+            Command::Status(_) => write!(f, "Status"),
+            Command::Lang(_) => write!(f, "Lang"),
+            Command::AutoInclude(_) => write!(f, "AutoInclude"),
         }
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use shared::github::{PrMetadata, RepoInfo, User};
+    use shared::{
+        github::{PrMetadata, RepoInfo, User},
+        Provider,
+    };
 
     use crate::api::CommentRepr;
 
@@ -201,6 +264,7 @@ pub mod tests {
             timestamp: chrono::Utc::now(),
             comment_id: Some(111),
             text: text.to_string(),
+            edited: false,
         }
     }
 
@@ -210,7 +274,7 @@ pub mod tests {
 
     const NAME: &str = "@name";
 
-    fn default_pr_metadata() -> PrMetadata {
+    pub(crate) fn default_pr_metadata() -> PrMetadata {
         PrMetadata {
             repo_info: RepoInfo {
                 owner: "a".to_string(),
@@ -227,6 +291,9 @@ pub mod tests {
             updated_at: chrono::Utc::now(),
             body: "abc".to_string(),
             closed: false,
+            head_sha: None,
+            labels: None,
+            draft: false,
         }
     }
 
@@ -327,6 +394,124 @@ compile_error!("Compiler configuration is unsupported by <ENV NAME HERE>, becaus
         }
     }
 
+    #[test]
+    pub fn correct_help() {
+        let aliases = vec!["help", "commands"];
+        for alias in aliases {
+            let help_comment = generate_command_comment(alias);
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &help_comment).unwrap();
+
+            assert!(matches!(command, Command::Help(_)))
+        }
+    }
+
+    #[test]
+    pub fn correct_status() {
+        let aliases = vec!["status", "info"];
+        for alias in aliases {
+            let status_comment = generate_command_comment(alias);
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &status_comment).unwrap();
+
+            assert!(matches!(command, Command::Status(_)))
+        }
+    }
+
+    #[test]
+    pub fn correct_coauthors() {
+        let aliases = vec!["coauthors", "coauthor", "co-authors", "pair"];
+        for alias in aliases {
+            let coauthors_comment = generate_command_comment(&format!("{alias} @a, @b-c"));
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &coauthors_comment).unwrap();
+
+            match command {
+                Command::Coauthors(event) => {
+                    assert_eq!(event.coauthors, vec!["a".to_string(), "b-c".to_string()])
+                }
+                _ => panic!("Expected coauthors command, got {command:?}"),
+            }
+        }
+    }
+
+    #[test]
+    pub fn correct_lang() {
+        let aliases = vec!["lang", "language", "locale"];
+        for alias in aliases {
+            let lang_comment = generate_command_comment(&format!("{alias} ES"));
+            let command =
+                Command::parse_command(NAME, &default_pr_metadata(), &lang_comment).unwrap();
+
+            match command {
+                Command::Lang(event) => assert_eq!(event.locale, "es"),
+                _ => panic!("Expected lang command, got {command:?}"),
+            }
+        }
+
+        for reset in ["lang", "lang default", "lang reset"] {
+            let comment = generate_command_comment(reset);
+            match Command::parse_command(NAME, &default_pr_metadata(), &comment).unwrap() {
+                Command::Lang(event) => assert!(event.locale.is_empty()),
+                command => panic!("Expected lang command, got {command:?}"),
+            }
+        }
+    }
+
+    #[test]
+    pub fn correct_auto_include() {
+        for (text, expected) in [
+            ("autoinclude", true),
+            ("auto-include off", false),
+            ("auto on", true),
+            ("autoinclude disable", false),
+        ] {
+            let comment = generate_command_comment(text);
+            match Command::parse_command(NAME, &default_pr_metadata(), &comment).unwrap() {
+                Command::AutoInclude(event) => assert_eq!(event.enabled, expected),
+                command => panic!("Expected auto-include command, got {command:?}"),
+            }
+        }
+
+        let comment = generate_command_comment("autoinclude sometimes");
+        assert!(matches!(
+            Command::parse_command(NAME, &default_pr_metadata(), &comment).unwrap(),
+            Command::Unknown(_)
+        ));
+    }
+
+    #[test]
+    pub fn coauthors_filter_author_and_duplicates() {
+        let comment = generate_command_comment("coauthors @a-u @a @a not-a-handle @@name");
+        let command = Command::parse_command(NAME, &default_pr_metadata(), &comment).unwrap();
+
+        match command {
+            Command::Coauthors(event) => {
+                assert_eq!(
+                    event.coauthors(Provider::GitHub, "a-u", NAME),
+                    vec!["a".to_string()]
+                );
+                // GitLab users can't be confused with the GitHub ones
+                assert_eq!(
+                    event.coauthors(Provider::GitLab, "gitlab:a-u", NAME),
+                    vec!["gitlab:a".to_string()]
+                );
+            }
+            _ => panic!("Expected coauthors command, got {command:?}"),
+        }
+    }
+
+    #[test]
+    pub fn namespaced_bot_is_mentioned_by_login() {
+        let comment = generate_command_comment("score 5");
+        let command = Command::parse_command(
+            &Provider::GitLab.user(NAME),
+            &default_pr_metadata(),
+            &comment,
+        );
+        assert!(matches!(command, Some(Command::Score(_))));
+    }
+
     #[test]
     pub fn correct_unknown() {
         let aliases = vec!["", "asdasdasdas", "hello workld"];
@@ -2,7 +2,7 @@ use tracing::{debug, instrument};
 
 use crate::messages::MsgCategory;
 
-use shared::{github::User, PRInfo, Score};
+use shared::{github::User, PRInfo, Score, ScoringScale};
 
 use super::*;
 
@@ -28,25 +28,9 @@ impl BotScored {
         }
     }
 
-    pub fn score(&self) -> (u8, bool) {
-        let score: Option<u32> = self
-            .score
-            .split_whitespace()
-            .next()
-            .and_then(|s| s.parse::<u32>().ok());
-
-        match score {
-            None => (0, true),
-            Some(score) => match score {
-                0 | 1 | 2 | 3 | 5 | 8 | 13 => (score as u8, false),
-                // edit to nearest valid score
-                number => {
-                    let mut valid_scores: Vec<i64> = vec![0, 1, 2, 3, 5, 8, 13];
-                    valid_scores.sort_by_key(|&x| (x - number as i64).abs());
-                    (valid_scores[0] as u8, true)
-                }
-            },
-        }
+    // Returns the points and a flag if the score was corrected to the nearest valid one
+    pub fn score(&self, scale: ScoringScale) -> (u32, bool) {
+        scale.parse(&self.score)
     }
 }
 
@@ -82,11 +66,12 @@ impl BotScored {
             return Ok(EventResult::Skipped);
         }
 
-        let (number, edited) = self.score();
+        let (number, edited) = self.score(info.scale);
 
-        if pr.author.login == sender.login {
+        // Co-authors share the rating, so they can't score the PR either
+        if pr.author.login == sender.login || info.coauthors.contains(&sender.login) {
             debug!(
-                "Author tried to score their own PR {}. Skipping.",
+                "Participant tried to score their own PR {}. Skipping.",
                 pr.repo_info.full_id,
             );
             if !self.muted {
@@ -102,17 +87,32 @@ impl BotScored {
             return Ok(EventResult::RepliedWithError);
         }
 
-        context
-            .near
-            .send_scored(pr, &sender.login, number as u64)
-            .await?;
+        if !context.repo_config(&pr.repo_info).await.can_score(sender) {
+            debug!(
+                "User {} is not allowed to score {} by the repository config. Skipping.",
+                sender.login, pr.repo_info.full_id,
+            );
+            if !self.muted {
+                context
+                    .reply_with_error(
+                        &pr.repo_info,
+                        self.comment_id,
+                        MsgCategory::ErrorScoringRestrictedMessage,
+                        vec![],
+                    )
+                    .await?;
+            }
+            return Ok(EventResult::RepliedWithError);
+        }
+
+        context.near.send_scored(pr, sender, number as u64).await?;
 
         if let Some(vote) = info.votes.iter_mut().find(|v| v.user == sender.login) {
-            vote.score = number as u32;
+            vote.score = number;
         } else {
             info.votes.push(Score {
                 user: sender.login.clone(),
-                score: number as u32,
+                score: number,
             });
         }
 
@@ -124,15 +124,17 @@ impl BotScored {
                     MsgCategory::CorrectableScoringMessage,
                     vec![
                         ("reviewer", sender.login.clone()),
-                        ("corrected_score", number.to_string()),
+                        ("corrected_score", info.scale.label(number)),
                         ("score", self.score.clone()),
+                        ("scale", info.scale.to_string()),
+                        ("valid_scores", info.scale.labels().join(", ")),
                     ],
                 )
                 .await?;
         } else if let Some(comment) = self.comment_id {
             context
-                .github
-                .like_comment(&pr.repo_info.owner, &pr.repo_info.repo, comment)
+                .provider(&pr.repo_info)?
+                .like_comment(&pr.repo_info, comment)
                 .await?;
         }
 
@@ -155,48 +157,64 @@ impl BotScored {
 
 #[cfg(test)]
 mod tests {
+    use shared::ScoringScale;
+
     use super::pr_commands::BotScored;
 
     #[test]
     pub fn score_parsing() {
         assert_eq!(
             (5, false),
-            BotScored::new("5".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("5".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (5, false),
-            BotScored::new("5 ".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("5 ".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (5, false),
-            BotScored::new("5 asdasdas".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("5 asdasdas".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (0, true),
-            BotScored::new("as".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("as".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (0, false),
-            BotScored::new("0".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("0".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (8, true),
-            BotScored::new("9".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("9".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (8, true),
-            BotScored::new("7".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("7".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
         );
 
         assert_eq!(
             (0, true),
-            BotScored::new("".to_string(), chrono::Utc::now(), Some(1)).score()
+            BotScored::new("".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::Fibonacci)
+        );
+
+        assert_eq!(
+            (8, false),
+            BotScored::new("L".to_string(), chrono::Utc::now(), Some(1))
+                .score(ScoringScale::TShirt)
         );
     }
 }
@@ -1,16 +1,73 @@
 use chrono::Duration;
+use octocrab::models::AuthorAssociation;
 use tracing::{debug, instrument};
 
 use crate::messages::MsgCategory;
 
-use shared::github::User;
+use shared::{github::User, Provider};
 
 use super::*;
 
+/// Hidden marker of the reply to the include request of a draft, it's resumed when the PR is ready for review.
+/// The requester follows it as `<!-- sloth:draft-include:login -->`, the first replies had no requester
+pub const DRAFT_INCLUDE_MARKER: &str = "<!-- sloth:draft-include";
+
+pub fn draft_include_marker(requester: &str) -> String {
+    format!("{DRAFT_INCLUDE_MARKER}:{requester} -->")
+}
+
+// Requester of the latest deferred include, if there is one. The replies without a requester were made for the author
+pub fn draft_include_requester(pr: &PrMetadata, comments: &[CommentRepr]) -> Option<User> {
+    let reply = comments
+        .iter()
+        .filter(|comment| comment.text.contains(DRAFT_INCLUDE_MARKER))
+        .max_by_key(|comment| comment.timestamp)?;
+    let login = reply
+        .text
+        .split_once(DRAFT_INCLUDE_MARKER)
+        .and_then(|(_, rest)| rest.strip_prefix(':'))
+        .and_then(|rest| rest.split_once("-->"))
+        .map(|(login, _)| login.trim())
+        .filter(|login| !login.is_empty() && *login != pr.author.login);
+    let Some(login) = login else {
+        return Some(pr.author.clone());
+    };
+
+    // The request comment keeps the association of the requester, so maintainers stay maintainers
+    let requester = comments
+        .iter()
+        .filter(|comment| comment.user.login == login)
+        .max_by_key(|comment| comment.timestamp)
+        .map(|comment| comment.user.clone())
+        .unwrap_or_else(|| User::new(login.to_string(), AuthorAssociation::None));
+    Some(requester)
+}
+
+// Resumes the deferred include of a draft that is ready for review now on behalf of the requester
+pub fn ready_event(pr: PrMetadata, comments: &[CommentRepr]) -> Option<Event> {
+    if pr.draft || pr.closed || pr.merged.is_some() {
+        return None;
+    }
+    let sender = draft_include_requester(&pr, comments)?;
+
+    Some(Event {
+        event_time: pr.updated_at,
+        comment: None,
+        event: EventType::PRCommand {
+            command: Command::Include(BotIncluded::new(pr.updated_at, None)),
+            sender,
+            notification: None,
+            pr,
+        },
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct BotIncluded {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub user_comment_id: Option<u64>,
+    // Included without a command: by mentioning the bot in the PR body or by the auto-inclusion of the author
+    pub automatic: bool,
 }
 
 impl BotIncluded {
@@ -18,6 +75,16 @@ impl BotIncluded {
         Self {
             timestamp,
             user_comment_id: comment_id,
+            automatic: false,
+        }
+    }
+
+    // Included on behalf of a registered author who didn't opt out
+    pub fn automatic(timestamp: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            timestamp,
+            user_comment_id: None,
+            automatic: true,
         }
     }
 }
@@ -39,6 +106,14 @@ impl BotIncluded {
             return Ok(EventResult::Skipped);
         }
 
+        if self.automatic && !context.repo_config(&pr.repo_info).await.auto_include {
+            debug!(
+                "Auto-include is disabled by the config of {}. Skipping",
+                pr.repo_info.full_id,
+            );
+            return Ok(EventResult::Skipped);
+        }
+
         match (pr.merged, pr.closed) {
             (Some(merged_at), _) if (chrono::Utc::now() - merged_at) < Duration::days(1) => {}
             (_, false) => {}
@@ -80,7 +155,7 @@ impl BotIncluded {
                     pr.repo_info.full_id
                 );
                 let invite_txt = context
-                    .messages
+                    .messages()
                     .invite_message(&pr.author.login, &sender.login)?;
                 context
                     .reply_with_text(&pr.repo_info, self.user_comment_id, &invite_txt)
@@ -91,15 +166,24 @@ impl BotIncluded {
             }
         }
 
+        if pr.draft {
+            return self.defer_draft(pr, &context, sender).await;
+        }
+
         debug!("Starting PR {}", pr.repo_info.full_id);
-        context.near.send_start(pr, sender.is_maintainer()).await?;
+        let scale = context.repo_config(&pr.repo_info).await.scale;
+        context
+            .near
+            .send_start(pr, sender.is_maintainer(), scale)
+            .await?;
+        info.scale = scale.unwrap_or(info.scale);
         info.exist = true;
         info.excluded = false;
 
         if let Some(comment_id) = self.user_comment_id {
             context
-                .github
-                .like_comment(&pr.repo_info.owner, &pr.repo_info.repo, comment_id)
+                .provider(&pr.repo_info)?
+                .like_comment(&pr.repo_info, comment_id)
                 .await?;
         }
 
@@ -108,20 +192,180 @@ impl BotIncluded {
         Ok(EventResult::success(false))
     }
 
+    // Drafts don't count for the streaks, so we acknowledge the request once and wait for the review
+    async fn defer_draft(
+        &self,
+        pr: &PrMetadata,
+        context: &Context,
+        sender: &User,
+    ) -> anyhow::Result<EventResult> {
+        debug!(
+            "PR {} is a draft. Deferring the include",
+            pr.repo_info.full_id
+        );
+        let provider = context.provider(&pr.repo_info)?;
+        let comments = provider
+            .get_comments_since(&pr.repo_info, pr.created)
+            .await?;
+        let acknowledged = draft_include_requester(pr, &comments).is_some();
+
+        if acknowledged {
+            if let Some(comment_id) = self.user_comment_id {
+                provider.like_comment(&pr.repo_info, comment_id).await?;
+            }
+        } else {
+            context
+                .reply_with_marker(
+                    &pr.repo_info,
+                    self.user_comment_id,
+                    MsgCategory::DraftIncludeMessage,
+                    vec![("user", sender.login.clone())],
+                    &draft_include_marker(&sender.login),
+                )
+                .await?;
+        }
+        Ok(EventResult::success(false))
+    }
+
     pub fn construct(comment: &CommentRepr) -> Command {
         Command::Include(BotIncluded::new(comment.timestamp, comment.comment_id))
     }
 
     pub fn parse_body(bot_name: &str, pr_metadata: &PrMetadata) -> Option<Command> {
         let body = pr_metadata.body.as_str();
-        let bot_name = format!("@{}", bot_name);
+        let bot_name = format!("@{}", Provider::login(bot_name));
         if !body.contains(&bot_name) {
             return None;
         }
 
-        Some(Command::Include(Self {
-            timestamp: pr_metadata.updated_at,
-            user_comment_id: None,
-        }))
+        Some(Command::Include(Self::automatic(pr_metadata.updated_at)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use octocrab::models::AuthorAssociation;
+    use shared::github::User;
+
+    use crate::{
+        api::CommentRepr,
+        events::{pr_commands::tests::default_pr_metadata, EventType},
+    };
+
+    use super::{
+        draft_include_marker, draft_include_requester, ready_event, Command, DRAFT_INCLUDE_MARKER,
+    };
+
+    fn comment(
+        login: &str,
+        association: AuthorAssociation,
+        text: &str,
+        minutes: i64,
+    ) -> CommentRepr {
+        let timestamp: DateTime<Utc> = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        CommentRepr {
+            id: 0,
+            user: User::new(login.to_string(), association),
+            timestamp: timestamp + Duration::minutes(minutes),
+            text: text.to_string(),
+            comment_id: Some(0),
+            edited: false,
+        }
+    }
+
+    fn requester(user: Option<User>) -> Option<(String, AuthorAssociation)> {
+        user.map(|user| (user.login, user.contributor_type))
+    }
+
+    fn reply(marker: &str, minutes: i64) -> CommentRepr {
+        comment(
+            "sloth",
+            AuthorAssociation::None,
+            &format!("Waiting for review\n{marker}"),
+            minutes,
+        )
+    }
+
+    #[test]
+    fn draft_include_is_deferred_once() {
+        let pr = default_pr_metadata();
+        assert!(draft_include_requester(&pr, &[]).is_none());
+
+        let comments = vec![comment(
+            "a-u",
+            AuthorAssociation::Contributor,
+            "@sloth include",
+            0,
+        )];
+        assert!(draft_include_requester(&pr, &comments).is_none());
+
+        let comments = vec![comments[0].clone(), reply(&draft_include_marker("a-u"), 1)];
+        assert_eq!(
+            requester(draft_include_requester(&pr, &comments)),
+            requester(Some(pr.author))
+        );
+    }
+
+    #[test]
+    fn draft_include_keeps_the_requester() {
+        let pr = default_pr_metadata();
+        let maintainer = comment("maintainer", AuthorAssociation::Member, "@sloth include", 0);
+        let comments = vec![
+            maintainer.clone(),
+            reply(&draft_include_marker("maintainer"), 1),
+        ];
+        assert_eq!(
+            requester(draft_include_requester(&pr, &comments)),
+            requester(Some(maintainer.user))
+        );
+
+        // The request comment could be deleted, the requester is still known from the reply
+        let comments = vec![reply(&draft_include_marker("someone"), 1)];
+        assert_eq!(
+            requester(draft_include_requester(&pr, &comments)),
+            Some(("someone".to_string(), AuthorAssociation::None))
+        );
+    }
+
+    #[test]
+    fn legacy_draft_include_is_for_the_author() {
+        let pr = default_pr_metadata();
+        let comments = vec![reply(&format!("{DRAFT_INCLUDE_MARKER} -->"), 0)];
+        assert_eq!(
+            requester(draft_include_requester(&pr, &comments)),
+            requester(Some(pr.author))
+        );
+    }
+
+    #[test]
+    fn ready_draft_is_included_for_the_requester() {
+        let mut pr = default_pr_metadata();
+        let maintainer = comment("maintainer", AuthorAssociation::Owner, "@sloth include", 0);
+        let comments = vec![
+            maintainer.clone(),
+            reply(&draft_include_marker("maintainer"), 1),
+        ];
+
+        pr.draft = true;
+        assert!(ready_event(pr.clone(), &comments).is_none());
+
+        pr.draft = false;
+        assert!(ready_event(pr.clone(), &comments[..1]).is_none());
+
+        let event = ready_event(pr, &comments).unwrap();
+        assert!(event.comment.is_none());
+        let EventType::PRCommand {
+            command,
+            sender,
+            notification,
+            ..
+        } = event.event
+        else {
+            panic!("Expected a PR command");
+        };
+        assert!(matches!(command, Command::Include(include) if !include.automatic));
+        assert_eq!(requester(Some(sender)), requester(Some(maintainer.user)));
+        assert!(notification.is_none());
     }
 }
@@ -0,0 +1,36 @@
+use tracing::instrument;
+
+use shared::PRInfo;
+
+use super::*;
+
+#[derive(Debug, Clone)]
+pub struct BotStatus {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub comment_id: Option<u64>,
+}
+
+impl BotStatus {
+    #[instrument(skip(self, pr, context, info), fields(pr = pr.repo_info.full_id))]
+    pub async fn execute(
+        &self,
+        pr: &PrMetadata,
+        context: Context,
+        info: &PRInfo,
+    ) -> anyhow::Result<EventResult> {
+        let user = context.near.user_info(&pr.author.login, vec![]).await?;
+        let text = context.messages().pr_status_message(info, pr, user)?;
+
+        context
+            .reply_with_text(&pr.repo_info, self.comment_id, &text)
+            .await?;
+        Ok(EventResult::success(false))
+    }
+
+    pub fn construct(comment: &CommentRepr) -> Command {
+        Command::Status(BotStatus {
+            timestamp: comment.timestamp,
+            comment_id: comment.comment_id,
+        })
+    }
+}
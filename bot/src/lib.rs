@@ -1,3 +1,4 @@
 pub mod api;
 pub mod events;
 pub mod messages;
+pub mod repo_config;
@@ -1,10 +1,25 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
 use futures::future::join_all;
 use race_of_sloths_bot::{
-    api::{prometheus::PrometheusClient, GithubClient},
-    events::{actions::Action, Context, Event, EventResult, EventType},
-    messages::MessageLoader,
+    api::{prometheus::PrometheusClient, GithubClient, GitlabClient, ProviderClient},
+    events::{
+        actions::{
+            marked_last_update, Action, Inactivity, PullRequestRescore, STALE_MARKER,
+            STALE_REVIVAL_DAYS, STALE_WARNING_MARKER,
+        },
+        labels,
+        pr_commands::{self, BotIncluded, Command},
+        Context, Event, EventResult, EventType,
+    },
+    messages::MessageSource,
 };
 use rocket::routes;
 use serde::Deserialize;
@@ -15,6 +30,17 @@ use tracing_subscriber::{layer::SubscriberExt, EnvFilter};
 use shared::github::PrMetadata;
 use shared::near::NearClient;
 use shared::telegram;
+use shared::{GithubHandle, Provider, Score, ScoringScale};
+
+// Executed PRs are archived in the contract after this time
+const ARCHIVE_AFTER_DAYS: i64 = 180;
+// Keeps the archival transaction within the gas limit
+const ARCHIVE_BATCH: u32 = 50;
+// Linked users checked for a rename per run, each one costs a GitHub request
+const RENAME_BATCH: u64 = 20;
+
+// User id the next rename check starts from, it goes around all the linked users
+static RENAME_CURSOR: AtomicU32 = AtomicU32::new(0);
 
 #[derive(Deserialize)]
 struct Env {
@@ -28,6 +54,8 @@ struct Env {
     telegram_token: String,
     telegram_chat_id: String,
     desired_bot_name: Option<String>,
+    gitlab_url: Option<String>,
+    gitlab_token: Option<String>,
 }
 
 #[rocket::get("/metrics")]
@@ -74,19 +102,36 @@ async fn main() -> anyhow::Result<()> {
         .map(|s| s.to_string())
         .collect();
     let github_api = GithubClient::new(env.github_token, read_tokens, prometheus.clone()).await?;
+    let gitlab_api = match env.gitlab_token {
+        Some(token) => {
+            let url = env
+                .gitlab_url
+                .unwrap_or_else(|| shared::Provider::GITLAB_URL.to_string());
+            Some(
+                GitlabClient::new(url, token, prometheus.clone())
+                    .await?
+                    .into(),
+            )
+        }
+        None => None,
+    };
     let bot_name = env
         .desired_bot_name
         .unwrap_or_else(|| github_api.write_user_handle().to_string());
-    let messages = MessageLoader::load_from_file(&env.message_file, &bot_name)?;
+    let message_source = Arc::new(MessageSource::load(env.message_file, bot_name.clone())?);
     let near_api =
         NearClient::new(env.contract, env.secret_key, env.is_mainnet, env.rpc_addr).await?;
     let context = Context {
         github: github_api.into(),
+        gitlab: gitlab_api,
         bot_name,
         near: near_api.into(),
-        messages: messages.into(),
+        messages: message_source.current(),
+        message_source,
+        locale: None,
         prometheus,
         telegram: telegram.into(),
+        repo_configs: Default::default(),
     };
 
     tokio::select! {
@@ -117,6 +162,7 @@ async fn run(context: Context) {
 
     loop {
         let current_time = std::time::SystemTime::now();
+        context.message_source.reload_if_changed();
         (_, _, merge_time) = tokio::join!(
             interval.tick(),
             event_task(context.clone()),
@@ -126,17 +172,33 @@ async fn run(context: Context) {
 }
 
 async fn event_task(context: Context) {
-    let events = match context.github.get_events().await {
+    let mut events = match context.github.get_events().await {
         Ok(events) => events,
         Err(e) => {
             error!("Failed to get events: {}", e);
-            return;
+            vec![]
         }
     };
 
+    if let Some(gitlab) = &context.gitlab {
+        match gitlab.get_events().await {
+            Ok(gitlab_events) => events.extend(gitlab_events),
+            Err(e) => error!("Failed to get GitLab events: {}", e),
+        }
+    }
+
     info!("Received {} events.", events.len());
 
-    let events_per_pr = events.into_iter().fold(
+    let futures = events_per_pr(events).into_iter().map(|(key, events)| {
+        debug!("Received {} events for PR {}", events.len(), key);
+        execute_events_from_one_pr(context.clone(), events)
+    });
+
+    join_all(futures).await;
+}
+
+fn events_per_pr(events: Vec<Event>) -> HashMap<String, Vec<Event>> {
+    events.into_iter().fold(
         std::collections::HashMap::new(),
         |mut map: HashMap<String, Vec<Event>>, event| {
             let repo_info = event.event.repo_info();
@@ -145,14 +207,7 @@ async fn event_task(context: Context) {
                 .push(event);
             map
         },
-    );
-
-    let futures = events_per_pr.into_iter().map(|(key, events)| {
-        debug!("Received {} events for PR {}", events.len(), key);
-        execute_events_from_one_pr(context.clone(), events)
-    });
-
-    join_all(futures).await;
+    )
 }
 
 async fn merge_and_execute_task(
@@ -164,6 +219,23 @@ async fn merge_and_execute_task(
     if current_time < merge_time {
         return merge_time;
     }
+    // Contract calls of the concurrently executed PRs are sent in batches
+    let context = Context {
+        near: Arc::new(context.near.batched()),
+        ..context
+    };
+
+    // Labels added since the previous run include and score the PRs before they are checked for the merge
+    let since = chrono::DateTime::<chrono::Utc>::from(current_time)
+        - chrono::Duration::from_std(merge_interval).unwrap_or_default();
+    let mut events = label_events(&context, since).await;
+    events.extend(auto_include_events(&context, since).await);
+    events.extend(ready_events(&context, since).await);
+    events.extend(unstale_events(&context).await);
+    let futures = events_per_pr(events)
+        .into_values()
+        .map(|events| execute_events_from_one_pr(context.clone(), events));
+    join_all(futures).await;
 
     let events = match merge_events(&context).await {
         Ok(events) => events,
@@ -193,9 +265,66 @@ async fn merge_and_execute_task(
         .map(|event| async { execute_events_from_one_pr(context.clone(), vec![event]).await });
     join_all(futures).await;
 
+    archive_executed_prs(&context).await;
+    sync_renames(&context).await;
+
     current_time + merge_interval
 }
 
+// Releases the contract storage, a batch per run is enough to keep up with the new PRs
+#[instrument(skip(context))]
+async fn archive_executed_prs(context: &Context) {
+    let before = chrono::Utc::now() - chrono::Duration::days(ARCHIVE_AFTER_DAYS);
+    match context
+        .near
+        .send_archive(
+            before.timestamp_nanos_opt().unwrap_or_default() as u64,
+            ARCHIVE_BATCH,
+        )
+        .await
+    {
+        Ok(prs) if !prs.is_empty() => info!("Archived {} executed PRs", prs.len()),
+        Ok(_) => {}
+        Err(e) => error!("Failed to archive executed PRs: {e}"),
+    }
+}
+
+// Moves the accounts to the new handles of the renamed users, so the released handles can be taken
+#[instrument(skip(context))]
+async fn sync_renames(context: &Context) {
+    let from = RENAME_CURSOR.load(Ordering::SeqCst);
+    let users = match context.near.linked_users(from, RENAME_BATCH).await {
+        Ok(users) => users,
+        Err(e) => {
+            error!("Failed to get linked users: {e}");
+            return;
+        }
+    };
+    let next = match users.last() {
+        Some((user_id, _, _)) if users.len() as u64 == RENAME_BATCH => user_id + 1,
+        _ => 0,
+    };
+    RENAME_CURSOR.store(next, Ordering::SeqCst);
+
+    for (_, github_id, handle) in users {
+        let login = match context.github.get_login(github_id).await {
+            Ok(Some(login)) => login,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Failed to get the login of {handle}: {e}");
+                continue;
+            }
+        };
+        if login == handle {
+            continue;
+        }
+        match context.near.send_rename(github_id, &handle, &login).await {
+            Ok(_) => info!("Renamed {handle} to {login}"),
+            Err(e) => error!("Failed to rename {handle} to {login}: {e}"),
+        }
+    }
+}
+
 // Runs events from the same PR
 #[instrument(skip(context, events))]
 async fn execute_events_from_one_pr(context: Context, mut events: Vec<Event>) {
@@ -229,6 +358,7 @@ async fn execute_events_from_one_pr(context: Context, mut events: Vec<Event>) {
             return;
         }
     };
+    let context = context.localized(repo_info, event.event.recipient()).await;
 
     for event in &events {
         match event.execute(context.clone(), &mut check_info).await {
@@ -275,46 +405,72 @@ async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     let mut results = vec![];
 
     for pr in prs {
-        let pr = context
-            .github
-            .get_pull_request(&pr.organization, &pr.repo, pr.number)
-            .await;
-        let pr = match pr {
-            Ok(pr) => pr,
+        let votes = pr.score.clone();
+        let scale = pr.scale;
+        let participants = pr.participants();
+        let repo_info = PrMetadata::from(pr).repo_info;
+        let provider = match context.provider(&repo_info) {
+            Ok(provider) => provider,
             Err(e) => {
-                error!("Failed to get PR: {e}");
+                error!("Failed to get provider for {}: {e}", repo_info.full_id);
                 continue;
             }
         };
-
-        let merged_by = pr.merged_by.clone();
-
-        let pr_metadata = match PrMetadata::try_from(pr) {
+        let (pr_metadata, merged_by) = match provider.get_pull_request_metadata(&repo_info).await {
             Ok(pr) => pr,
             Err(e) => {
-                error!("Failed to convert PR: {e}");
+                error!("Failed to get PR: {e}");
                 continue;
             }
         };
 
+        if pr_metadata.merged.is_none() && pr_metadata.draft && !pr_metadata.closed {
+            info!(
+                "PR {} was converted back to a draft. Creating an event",
+                pr_metadata.repo_info.full_id
+            );
+            results.push(Event {
+                event: EventType::Action {
+                    action: Action::redraft(),
+                    pr: pr_metadata,
+                },
+                comment: None,
+                event_time: chrono::Utc::now(),
+            });
+            continue;
+        }
         if pr_metadata.merged.is_none() {
             trace!(
                 "PR {} is not merged. Checking for stale",
                 pr_metadata.repo_info.full_id
             );
-            if check_for_stale_pr(&pr_metadata) {
+            let action = match inactivity_action(context, provider, &pr_metadata).await {
+                Ok(action) => action,
+                Err(e) => {
+                    error!(
+                        "Failed to check inactivity of {}: {e}",
+                        pr_metadata.repo_info.full_id
+                    );
+                    None
+                }
+            };
+            if let Some(action) = action {
                 info!(
-                    "PR {} is stale. Creating an event",
+                    "PR {} is inactive. Creating {action} event",
                     pr_metadata.repo_info.full_id
                 );
                 results.push(Event {
                     event: EventType::Action {
-                        action: Action::stale(),
+                        action,
                         pr: pr_metadata,
                     },
                     comment: None,
                     event_time: chrono::Utc::now(),
                 });
+            } else if let Some(event) =
+                rescore_event(context, pr_metadata, &votes, &participants, scale).await
+            {
+                results.push(event);
             }
             continue;
         }
@@ -322,17 +478,10 @@ async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
             "PR {} is merged. Creating an event",
             pr_metadata.repo_info.full_id
         );
-        let merged_by = merged_by
-            .map(|e| e.login)
-            .unwrap_or_else(|| pr_metadata.author.login.clone());
-
-        let reviewers = context
-            .github
-            .get_positive_or_pending_review(
-                &pr_metadata.repo_info.owner,
-                &pr_metadata.repo_info.repo,
-                pr_metadata.repo_info.number,
-            )
+        let merged_by = merged_by.unwrap_or_else(|| pr_metadata.author.login.clone());
+
+        let reviewers = provider
+            .get_positive_or_pending_review(&pr_metadata.repo_info)
             .await
             .unwrap_or_default();
         results.push(Event {
@@ -348,6 +497,243 @@ async fn merge_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     Ok(results)
 }
 
+#[instrument(skip(context))]
+async fn label_events(context: &Context, since: chrono::DateTime<chrono::Utc>) -> Vec<Event> {
+    let mut providers: Vec<&dyn ProviderClient> = vec![context.github.as_ref()];
+    if let Some(gitlab) = &context.gitlab {
+        providers.push(gitlab.as_ref());
+    }
+
+    let mut results = vec![];
+    for provider in providers {
+        let prs = match provider
+            .get_labeled_pull_requests(labels::INCLUDE_LABEL, since)
+            .await
+        {
+            Ok(prs) => prs,
+            Err(e) => {
+                error!("Failed to get labeled PRs: {e}");
+                continue;
+            }
+        };
+        info!("Received {} labeled PRs", prs.len());
+
+        for repo_info in prs {
+            let (pr, _) = match provider.get_pull_request_metadata(&repo_info).await {
+                Ok(pr) => pr,
+                Err(e) => {
+                    error!("Failed to get PR {}: {e}", repo_info.full_id);
+                    continue;
+                }
+            };
+            match provider.get_labels(&repo_info).await {
+                Ok(pr_labels) => results.extend(labels::label_events(&pr, &pr_labels, since)),
+                Err(e) => error!("Failed to get labels of {}: {e}", repo_info.full_id),
+            }
+        }
+    }
+    results
+}
+
+// Providers with their active (organization, repo) pairs
+async fn active_repos(context: &Context) -> Vec<(&dyn ProviderClient, Vec<(String, String)>)> {
+    let repos = match context.near.repos().await {
+        Ok(repos) => repos,
+        Err(e) => {
+            error!("Failed to get repos: {e}");
+            return vec![];
+        }
+    };
+
+    let (mut github_repos, mut gitlab_repos) = (vec![], vec![]);
+    for org in repos {
+        let Some((provider, _)) = Provider::from_organization(&org.organization) else {
+            continue;
+        };
+        let repos = match provider {
+            Provider::GitHub => &mut github_repos,
+            Provider::GitLab => &mut gitlab_repos,
+        };
+        repos.extend(
+            org.repos
+                .into_iter()
+                .filter(|repo| !repo.paused && !repo.blocked)
+                .map(|repo| (org.organization.clone(), repo.login)),
+        );
+    }
+
+    let mut providers: Vec<(&dyn ProviderClient, _)> =
+        vec![(context.github.as_ref(), github_repos)];
+    if let Some(gitlab) = &context.gitlab {
+        providers.push((gitlab.as_ref(), gitlab_repos));
+    }
+    providers
+}
+
+// New PRs in the active repositories are included on behalf of the registered authors who didn't opt out
+#[instrument(skip(context))]
+async fn auto_include_events(
+    context: &Context,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Vec<Event> {
+    let mut results = vec![];
+    for (provider, repos) in active_repos(context).await {
+        if repos.is_empty() {
+            continue;
+        }
+        let prs = match provider.get_new_pull_requests(&repos, since).await {
+            Ok(prs) => prs,
+            Err(e) => {
+                error!("Failed to get new PRs: {e}");
+                continue;
+            }
+        };
+        info!("Received {} new PRs", prs.len());
+
+        for repo_info in prs {
+            let (pr, _) = match provider.get_pull_request_metadata(&repo_info).await {
+                Ok(pr) => pr,
+                Err(e) => {
+                    error!("Failed to get PR {}: {e}", repo_info.full_id);
+                    continue;
+                }
+            };
+            match context.near.user_auto_include(&pr.author.login).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    error!("Failed to check auto-include of {}: {e}", pr.author.login);
+                    continue;
+                }
+            }
+
+            results.push(Event {
+                event_time: pr.created,
+                comment: None,
+                event: EventType::PRCommand {
+                    command: Command::Include(BotIncluded::automatic(pr.created)),
+                    sender: pr.author.clone(),
+                    notification: None,
+                    pr,
+                },
+            });
+        }
+    }
+    results
+}
+
+// Include requests of drafts are resumed when the PRs are marked as ready for review
+#[instrument(skip(context))]
+async fn ready_events(context: &Context, since: chrono::DateTime<chrono::Utc>) -> Vec<Event> {
+    // Included PRs converted back to drafts are stale and restored with their votes by the unstale task
+    let now = chrono::Utc::now();
+    let stale: HashSet<String> = match context.near.stale_prs_all().await {
+        Ok(prs) => prs
+            .into_iter()
+            .filter(|(_, stale_at)| {
+                let stale_at = chrono::DateTime::from_timestamp_nanos(*stale_at as i64);
+                now - stale_at <= chrono::Duration::days(STALE_REVIVAL_DAYS)
+            })
+            .map(|(pr, _)| PrMetadata::from(pr).repo_info.full_id)
+            .collect(),
+        Err(e) => {
+            error!("Failed to get stale PRs: {e}");
+            return vec![];
+        }
+    };
+
+    let mut results = vec![];
+    for (provider, repos) in active_repos(context).await {
+        if repos.is_empty() {
+            continue;
+        }
+        let prs = match provider.get_ready_pull_requests(&repos, since).await {
+            Ok(prs) => prs,
+            Err(e) => {
+                error!("Failed to get ready PRs: {e}");
+                continue;
+            }
+        };
+        info!("Received {} ready PRs", prs.len());
+
+        for repo_info in prs {
+            if stale.contains(&repo_info.full_id) {
+                continue;
+            }
+            match context.check_info(&repo_info).await {
+                Ok(info) if !info.exist && !info.excluded => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    error!("Failed to get PR info for {}: {e}", repo_info.full_id);
+                    continue;
+                }
+            }
+            let pr = match provider.get_pull_request_metadata(&repo_info).await {
+                Ok((pr, _)) if !pr.draft => pr,
+                Ok(_) => continue,
+                Err(e) => {
+                    error!("Failed to get PR {}: {e}", repo_info.full_id);
+                    continue;
+                }
+            };
+            match provider.get_comments_since(&pr.repo_info, pr.created).await {
+                Ok(comments) => results.extend(pr_commands::ready_event(pr, &comments)),
+                Err(e) => error!("Failed to get comments of {}: {e}", pr.repo_info.full_id),
+            }
+        }
+    }
+    results
+}
+
+// Score comments can be edited or deleted and score labels can be added without notifying us,
+// so we check voted and score labeled PRs periodically
+async fn rescore_event(
+    context: &Context,
+    pr: PrMetadata,
+    votes: &[Score],
+    participants: &[GithubHandle],
+    scale: ScoringScale,
+) -> Option<Event> {
+    let score_labeled = pr
+        .labels
+        .iter()
+        .flatten()
+        .any(|label| labels::is_score_label(label));
+    if votes.is_empty() && !score_labeled {
+        return None;
+    }
+
+    let scores = match context.provider(&pr.repo_info) {
+        Ok(provider) => provider.get_scores_and_active_pr_status(&pr).await,
+        Err(e) => Err(e),
+    };
+    let (scores, _) = match scores {
+        Ok(scores) => scores,
+        Err(e) => {
+            error!("Failed to get scores for {}: {e}", pr.repo_info.full_id);
+            return None;
+        }
+    };
+
+    let rescore = PullRequestRescore::new(scores);
+    if !rescore.has_changes(votes, participants, &context.bot_name, scale) {
+        return None;
+    }
+
+    info!(
+        "Votes of PR {} are outdated. Creating an event",
+        pr.repo_info.full_id
+    );
+    Some(Event {
+        event_time: pr.updated_at,
+        comment: None,
+        event: EventType::Action {
+            action: Action::Rescore(rescore),
+            pr,
+        },
+    })
+}
+
 #[instrument(skip(context))]
 async fn finalized_events(context: &Context) -> anyhow::Result<Vec<Event>> {
     let prs = context.near.unfinalized_prs_all().await?;
@@ -369,12 +755,90 @@ async fn finalized_events(context: &Context) -> anyhow::Result<Vec<Event>> {
         .collect())
 }
 
-fn check_for_stale_pr(pr: &PrMetadata) -> bool {
-    if pr.merged.is_some() {
-        return false;
+// Warns the author before the PR becomes stale
+async fn inactivity_action(
+    context: &Context,
+    provider: &dyn ProviderClient,
+    pr: &PrMetadata,
+) -> anyhow::Result<Option<Action>> {
+    let config = context.repo_config(&pr.repo_info).await;
+    let now = chrono::Utc::now();
+
+    let mut warned_at = None;
+    if Inactivity::needs_comments(pr, &config, now) {
+        let since = pr.updated_at - chrono::Duration::minutes(1);
+        let comments = provider.get_comments_since(&pr.repo_info, since).await?;
+        warned_at = marked_last_update(pr, &comments, STALE_WARNING_MARKER);
     }
 
+    Ok(match Inactivity::of(pr, &config, warned_at, now) {
+        Inactivity::Active => None,
+        Inactivity::Warning => Some(Action::stale_warning()),
+        Inactivity::Stale => Some(Action::stale()),
+    })
+}
+
+// Stale PRs return to the race if they were reopened or updated by anyone but us
+#[instrument(skip(context))]
+async fn unstale_events(context: &Context) -> Vec<Event> {
+    let prs = match context.near.stale_prs_all().await {
+        Ok(prs) => prs,
+        Err(e) => {
+            error!("Failed to get stale PRs: {e}");
+            return vec![];
+        }
+    };
+
     let now = chrono::Utc::now();
-    let stale = now - pr.updated_at;
-    stale.num_days() > 14 || pr.closed
+    let mut results = vec![];
+    for (pr, stale_at) in prs {
+        let stale_at = chrono::DateTime::from_timestamp_nanos(stale_at as i64);
+        if now - stale_at > chrono::Duration::days(STALE_REVIVAL_DAYS) {
+            continue;
+        }
+
+        let repo_info = PrMetadata::from(pr).repo_info;
+        let provider = match context.provider(&repo_info) {
+            Ok(provider) => provider,
+            Err(e) => {
+                error!("Failed to get provider for {}: {e}", repo_info.full_id);
+                continue;
+            }
+        };
+        let (pr, _) = match provider.get_pull_request_metadata(&repo_info).await {
+            Ok(pr) => pr,
+            Err(e) => {
+                error!("Failed to get PR {}: {e}", repo_info.full_id);
+                continue;
+            }
+        };
+        // Drafts come back once they are ready for review
+        if pr.closed || pr.draft || pr.updated_at <= stale_at {
+            continue;
+        }
+
+        let since = pr.updated_at - chrono::Duration::minutes(1);
+        match provider.get_comments_since(&pr.repo_info, since).await {
+            Ok(comments) if marked_last_update(&pr, &comments, STALE_MARKER).is_none() => {}
+            Ok(_) => continue,
+            Err(e) => {
+                error!("Failed to get comments of {}: {e}", pr.repo_info.full_id);
+                continue;
+            }
+        }
+
+        info!(
+            "Stale PR {} was updated. Creating an event",
+            pr.repo_info.full_id
+        );
+        results.push(Event {
+            event: EventType::Action {
+                action: Action::unstale(),
+                pr,
+            },
+            comment: None,
+            event_time: now,
+        });
+    }
+    results
 }
@@ -1,24 +1,61 @@
+use minijinja::{context, syntax::SyntaxConfig, Environment, Value};
+use num_format::{Locale, ToFormattedString};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use shared::github::PrMetadata;
 use shared::{PRInfo, Score, TimePeriod, User};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::ops::Add;
-use std::path::PathBuf;
-use tracing::error;
-
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::SystemTime;
+use strum::{EnumIter, IntoEnumIterator};
+use tracing::{error, info};
+
+// Messages keep the `{variable}` substitution and can use `{% if %}` and `{% for %}` blocks,
+// so the wording of conditional parts can be changed in the messages file
+static TEMPLATES: LazyLock<Environment<'static>> = LazyLock::new(|| {
+    let mut env = Environment::new();
+    env.set_syntax(
+        SyntaxConfig::builder()
+            .block_delimiters("{%", "%}")
+            .variable_delimiters("{", "}")
+            .comment_delimiters("{#", "#}")
+            .build()
+            .expect("Template syntax is valid"),
+    );
+    env.set_keep_trailing_newline(true);
+    env.add_filter("number", |value: i64| {
+        value.to_formatted_string(&Locale::en)
+    });
+    env
+});
+
+// Marks the start of the status in the bot comment, so it can be updated in any language
+const STATUS_MARKER: &str = "<!-- status -->";
+// Comments created before the marker start the status with the summary
+const LEGACY_STATUS_START: &str = "<details><summary>Current status:";
+// Providers reject longer commit status descriptions
+const CHECK_STATUS_MAX_LENGTH: usize = 140;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, EnumIter)]
 pub enum MsgCategory {
     IncludeBasicMessage,
     IncludeCommonMessage,
     InviteMessage,
+    DraftIncludeMessage,
     CorrectableScoringMessage,
     ExcludeMessages,
+    CoauthorsMessage,
+    HelpMessage,
+    PrStatusMessage,
     PauseMessage,
     UnpauseMessage,
     UnpauseIssueMessage,
+    BountyMessage,
+    BountyCancelledMessage,
     MergeWithoutScoreMessageByOtherParty,
     MergeWithoutScoreMessageByAuthorWithoutReviewers,
     RatingMessagesCommon,
@@ -26,16 +63,28 @@ pub enum MsgCategory {
     FinalMessagesMonthlyStreak,
     FinalMessagesFirstLifetimeBonus,
     FinalMessagesLifetimeBonus,
+    FinalMessagesLifetimeBonusIncreased,
     FinalMessagesFeedbackForm,
     StaleMessage,
+    StaleWarningMessage,
+    RedraftMessage,
+    StatusMessage,
+    CheckStatusMessage,
+    StatusNameMessage,
+    LangMessage,
+    AutoIncludeMessage,
     ErrorUnknownCommandMessage,
     ErrorRightsViolationMessage,
     ErrorLateIncludeMessage,
     ErrorPausePausedMessage,
     ErrorUnpauseUnpausedMessage,
     ErrorPausedMessage,
+    ErrorContractPausedMessage,
     ErrorLateScoringMessage,
     ErrorSelfScore,
+    ErrorCoauthorsMessage,
+    ErrorScoringRestrictedMessage,
+    ErrorUnknownLocaleMessage,
     ErrorRepoIsBanned,
 
     FirstTimeContribution,
@@ -66,23 +115,44 @@ impl Messages {
         Self { message, variables }
     }
 
-    pub fn format(&self, values: HashMap<&'static str, String>) -> anyhow::Result<String> {
-        let mut formatted_message = self
+    pub fn format<V: Into<Value>>(
+        &self,
+        values: HashMap<&'static str, V>,
+    ) -> anyhow::Result<String> {
+        let template = self
             .message
             .choose(&mut thread_rng())
-            .ok_or_else(|| anyhow::anyhow!("Failed to choose randomly an message"))?
-            .clone();
+            .ok_or_else(|| anyhow::anyhow!("Failed to choose randomly an message"))?;
         for key in self.variables.iter() {
-            if let Some(value) = values.get(key.as_str()) {
-                formatted_message = formatted_message.replace(&format!("{{{}}}", key), value);
-            } else {
+            if !values.contains_key(key.as_str()) {
                 error!(
                     "The message expects a variable: {}, but it wasn't provided",
                     key
                 );
             }
         }
-        Ok(formatted_message)
+        let values = values
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect::<BTreeMap<_, Value>>();
+        Ok(TEMPLATES.render_str(template, values)?)
+    }
+
+    // Every template has to compile and use only the declared variables
+    fn check(&self) -> anyhow::Result<()> {
+        for template in &self.message {
+            let template = TEMPLATES.template_from_str(template)?;
+            let mut undeclared = template
+                .undeclared_variables(false)
+                .into_iter()
+                .filter(|variable| !self.variables.contains(variable))
+                .collect::<Vec<_>>();
+            if !undeclared.is_empty() {
+                undeclared.sort();
+                anyhow::bail!("uses undeclared variables {undeclared:?}");
+            }
+        }
+        Ok(())
     }
 
     fn partial_format(&mut self, values: &HashMap<&'static str, String>) {
@@ -106,11 +176,17 @@ pub struct MessageLoader {
     pub include_basic_messages: Messages,
     pub include_common_messages: Messages,
     pub invite_messages: Messages,
+    pub draft_include_messages: Messages,
     pub correctable_scoring_messages: Messages,
     pub exclude_messages: Messages,
+    pub coauthors_messages: Messages,
+    pub help_messages: Messages,
+    pub pr_status_messages: Messages,
     pub pause_messages: Messages,
     pub unpause_messages: Messages,
     pub unpause_issue_messages: Messages,
+    pub bounty_messages: Messages,
+    pub bounty_cancelled_messages: Messages,
     pub merge_without_score_by_other_party: Messages,
     pub merge_without_score_by_author_without_reviewers: Messages,
     pub rating_messages_common: Messages,
@@ -118,8 +194,16 @@ pub struct MessageLoader {
     pub final_messages_monthly_streak: Messages,
     pub final_messages_first_lifetime_bonus: Messages,
     pub final_messages_lifetime_bonus: Messages,
+    pub final_messages_lifetime_bonus_increased: Messages,
     pub final_messages_feedback_form: Messages,
     pub stale_messages: Messages,
+    pub stale_warning_messages: Messages,
+    pub redraft_messages: Messages,
+    pub status_messages: Messages,
+    pub check_status_messages: Messages,
+    pub status_name_messages: Messages,
+    pub lang_messages: Messages,
+    pub auto_include_messages: Messages,
 
     // Errors
     pub error_unknown_command_messages: Messages,
@@ -129,7 +213,11 @@ pub struct MessageLoader {
     pub error_pause_paused_messages: Messages,
     pub error_unpause_unpaused_messages: Messages,
     pub error_paused_messages: Messages,
+    pub error_contract_paused_messages: Messages,
     pub error_selfscore_messages: Messages,
+    pub error_coauthors_messages: Messages,
+    pub error_scoring_restricted_messages: Messages,
+    pub error_unknown_locale_messages: Messages,
     pub error_org_not_in_allowed_list_messages: Messages,
 
     // Message by amount of contributions
@@ -144,6 +232,171 @@ pub struct MessageLoader {
     pub contribution_8: Messages,
 }
 
+/// Message bundles per locale with English from the main file as a fallback.
+/// Locales are loaded from `locales/<locale>.toml` next to the main file and inherit its links.
+#[derive(Debug)]
+pub struct LocalizedMessages {
+    default: MessageLoader,
+    locales: BTreeMap<String, MessageLoader>,
+}
+
+impl LocalizedMessages {
+    pub const DEFAULT_LOCALE: &'static str = "en";
+
+    pub fn load_from_file(file_path: &PathBuf, bot_name: &str) -> anyhow::Result<Self> {
+        let base: toml::Table = toml::from_str(&fs::read_to_string(file_path)?)?;
+        let mut default: MessageLoader = toml::Value::Table(base.clone()).try_into()?;
+
+        let dir = Self::locales_dir(file_path);
+        let mut locales = BTreeMap::new();
+        if dir.is_dir() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let Some(locale) = path
+                    .extension()
+                    .filter(|ext| *ext == "toml")
+                    .and_then(|_| path.file_stem())
+                    .and_then(|stem| stem.to_str())
+                    .map(str::to_lowercase)
+                else {
+                    continue;
+                };
+
+                let mut table: toml::Table = toml::from_str(&fs::read_to_string(&path)?)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", path.display()))?;
+                for (key, value) in base.iter().filter(|(_, value)| !value.is_table()) {
+                    table.entry(key).or_insert_with(|| value.clone());
+                }
+                let mut messages: MessageLoader = toml::Value::Table(table)
+                    .try_into()
+                    .map_err(|e| anyhow::anyhow!("Invalid locale {locale}: {e}"))?;
+                messages
+                    .validate(&default)
+                    .map_err(|e| anyhow::anyhow!("Invalid locale {locale}: {e}"))?;
+                messages.postprocess_messages_with_link(bot_name);
+                messages
+                    .check_templates()
+                    .map_err(|e| anyhow::anyhow!("Invalid locale {locale}: {e}"))?;
+                locales.insert(locale, messages);
+            }
+        }
+
+        default.postprocess_messages_with_link(bot_name);
+        default.check_templates()?;
+        tracing::info!("Loaded message locales: {:?}", locales.keys());
+        Ok(Self { default, locales })
+    }
+
+    fn locales_dir(file_path: &Path) -> PathBuf {
+        file_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("locales")
+    }
+
+    // Falls back to English if the locale is not supported
+    pub fn get(&self, locale: Option<&str>) -> &MessageLoader {
+        locale
+            .and_then(|locale| self.locales.get(&locale.to_lowercase()))
+            .unwrap_or(&self.default)
+    }
+
+    pub fn is_supported(&self, locale: &str) -> bool {
+        let locale = locale.to_lowercase();
+        locale == Self::DEFAULT_LOCALE || self.locales.contains_key(&locale)
+    }
+
+    pub fn locales(&self) -> Vec<&str> {
+        std::iter::once(Self::DEFAULT_LOCALE)
+            .chain(self.locales.keys().map(String::as_str))
+            .collect()
+    }
+}
+
+impl From<MessageLoader> for LocalizedMessages {
+    fn from(default: MessageLoader) -> Self {
+        Self {
+            default,
+            locales: Default::default(),
+        }
+    }
+}
+
+/// Messages that are reloaded once the messages file or the locales change, so copy changes don't need a redeploy.
+/// Invalid files are rejected and the previous messages stay in use.
+pub struct MessageSource {
+    path: PathBuf,
+    bot_name: String,
+    messages: RwLock<Arc<LocalizedMessages>>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl MessageSource {
+    pub fn load(path: PathBuf, bot_name: String) -> anyhow::Result<Self> {
+        let modified = last_modified(&path);
+        let messages = LocalizedMessages::load_from_file(&path, &bot_name)?;
+        Ok(Self {
+            path,
+            bot_name,
+            messages: RwLock::new(Arc::new(messages)),
+            modified: Mutex::new(modified),
+        })
+    }
+
+    pub fn current(&self) -> Arc<LocalizedMessages> {
+        match self.messages.read() {
+            Ok(messages) => messages.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    // Returns true if the new messages were swapped in
+    pub fn reload_if_changed(&self) -> bool {
+        let modified = last_modified(&self.path);
+        {
+            let Ok(mut last) = self.modified.lock() else {
+                return false;
+            };
+            if *last == modified {
+                return false;
+            }
+            // Broken files are remembered as well, so the error is reported once per change
+            *last = modified;
+        }
+
+        match LocalizedMessages::load_from_file(&self.path, &self.bot_name) {
+            Ok(messages) => {
+                let Ok(mut current) = self.messages.write() else {
+                    return false;
+                };
+                *current = Arc::new(messages);
+                info!("Reloaded messages from {}", self.path.display());
+                true
+            }
+            Err(e) => {
+                error!(
+                    "Rejected the new messages from {}: {e}. Keeping the previous ones",
+                    self.path.display()
+                );
+                false
+            }
+        }
+    }
+}
+
+// The latest modification of the messages file and the locale bundles
+fn last_modified(path: &Path) -> Option<SystemTime> {
+    let locales = fs::read_dir(LocalizedMessages::locales_dir(path))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path());
+    std::iter::once(path.to_path_buf())
+        .chain(locales)
+        .filter_map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .max()
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct FinalMessageData {
     pub username: String,
@@ -154,6 +407,12 @@ pub struct FinalMessageData {
     pub lifetime_percent_reward: u32,
     pub total_lifetime_percent: u32,
     pub pr_number_this_week: u32,
+    // Rank tier reached with this PR
+    pub rank_up: Option<String>,
+    // Co-authors with the rating they received
+    pub coauthors: Vec<(String, u32)>,
+    // Claimed bounties as issue number with the bounty score
+    pub bounties: Vec<(u64, u32)>,
 }
 
 impl FinalMessageData {
@@ -170,10 +429,53 @@ impl MessageLoader {
         let file_content = fs::read_to_string(file_path)?;
         let mut result: Self = toml::from_str(&file_content)?;
         result.postprocess_messages_with_link(bot_name);
+        result.check_templates()?;
         tracing::trace!("Loaded messages: {:#?}", result);
         Ok(result)
     }
 
+    fn check_templates(&self) -> anyhow::Result<()> {
+        for category in MsgCategory::iter() {
+            self.get_message(category)
+                .check()
+                .map_err(|e| anyhow::anyhow!("Invalid template of {category}: {e}"))?;
+        }
+        for status in PrStatus::iter() {
+            if self.status_name(status)?.trim().is_empty() {
+                anyhow::bail!(
+                    "{} has no name for the {:?} status",
+                    MsgCategory::StatusNameMessage,
+                    status.id()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn status_name(&self, status: PrStatus) -> anyhow::Result<String> {
+        self.get_message(MsgCategory::StatusNameMessage)
+            .format([("status", status.id())].into_iter().collect())
+    }
+
+    // Every category of the locale has to exist and expect the same variables as in the default bundle
+    fn validate(&self, default: &MessageLoader) -> anyhow::Result<()> {
+        for category in MsgCategory::iter() {
+            let expected = default.get_message(category);
+            let actual = self.get_message(category);
+            if actual.message.is_empty() {
+                anyhow::bail!("{category} has no messages");
+            }
+            if actual.variables != expected.variables {
+                anyhow::bail!(
+                    "{category} expects variables {:?}, but the default bundle has {:?}",
+                    actual.variables,
+                    expected.variables
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn postprocess_messages_with_link(&mut self, bot_name: &str) {
         let values = vec![
             ("link", self.link.clone()),
@@ -189,20 +491,34 @@ impl MessageLoader {
             &mut self.include_basic_messages,
             &mut self.include_common_messages,
             &mut self.invite_messages,
+            &mut self.draft_include_messages,
             &mut self.correctable_scoring_messages,
             &mut self.exclude_messages,
+            &mut self.coauthors_messages,
+            &mut self.help_messages,
+            &mut self.pr_status_messages,
             &mut self.pause_messages,
             &mut self.unpause_messages,
             &mut self.unpause_issue_messages,
+            &mut self.bounty_messages,
+            &mut self.bounty_cancelled_messages,
             &mut self.merge_without_score_by_other_party,
             &mut self.merge_without_score_by_author_without_reviewers,
             &mut self.rating_messages_common,
             &mut self.final_messages_first_lifetime_bonus,
             &mut self.final_messages_lifetime_bonus,
+            &mut self.final_messages_lifetime_bonus_increased,
             &mut self.final_messages_monthly_streak,
             &mut self.final_messages_weekly_streak,
             &mut self.final_messages_feedback_form,
             &mut self.stale_messages,
+            &mut self.stale_warning_messages,
+            &mut self.redraft_messages,
+            &mut self.status_messages,
+            &mut self.check_status_messages,
+            &mut self.status_name_messages,
+            &mut self.lang_messages,
+            &mut self.auto_include_messages,
             &mut self.error_unknown_command_messages,
             &mut self.error_rights_violation_messages,
             &mut self.error_late_include_messages,
@@ -210,7 +526,11 @@ impl MessageLoader {
             &mut self.error_pause_paused_messages,
             &mut self.error_unpause_unpaused_messages,
             &mut self.error_paused_messages,
+            &mut self.error_contract_paused_messages,
             &mut self.error_selfscore_messages,
+            &mut self.error_coauthors_messages,
+            &mut self.error_scoring_restricted_messages,
+            &mut self.error_unknown_locale_messages,
             &mut self.error_org_not_in_allowed_list_messages,
             &mut self.first_time_contribution,
             &mut self.first_week_contribution,
@@ -232,11 +552,17 @@ impl MessageLoader {
             MsgCategory::IncludeBasicMessage => &self.include_basic_messages,
             MsgCategory::IncludeCommonMessage => &self.include_common_messages,
             MsgCategory::InviteMessage => &self.invite_messages,
+            MsgCategory::DraftIncludeMessage => &self.draft_include_messages,
             MsgCategory::CorrectableScoringMessage => &self.correctable_scoring_messages,
             MsgCategory::ExcludeMessages => &self.exclude_messages,
+            MsgCategory::CoauthorsMessage => &self.coauthors_messages,
+            MsgCategory::HelpMessage => &self.help_messages,
+            MsgCategory::PrStatusMessage => &self.pr_status_messages,
             MsgCategory::PauseMessage => &self.pause_messages,
             MsgCategory::UnpauseMessage => &self.unpause_messages,
             MsgCategory::UnpauseIssueMessage => &self.unpause_issue_messages,
+            MsgCategory::BountyMessage => &self.bounty_messages,
+            MsgCategory::BountyCancelledMessage => &self.bounty_cancelled_messages,
             MsgCategory::MergeWithoutScoreMessageByOtherParty => {
                 &self.merge_without_score_by_other_party
             }
@@ -251,16 +577,30 @@ impl MessageLoader {
             }
             MsgCategory::FinalMessagesFeedbackForm => &self.final_messages_feedback_form,
             MsgCategory::FinalMessagesLifetimeBonus => &self.final_messages_lifetime_bonus,
+            MsgCategory::FinalMessagesLifetimeBonusIncreased => {
+                &self.final_messages_lifetime_bonus_increased
+            }
             MsgCategory::StaleMessage => &self.stale_messages,
+            MsgCategory::StaleWarningMessage => &self.stale_warning_messages,
+            MsgCategory::RedraftMessage => &self.redraft_messages,
+            MsgCategory::StatusMessage => &self.status_messages,
+            MsgCategory::CheckStatusMessage => &self.check_status_messages,
+            MsgCategory::StatusNameMessage => &self.status_name_messages,
+            MsgCategory::LangMessage => &self.lang_messages,
+            MsgCategory::AutoIncludeMessage => &self.auto_include_messages,
             MsgCategory::ErrorUnknownCommandMessage => &self.error_unknown_command_messages,
             MsgCategory::ErrorRightsViolationMessage => &self.error_rights_violation_messages,
             MsgCategory::ErrorLateIncludeMessage => &self.error_late_include_messages,
             MsgCategory::ErrorLateScoringMessage => &self.error_late_scoring_messages,
             MsgCategory::ErrorSelfScore => &self.error_selfscore_messages,
+            MsgCategory::ErrorCoauthorsMessage => &self.error_coauthors_messages,
+            MsgCategory::ErrorScoringRestrictedMessage => &self.error_scoring_restricted_messages,
+            MsgCategory::ErrorUnknownLocaleMessage => &self.error_unknown_locale_messages,
             MsgCategory::ErrorRepoIsBanned => &self.error_org_not_in_allowed_list_messages,
             MsgCategory::ErrorPausePausedMessage => &self.error_pause_paused_messages,
             MsgCategory::ErrorUnpauseUnpausedMessage => &self.error_unpause_unpaused_messages,
             MsgCategory::ErrorPausedMessage => &self.error_paused_messages,
+            MsgCategory::ErrorContractPausedMessage => &self.error_contract_paused_messages,
 
             MsgCategory::FirstTimeContribution => &self.first_time_contribution,
             MsgCategory::FirstWeekContribution => &self.first_week_contribution,
@@ -282,6 +622,7 @@ impl MessageLoader {
         pr: &PrMetadata,
         user: Option<User>,
         final_data: Option<FinalMessageData>,
+        intro: Option<&str>,
     ) -> String {
         let user = if let Some(user) = user {
             user
@@ -299,6 +640,7 @@ impl MessageLoader {
                     },
                 )],
                 streaks: vec![],
+                rank: None,
             }
         };
 
@@ -329,7 +671,10 @@ impl MessageLoader {
                     .collect(),
             )
             .unwrap_or_default();
-        message + &status_message + &common
+        let intro = intro
+            .map(|intro| format!("{}\n\n", intro.trim()))
+            .unwrap_or_default();
+        intro + &message + &status_message + &common
     }
 
     pub fn status_message(
@@ -339,93 +684,164 @@ impl MessageLoader {
         pr: &PrMetadata,
         final_data: Option<FinalMessageData>,
     ) -> anyhow::Result<String> {
-        let mut message = String::new();
-
-        let status = if check_info.excluded {
-            "excluded"
-        } else if !check_info.exist {
-            "stale" // PR was remove for inactivity
-        } else if check_info.executed {
-            "executed"
-        } else if check_info.votes.is_empty() {
-            "waiting for scoring"
-        } else if !check_info.merged {
-            "waiting for merge"
-        } else {
-            "waiting for finalization"
-        };
-
-        message.push_str(&format!(
-            "\n<details><summary>Current status: <i>{status}</i></summary>\n",
-        ));
+        let status = PrStatus::new(check_info);
+        let scale = check_info.scale;
 
-        if status == "waiting for scoring" {
-            message.push_str(&format!("\nWe're waiting for maintainer to score this pull request with `@{bot_name} score [0,1,2,3,5,8,13]` command. Alternatively, autoscoring [1,2] will be applied for this pull request\n", bot_name = bot_name));
-        }
-
-        if status == "stale" {
-            message.push_str(&format!("\nThis pull request was removed from the race, but you can include it again with `@{bot_name} include` command"));
-        }
-
-        if status == "waiting for finalization" {
-            message.push_str(&format!("\nThe pull request is merged, you have 24 hours to finalize your scoring. The scoring ends {}", pr.merged.unwrap().add(chrono::Duration::days(1)).format("%c")));
-        }
-
-        if !check_info.votes.is_empty() {
-            message.push_str("\n| Reviewer | Score |\n");
-            message.push_str("|--------|--------|\n");
-
-            for vote in &check_info.votes {
-                message.push_str(&format!("| @{}  | {} |\n", vote.user, vote.score));
-            }
-        }
-
-        if status == "executed" {
+        let rating = if status == PrStatus::Executed {
             let final_data = final_data.ok_or_else(|| {
                 anyhow::anyhow!("Constraint violation: final_data is None for executed PR")
             })?;
-            message.push_str("\n\n");
-            message.push_str(&self.final_message(final_data, bot_name, check_info.votes.clone())?)
+            self.final_message(final_data, bot_name, &check_info.votes)?
         } else if !check_info.votes.is_empty() {
             let score = check_info.average_score();
-            message.push_str("\n\n");
-            let rating = rating_breakthrough(score * 10, score, 0, 0, 0);
-            let rating_message = self.rating_message(
+            let base_rating =
+                shared::split_rating(score * 10, check_info.coauthors.len() as u32 + 1, true);
+            let rating = rating_breakthrough(base_rating, base_rating, 0, 0, 0, 0);
+            self.rating_message(
                 score.to_string(),
                 rating,
-                check_info.votes.clone(),
+                &check_info.votes,
                 bot_name,
-            )?;
-            message.push_str("\n\n");
-            message.push_str(&rating_message);
-        }
+                &[],
+                &[],
+            )?
+        } else {
+            String::new()
+        };
+
+        let votes = check_info
+            .votes
+            .iter()
+            .map(|vote| context! { user => vote.user, score => scale.label(vote.score) })
+            .collect::<Vec<_>>();
+        let scoring_ends = pr.merged.map(|merged| {
+            merged
+                .add(chrono::Duration::days(1))
+                .format("%c")
+                .to_string()
+        });
+
+        let status_message = self.get_message(MsgCategory::StatusMessage).format(
+            [
+                ("status", Value::from(status.id())),
+                ("status_name", Value::from(self.status_name(status)?)),
+                ("scores", Value::from(scale.labels().join(","))),
+                (
+                    "autoscore",
+                    Value::from(format!("{},{}", scale.label(1), scale.label(2))),
+                ),
+                ("scoring_ends", Value::from(scoring_ends)),
+                ("coauthors", Value::from(check_info.coauthors.clone())),
+                ("votes", Value::from(votes)),
+                ("rating", Value::from(rating)),
+            ]
+            .into_iter()
+            .collect(),
+        )?;
 
-        message.push_str("\n</details>");
+        Ok(format!("\n{STATUS_MARKER}{status_message}"))
+    }
+
+    // One line summary for the commit status of the PR
+    pub fn check_status_message(
+        &self,
+        check_info: &PRInfo,
+        pr: &PrMetadata,
+        final_data: Option<&FinalMessageData>,
+    ) -> anyhow::Result<String> {
+        let status = PrStatus::new(check_info);
+        let in_race = matches!(
+            status,
+            PrStatus::WaitingForMerge | PrStatus::WaitingForFinalization
+        );
+
+        let rating = match final_data {
+            Some(final_data) if check_info.executed => Some(final_data.total_rating),
+            _ if in_race => Some(shared::split_rating(
+                check_info.average_score() * 10,
+                check_info.coauthors.len() as u32 + 1,
+                true,
+            )),
+            _ => None,
+        };
+        let finalization = check_info
+            .ready_to_move_timestamp
+            .map(|timestamp| chrono::DateTime::from_timestamp_nanos(timestamp as i64))
+            .or_else(|| {
+                pr.merged
+                    .map(|merged| merged.add(chrono::Duration::days(1)))
+            })
+            .filter(|_| check_info.merged && !check_info.executed && check_info.exist)
+            .map(|finalization| finalization.format("%b %d %H:%M UTC").to_string());
+
+        let status_name = self.status_name(status)?;
+        let render = |votes: usize, rating: Option<u32>, finalization: Option<&String>| {
+            self.get_message(MsgCategory::CheckStatusMessage).format(
+                [
+                    ("status", Value::from(status.id())),
+                    ("status_name", Value::from(status_name.clone())),
+                    ("votes", Value::from(votes)),
+                    ("score", Value::from(check_info.average_score())),
+                    ("rating", Value::from(rating)),
+                    ("finalization", Value::from(finalization)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
 
-        Ok(message)
+        // Optional parts are dropped from the end until the summary fits, so no value is cut in half
+        let votes = check_info.votes.len();
+        let variants = [
+            (votes, rating, finalization.as_ref()),
+            (votes, rating, None),
+            (votes, None, None),
+            (0, None, None),
+        ];
+        let mut text = String::new();
+        for (votes, rating, finalization) in variants {
+            text = render(votes, rating, finalization)?.trim().to_string();
+            if text.chars().count() <= CHECK_STATUS_MAX_LENGTH {
+                return Ok(text);
+            }
+        }
+        Ok(truncate_at_word(&text, CHECK_STATUS_MAX_LENGTH))
     }
 
     fn rating_message(
         &self,
         score: String,
         rating: String,
-        scorers: Vec<Score>,
+        scorers: &[Score],
         bot_name: &str,
+        bounties: &[(u64, u32)],
+        coauthors: &[(String, u32)],
     ) -> anyhow::Result<String> {
-        let mut message = self
-            .get_message(MsgCategory::RatingMessagesCommon)
-            .format([("score", score), ("rating", rating)].into_iter().collect())?;
-
-        for scorer in scorers {
-            if scorer.user != bot_name {
-                message.push_str(&format!(
-                    "\n@{} received 25 Sloth Points for reviewing and scoring this pull request.",
-                    scorer.user
-                ));
-            }
-        }
-
-        Ok(message)
+        let reviewers = scorers
+            .iter()
+            .filter(|scorer| scorer.user != bot_name)
+            .map(|scorer| Value::from(scorer.user.clone()))
+            .collect::<Vec<_>>();
+        let bounties = bounties
+            .iter()
+            .map(|(issue, score)| context! { issue => issue, score => score })
+            .collect::<Vec<_>>();
+        let coauthors = coauthors
+            .iter()
+            .map(|(user, rating)| context! { user => user, rating => rating })
+            .collect::<Vec<_>>();
+
+        self.get_message(MsgCategory::RatingMessagesCommon).format(
+            [
+                ("score", Value::from(score)),
+                ("rating", Value::from(rating)),
+                ("reviewers", Value::from(reviewers)),
+                ("bounties", Value::from(bounties)),
+                ("coauthors", Value::from(coauthors)),
+            ]
+            .into_iter()
+            .collect(),
+        )
     }
 
     fn user_specific_message(&self, user: &User) -> String {
@@ -483,7 +899,9 @@ impl MessageLoader {
     }
 
     pub fn update_pr_status_message(&self, old_text: String, status: String) -> Option<String> {
-        let place = old_text.find("<details><summary>Current status:")?;
+        let place = old_text
+            .find(STATUS_MARKER)
+            .or_else(|| old_text.find(LEGACY_STATUS_START))?;
 
         let end_details = old_text[place..].find("</details>");
         if let Some(j) = end_details {
@@ -505,46 +923,60 @@ impl MessageLoader {
             lifetime_percent_reward,
             total_lifetime_percent,
             pr_number_this_week,
+            rank_up,
+            coauthors,
+            bounties,
         }: FinalMessageData,
         bot_name: &str,
-        scorers: Vec<Score>,
+        scorers: &[Score],
     ) -> anyhow::Result<String> {
+        let participants = coauthors.len() as u32 + 1;
+        let bounty_score: u32 = bounties.iter().map(|(_, score)| score).sum();
         let rating = rating_breakthrough(
             total_rating,
-            score,
+            shared::split_rating(score * 10, participants, true),
+            shared::split_rating(bounty_score * 10, participants, true),
             weekly_streak_bonus,
             monthly_streak_bonus,
             total_lifetime_percent,
         );
-        let final_common = self.rating_message(score.to_string(), rating, scorers, bot_name)?;
-
-        let optional_message = if lifetime_percent_reward > 0 && total_lifetime_percent > 5 {
-            let rank: &str = match total_lifetime_percent {
-                a if a >= 25 => "Rust",
-                a if a >= 20 => "Platinum",
-                a if a >= 15 => "Gold",
-                a if a >= 10 => "Silver",
-                a => {
-                    tracing::error!(
-                        "Expected total_lifetime_bonus as one of predefined values, but got: {a}. Recovering to Bronze",
-                    );
-                    "Bronze"
-                }
+        let final_common = self.rating_message(
+            score.to_string(),
+            rating,
+            scorers,
+            bot_name,
+            &bounties,
+            &coauthors,
+        )?;
+
+        // Ranks are configured in the contract, so the bot only announces the reached one
+        let optional_message = if let Some(rank) = rank_up {
+            let category = if lifetime_percent_reward == total_lifetime_percent {
+                MsgCategory::FinalMessagesFirstLifetimeBonus
+            } else {
+                MsgCategory::FinalMessagesLifetimeBonus
             };
-            self.get_message(MsgCategory::FinalMessagesLifetimeBonus)
+            self.get_message(category).format(
+                [
+                    ("total_lifetime_percent", total_lifetime_percent.to_string()),
+                    ("lifetime_percent", lifetime_percent_reward.to_string()),
+                    ("pr_author_username", username),
+                    ("rank_name", rank),
+                ]
+                .into_iter()
+                .collect(),
+            )?
+        } else if lifetime_percent_reward > 0 {
+            self.get_message(MsgCategory::FinalMessagesLifetimeBonusIncreased)
                 .format(
                     [
                         ("total_lifetime_percent", total_lifetime_percent.to_string()),
                         ("lifetime_percent", lifetime_percent_reward.to_string()),
                         ("pr_author_username", username),
-                        ("rank_name", rank.to_string()),
                     ]
                     .into_iter()
                     .collect(),
                 )?
-        } else if lifetime_percent_reward > 0 {
-            self.get_message(MsgCategory::FinalMessagesFirstLifetimeBonus)
-                .format([("pr_author_username", username)].into_iter().collect())?
         } else if monthly_streak_bonus > 0 {
             self.get_message(MsgCategory::FinalMessagesMonthlyStreak)
                 .format([("pr_author_username", username)].into_iter().collect())?
@@ -553,7 +985,7 @@ impl MessageLoader {
                 .format([("pr_author_username", username)].into_iter().collect())?
         } else if pr_number_this_week % 3 == 0 {
             self.get_message(MsgCategory::FinalMessagesFeedbackForm)
-                .format([].into_iter().collect())?
+                .format(HashMap::<_, String>::new())?
         } else {
             String::new()
         };
@@ -561,6 +993,50 @@ impl MessageLoader {
         Ok(format!("{}\n\n{}", final_common, optional_message))
     }
 
+    // Reply for the status command
+    pub fn pr_status_message(
+        &self,
+        check_info: &PRInfo,
+        pr: &PrMetadata,
+        user: Option<User>,
+    ) -> anyhow::Result<String> {
+        let status = PrStatus::new(check_info);
+        let votes = check_info
+            .votes
+            .iter()
+            .map(|vote| context! { user => vote.user, score => check_info.scale.label(vote.score) })
+            .collect::<Vec<_>>();
+
+        let finalization = check_info.ready_to_move_timestamp.map(|timestamp| {
+            chrono::DateTime::from_timestamp_nanos(timestamp as i64)
+                .format("%c")
+                .to_string()
+        });
+
+        // Streaks are configured in the contract, where the first one is weekly and the second one is monthly
+        let streak = |id| {
+            user.as_ref()
+                .and_then(|user| user.streaks.iter().find(|(streak_id, _)| *streak_id == id))
+                .map(|(_, data)| context! { amount => data.amount, best => data.best })
+        };
+
+        self.get_message(MsgCategory::PrStatusMessage).format(
+            [
+                ("status", Value::from(status.id())),
+                ("status_name", Value::from(self.status_name(status)?)),
+                ("votes", Value::from(votes)),
+                ("score", Value::from(check_info.average_score())),
+                ("merged", Value::from(check_info.merged)),
+                ("finalization", Value::from(finalization)),
+                ("pr_author_username", Value::from(pr.author.login.clone())),
+                ("weekly_streak", Value::from(streak(0))),
+                ("monthly_streak", Value::from(streak(1))),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
     pub fn invite_message(&self, pr_author: &str, sender: &str) -> anyhow::Result<String> {
         let include_common_message = self.get_message(MsgCategory::IncludeCommonMessage).format(
             [("pr_author_username", pr_author.to_string())]
@@ -580,19 +1056,88 @@ impl MessageLoader {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+enum PrStatus {
+    Excluded,
+    // PR was removed for inactivity
+    Stale,
+    Executed,
+    WaitingForScoring,
+    WaitingForMerge,
+    WaitingForFinalization,
+}
+
+impl PrStatus {
+    fn new(check_info: &PRInfo) -> Self {
+        if check_info.excluded {
+            Self::Excluded
+        } else if !check_info.exist {
+            Self::Stale
+        } else if check_info.executed {
+            Self::Executed
+        } else if check_info.votes.is_empty() {
+            Self::WaitingForScoring
+        } else if !check_info.merged {
+            Self::WaitingForMerge
+        } else {
+            Self::WaitingForFinalization
+        }
+    }
+
+    // Templates branch on the id, the name shown to the users is in the `status_name_messages`
+    fn id(self) -> &'static str {
+        match self {
+            Self::Excluded => "excluded",
+            Self::Stale => "stale",
+            Self::Executed => "executed",
+            Self::WaitingForScoring => "waiting for scoring",
+            Self::WaitingForMerge => "waiting for merge",
+            Self::WaitingForFinalization => "waiting for finalization",
+        }
+    }
+}
+
+// Cuts the text at the last whitespace that fits, so words and numbers stay whole
+fn truncate_at_word(text: &str, max_length: usize) -> String {
+    let Some((end, _)) = text.char_indices().nth(max_length) else {
+        return text.to_string();
+    };
+    let cut = &text[..end];
+    match text[end..].starts_with(char::is_whitespace) {
+        true => cut,
+        false => cut
+            .rsplit_once(char::is_whitespace)
+            .map_or(cut, |(cut, _)| cut),
+    }
+    .trim_end_matches(|c: char| c.is_whitespace() || c == '·')
+    .to_string()
+}
+
+pub fn format_handles(handles: &[String]) -> String {
+    handles
+        .iter()
+        .map(|handle| format!("@{handle}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn rating_breakthrough(
     total_rating: u32,
-    score: u32,
+    base_rating: u32,
+    bounty: u32,
     weekly: u32,
     monthly: u32,
     percent: u32,
 ) -> String {
     let mut result = total_rating.to_string();
-    if (weekly == 0 && monthly == 0 && percent == 0) || total_rating == 0 {
+    if (bounty == 0 && weekly == 0 && monthly == 0 && percent == 0) || total_rating == 0 {
         return result;
     }
 
-    result.push_str(&format!(" ({} base", score * 10));
+    result.push_str(&format!(" ({} base", base_rating));
+    if bounty > 0 {
+        result.push_str(&format!(" + {} bounty", bounty));
+    }
     if weekly > 0 {
         result.push_str(&format!(" + {} weekly bonus", weekly));
     }
@@ -619,7 +1164,11 @@ mod tests {
 
     use crate::messages::FinalMessageData;
 
-    use super::MessageLoader;
+    use std::collections::HashSet;
+
+    use minijinja::{context, Value};
+
+    use super::{LocalizedMessages, MessageLoader, MessageSource, Messages, MsgCategory, PrStatus};
 
     fn load_message_loader() -> MessageLoader {
         let file = include_str!("../../Messages.toml");
@@ -666,6 +1215,7 @@ mod tests {
             percentage_bonus: 5,
             period_data: vec![("all-time".to_string(), period_data(1))],
             streaks: vec![],
+            rank: Some("Bronze".to_string()),
         };
 
         let mut pr_info = shared::PRInfo {
@@ -673,10 +1223,15 @@ mod tests {
             new_repo: false,
             paused_repo: false,
             blocked_repo: false,
+            paused_contract: false,
+            migrating_contract: false,
             merged: false,
             executed: false,
             excluded: false,
             exist: true,
+            coauthors: vec![],
+            ready_to_move_timestamp: None,
+            scale: Default::default(),
         };
         let pr = PrMetadata {
             repo_info: RepoInfo {
@@ -694,15 +1249,36 @@ mod tests {
             updated_at: chrono::Utc::now(),
             body: "".to_string(),
             closed: false,
+            head_sha: None,
+            labels: None,
+            draft: false,
         };
 
-        let text1 = message_loader.include_message_text("bot", &pr_info, &pr, Some(user), None);
+        let text1 = message_loader.include_message_text(
+            "bot",
+            &pr_info,
+            &pr,
+            Some(user.clone()),
+            None,
+            None,
+        );
         let status_message_init = message_loader
             .status_message("bot", &pr_info, &pr, None)
             .unwrap();
         println!("{}", text1);
         assert!(text1.contains(&status_message_init),);
 
+        let with_intro = message_loader.include_message_text(
+            "bot",
+            &pr_info,
+            &pr,
+            Some(user),
+            None,
+            Some("Welcome to our repo!\n"),
+        );
+        assert!(with_intro.starts_with("Welcome to our repo!\n\n"));
+        assert!(with_intro.contains(&status_message_init));
+
         pr_info.votes.push(Score {
             user: "b".to_string(),
             score: 5,
@@ -735,6 +1311,9 @@ mod tests {
                     lifetime_percent_reward: 0,
                     total_lifetime_percent: 5,
                     pr_number_this_week: 1,
+                    rank_up: None,
+                    coauthors: vec![],
+                    bounties: vec![],
                 }),
             )
             .unwrap();
@@ -748,15 +1327,442 @@ mod tests {
         assert!(text3.contains(&new_status_message));
     }
 
+    #[test]
+    fn pr_status() {
+        let message_loader = load_message_loader();
+
+        let pr_info = shared::PRInfo {
+            votes: vec![Score {
+                user: "b".to_string(),
+                score: 5,
+            }],
+            exist: true,
+            merged: true,
+            ready_to_move_timestamp: Some(0),
+            ..Default::default()
+        };
+        let pr = PrMetadata {
+            repo_info: RepoInfo {
+                owner: "a".to_string(),
+                repo: "a".to_string(),
+                number: 0,
+                full_id: "a/a/0".to_string(),
+            },
+            author: User::new(
+                "a".to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+            created: chrono::Utc::now(),
+            merged: Some(chrono::Utc::now()),
+            updated_at: chrono::Utc::now(),
+            body: "".to_string(),
+            closed: false,
+            head_sha: None,
+            labels: None,
+            draft: false,
+        };
+        let user = shared::User {
+            name: "a".to_string(),
+            id: 1,
+            percentage_bonus: 0,
+            period_data: vec![],
+            streaks: vec![(
+                0,
+                shared::StreakUserData {
+                    amount: 2,
+                    best: 3,
+                    latest_time_string: "".to_string(),
+                },
+            )],
+            rank: None,
+        };
+
+        let text = message_loader
+            .pr_status_message(&pr_info, &pr, Some(user))
+            .unwrap();
+        assert!(text.contains("waiting for finalization"));
+        assert!(text.contains("@b (5)"));
+        assert!(text.contains("Thu Jan  1 00:00:00 1970"));
+        assert!(text.contains("weekly streak of 2 (best 3)"));
+        assert!(text.contains("monthly streak of 0"));
+    }
+
+    #[test]
+    fn templates_support_blocks() {
+        let messages = Messages::new(
+            vec!["{% for vote in votes %}@{vote.user} {% else %}none {% endfor %}{total | number}{% if merged %} merged{% endif %}".to_string()],
+            ["votes", "total", "merged"].map(String::from).into_iter().collect(),
+        );
+        assert!(messages.check().is_ok());
+
+        let votes = vec![context! { user => "a" }, context! { user => "b" }];
+        let text = messages
+            .format(
+                [
+                    ("votes", Value::from(votes)),
+                    ("total", Value::from(12345)),
+                    ("merged", Value::from(true)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+        assert_eq!(text, "@a @b 12,345 merged");
+
+        let undeclared = Messages::new(
+            vec!["{% if merged %}{user}{% endif %}".to_string()],
+            ["merged".to_string()].into_iter().collect(),
+        );
+        assert!(undeclared.check().is_err());
+
+        let broken = Messages::new(vec!["{% if merged %}".to_string()], HashSet::new());
+        assert!(broken.check().is_err());
+    }
+
+    #[test]
+    fn final_message_lists_bounties_and_coauthors() {
+        let message_loader = load_message_loader();
+        let pr_info = shared::PRInfo {
+            votes: vec![Score {
+                user: "b".to_string(),
+                score: 5,
+            }],
+            exist: true,
+            merged: true,
+            executed: true,
+            coauthors: vec!["c".to_string()],
+            ..Default::default()
+        };
+        let pr = PrMetadata {
+            repo_info: RepoInfo {
+                owner: "a".to_string(),
+                repo: "a".to_string(),
+                number: 0,
+                full_id: "a/a/0".to_string(),
+            },
+            author: User::new(
+                "a".to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+            created: chrono::Utc::now(),
+            merged: Some(chrono::Utc::now()),
+            updated_at: chrono::Utc::now(),
+            body: "".to_string(),
+            closed: false,
+            head_sha: None,
+            labels: None,
+            draft: false,
+        };
+
+        let text = message_loader
+            .status_message(
+                "bot",
+                &pr_info,
+                &pr,
+                Some(FinalMessageData {
+                    username: "a".to_string(),
+                    total_rating: 1234,
+                    score: 5,
+                    coauthors: vec![("c".to_string(), 1234)],
+                    bounties: vec![(7, 3)],
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        assert!(text.contains("Co-authors: @c."));
+        assert!(text.contains("| @b  | 5 |"));
+        assert!(text.contains("@b received 25 Sloth Points"));
+        assert!(text.contains("The bounty of 3 score for resolving #7"));
+        assert!(text.contains("@c received 1,234 Sloth Points as a co-author"));
+    }
+
+    #[test]
+    fn final_message_announces_rank_from_contract() {
+        let message_loader = load_message_loader();
+        let final_message = |rank_up: Option<&str>, lifetime_percent_reward| {
+            message_loader
+                .final_message(
+                    FinalMessageData {
+                        username: "a".to_string(),
+                        score: 5,
+                        lifetime_percent_reward,
+                        total_lifetime_percent: 15,
+                        rank_up: rank_up.map(str::to_string),
+                        ..Default::default()
+                    },
+                    "bot",
+                    &[],
+                )
+                .unwrap()
+        };
+
+        let text = final_message(Some("Sloth Master"), 5);
+        assert!(text.contains("achieved a Sloth Master rank"));
+        assert!(text.contains("total bonus being now 15%"));
+
+        let text = final_message(Some("Gold"), 15);
+        assert!(text.contains("honored with a Gold rank and a 15% lifetime bonus"));
+
+        let text = final_message(None, 5);
+        assert!(!text.contains("rank"));
+        assert!(text.contains("total bonus being now 15%"));
+    }
+
+    #[test]
+    fn check_status_summary() {
+        let message_loader = load_message_loader();
+        let merged = chrono::DateTime::parse_from_rfc3339("2024-10-01T10:00:00Z")
+            .unwrap()
+            .to_utc();
+        let pr = PrMetadata {
+            repo_info: RepoInfo {
+                owner: "a".to_string(),
+                repo: "a".to_string(),
+                number: 0,
+                full_id: "a/a/0".to_string(),
+            },
+            author: User::new(
+                "a".to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+            created: merged,
+            merged: Some(merged),
+            updated_at: merged,
+            body: "".to_string(),
+            closed: false,
+            head_sha: Some("abc".to_string()),
+            labels: None,
+            draft: false,
+        };
+        let mut pr_info = shared::PRInfo {
+            exist: true,
+            ..Default::default()
+        };
+
+        let text = message_loader
+            .check_status_message(&pr_info, &pr, None)
+            .unwrap();
+        assert_eq!(text, "waiting for scoring");
+
+        pr_info.merged = true;
+        pr_info.votes = vec![
+            Score {
+                user: "b".to_string(),
+                score: 5,
+            },
+            Score {
+                user: "c".to_string(),
+                score: 8,
+            },
+        ];
+        let text = message_loader
+            .check_status_message(&pr_info, &pr, None)
+            .unwrap();
+        assert_eq!(
+            text,
+            "waiting for finalization · 2 votes with average score 6 · projected rating 60 · finalization on Oct 02 10:00 UTC"
+        );
+
+        pr_info.executed = true;
+        let text = message_loader
+            .check_status_message(
+                &pr_info,
+                &pr,
+                Some(&FinalMessageData {
+                    total_rating: 75,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        assert_eq!(text, "executed · 2 votes with average score 6 · rating 75");
+    }
+
+    #[test]
+    fn check_status_summary_drops_optional_parts() {
+        let mut message_loader = load_message_loader();
+        message_loader.status_name_messages = Messages::new(
+            vec![
+                "waiting for the maintainers to finalize the pull request after merging"
+                    .to_string(),
+            ],
+            ["status".to_string()].into_iter().collect(),
+        );
+        let merged = chrono::DateTime::parse_from_rfc3339("2024-10-01T10:00:00Z")
+            .unwrap()
+            .to_utc();
+        let pr = PrMetadata {
+            repo_info: RepoInfo {
+                owner: "a".to_string(),
+                repo: "a".to_string(),
+                number: 0,
+                full_id: "a/a/0".to_string(),
+            },
+            author: User::new(
+                "a".to_string(),
+                octocrab::models::AuthorAssociation::Contributor,
+            ),
+            created: merged,
+            merged: Some(merged),
+            updated_at: merged,
+            body: "".to_string(),
+            closed: false,
+            head_sha: Some("abc".to_string()),
+            labels: None,
+            draft: false,
+        };
+        let pr_info = shared::PRInfo {
+            exist: true,
+            merged: true,
+            votes: vec![
+                Score {
+                    user: "b".to_string(),
+                    score: 5,
+                },
+                Score {
+                    user: "c".to_string(),
+                    score: 8,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let text = message_loader
+            .check_status_message(&pr_info, &pr, None)
+            .unwrap();
+        assert_eq!(
+            text,
+            "waiting for the maintainers to finalize the pull request after merging · 2 votes with average score 6 · projected rating 60"
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_words_whole() {
+        assert_eq!(super::truncate_at_word("one two", 7), "one two");
+        assert_eq!(super::truncate_at_word("one two three", 9), "one two");
+        assert_eq!(super::truncate_at_word("one two · three", 10), "one two");
+        assert_eq!(super::truncate_at_word("one two", 3), "one");
+        assert_eq!(super::truncate_at_word("onetwo", 3), "one");
+    }
+
+    #[test]
+    fn locales_are_loaded_and_valid() {
+        for file in ["Messages.toml", "Messages.staging.toml"] {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join(file);
+            let messages = LocalizedMessages::load_from_file(&path, "bot").unwrap();
+
+            assert_eq!(messages.locales(), vec!["en", "es", "zh"]);
+            assert!(messages.is_supported("ES"));
+            assert!(!messages.is_supported("fr"));
+            // Links are inherited from the main file
+            assert_eq!(messages.get(Some("es")).link, messages.get(None).link);
+
+            let reply = |locale| {
+                messages
+                    .get(locale)
+                    .get_message(MsgCategory::LangMessage)
+                    .format([("user", "a".to_string())].into_iter().collect())
+                    .unwrap()
+            };
+            assert!(reply(Some("es")).contains("español"));
+            assert!(reply(Some("zh")).contains("中文"));
+            assert!(reply(Some("fr")).contains("English"));
+
+            let status = |locale| {
+                messages
+                    .get(locale)
+                    .status_name(PrStatus::WaitingForScoring)
+                    .unwrap()
+            };
+            assert_eq!(status(None), "waiting for scoring");
+            assert_eq!(status(Some("es")), "esperando puntuación");
+            assert_eq!(status(Some("zh")), "等待评分");
+        }
+    }
+
+    #[test]
+    fn messages_are_reloaded_on_change() {
+        let dir = std::env::temp_dir().join(format!("sloth-messages-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Messages.toml");
+        let original = include_str!("../../Messages.toml");
+        std::fs::write(&path, original).unwrap();
+
+        let source = MessageSource::load(path.clone(), "bot".to_string()).unwrap();
+        let before = source.current();
+        assert!(!source.reload_if_changed());
+
+        let touch = |text: &str, seconds| {
+            std::fs::write(&path, text).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(
+                    std::time::SystemTime::now() + std::time::Duration::from_secs(seconds),
+                )
+                .unwrap();
+        };
+
+        touch("link = 1", 10);
+        assert!(!source.reload_if_changed());
+        assert!(std::sync::Arc::ptr_eq(&before, &source.current()));
+
+        touch(
+            &original.replace("Language updated", "Language changed"),
+            20,
+        );
+        assert!(source.reload_if_changed());
+        let text = source
+            .current()
+            .get(None)
+            .get_message(MsgCategory::LangMessage)
+            .format([("user", "a".to_string())].into_iter().collect())
+            .unwrap();
+        assert!(text.contains("Language changed"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn locale_with_different_variables_is_rejected() {
+        let default = load_message_loader();
+        let mut locale = load_message_loader();
+        assert!(locale.validate(&default).is_ok());
+
+        locale.lang_messages = Messages::new(
+            vec!["{username}".to_string()],
+            ["username".to_string()].into_iter().collect(),
+        );
+        assert!(locale.validate(&default).is_err());
+
+        locale.lang_messages = Messages::new(vec![], ["user".to_string()].into_iter().collect());
+        assert!(locale.validate(&default).is_err());
+    }
+
+    #[test]
+    fn locale_without_status_name_is_rejected() {
+        let mut locale = load_message_loader();
+        assert!(locale.check_templates().is_ok());
+
+        locale.status_name_messages = Messages::new(
+            vec![r#"{% if status == "excluded" %}excluded{% endif %}"#.to_string()],
+            ["status".to_string()].into_iter().collect(),
+        );
+        assert!(locale.check_templates().is_err());
+    }
+
     #[test]
     fn rating_breakthrough_full() {
         let total_rating = 100;
-        let score = 5;
+        let base_rating = 50;
         let weekly = 10;
         let monthly = 20;
         let percent = 5;
 
-        let result = super::rating_breakthrough(total_rating, score, weekly, monthly, percent);
+        let result =
+            super::rating_breakthrough(total_rating, base_rating, 0, weekly, monthly, percent);
         assert_eq!(
             result,
             "100 (50 base + 10 weekly bonus + 20 monthly bonus + 5% lifetime bonus)"
@@ -765,13 +1771,19 @@ mod tests {
 
     #[test]
     fn rating_breakthrough_none() {
-        let result = super::rating_breakthrough(100, 10, 0, 0, 0);
+        let result = super::rating_breakthrough(100, 100, 0, 0, 0, 0);
         assert_eq!(result, "100");
     }
 
     #[test]
     fn rating_breakthrough_partial() {
-        let result = super::rating_breakthrough(100, 5, 0, 5, 0);
+        let result = super::rating_breakthrough(100, 50, 0, 0, 5, 0);
         assert_eq!(result, "100 (50 base + 5 monthly bonus)");
     }
+
+    #[test]
+    fn rating_breakthrough_bounty() {
+        let result = super::rating_breakthrough(180, 50, 130, 0, 0, 0);
+        assert_eq!(result, "180 (50 base + 130 bounty)");
+    }
 }
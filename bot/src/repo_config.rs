@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use shared::{github::User, GithubHandle, Provider, ScoringScale};
+
+/// Optional configuration that maintainers put into the repository.
+/// Missing fields fall back to the global bot behavior.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RepoConfig {
+    /// Who can score pull requests: "everyone", "maintainers" or a list of handles
+    pub scorers: Scorers,
+    /// Whether the PRs are included without a command: by mentioning the bot in the PR body
+    /// or automatically for the registered authors
+    pub auto_include: bool,
+    /// Days without updates after which the PR is removed from the race
+    pub stale_days: i64,
+    /// Days without updates after which the author is warned that the PR is going stale.
+    /// It has to be less than `stale_days`
+    pub stale_warning_days: i64,
+    /// Text added to the top of the status message
+    pub intro: Option<String>,
+    /// Scale of the new PRs: "fibonacci", "linear" or "tshirt". The contract default is used if not set
+    pub scale: Option<ScoringScale>,
+    /// Language of the replies, e.g. "es". Users can override it with the `lang` command
+    pub locale: Option<String>,
+    /// Where the PR status is published: "comment", "checks" (commit status only) or "both"
+    pub status: StatusReporting,
+    /// Whether the bot keeps the `sloth:included` and `sloth:finalized` labels on the PRs
+    pub status_labels: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Scorers {
+    Group(ScorerGroup),
+    Handles(Vec<GithubHandle>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScorerGroup {
+    Everyone,
+    Maintainers,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusReporting {
+    #[default]
+    Comment,
+    Checks,
+    Both,
+}
+
+impl StatusReporting {
+    pub fn comment(self) -> bool {
+        matches!(self, Self::Comment | Self::Both)
+    }
+
+    pub fn checks(self) -> bool {
+        matches!(self, Self::Checks | Self::Both)
+    }
+}
+
+impl Default for RepoConfig {
+    fn default() -> Self {
+        Self {
+            scorers: Scorers::Group(ScorerGroup::Everyone),
+            auto_include: true,
+            stale_days: 14,
+            stale_warning_days: 10,
+            intro: None,
+            scale: None,
+            locale: None,
+            status: StatusReporting::Comment,
+            status_labels: false,
+        }
+    }
+}
+
+impl RepoConfig {
+    pub fn path(provider: Provider) -> &'static str {
+        match provider {
+            Provider::GitHub => ".github/race-of-sloths.toml",
+            Provider::GitLab => ".gitlab/race-of-sloths.toml",
+        }
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let config: Self = toml::from_str(text)?;
+        anyhow::ensure!(config.stale_days > 0, "stale_days must be positive");
+        anyhow::ensure!(
+            config.stale_warning_days < config.stale_days,
+            "stale_warning_days must be less than stale_days"
+        );
+        Ok(config)
+    }
+
+    pub fn can_score(&self, user: &User) -> bool {
+        match &self.scorers {
+            Scorers::Group(ScorerGroup::Everyone) => true,
+            Scorers::Group(ScorerGroup::Maintainers) => user.is_maintainer(),
+            Scorers::Handles(handles) => handles
+                .iter()
+                .any(|handle| handle.eq_ignore_ascii_case(&user.login)),
+        }
+    }
+}
+
+/// Configs by repository, valid while the default branch stays on the same commit.
+/// The commit of the default branch is cached for a short time, so we don't ask for it on every event
+#[derive(Debug, Default)]
+pub struct RepoConfigCache {
+    configs: Mutex<HashMap<String, (String, RepoConfig)>>,
+    shas: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl RepoConfigCache {
+    pub const SHA_TTL: Duration = Duration::from_secs(60);
+
+    pub fn sha(&self, repo: &str) -> Option<String> {
+        self.sha_at(repo, Instant::now())
+    }
+
+    fn sha_at(&self, repo: &str, now: Instant) -> Option<String> {
+        self.shas
+            .lock()
+            .ok()?
+            .get(repo)
+            .filter(|(fetched_at, _)| now.saturating_duration_since(*fetched_at) < Self::SHA_TTL)
+            .map(|(_, sha)| sha.clone())
+    }
+
+    pub fn insert_sha(&self, repo: String, sha: String) {
+        self.insert_sha_at(repo, sha, Instant::now());
+    }
+
+    fn insert_sha_at(&self, repo: String, sha: String, now: Instant) {
+        if let Ok(mut shas) = self.shas.lock() {
+            shas.insert(repo, (now, sha));
+        }
+    }
+
+    pub fn get(&self, repo: &str, sha: &str) -> Option<RepoConfig> {
+        self.configs
+            .lock()
+            .ok()?
+            .get(repo)
+            .filter(|(cached_sha, _)| cached_sha == sha)
+            .map(|(_, config)| config.clone())
+    }
+
+    pub fn insert(&self, repo: String, sha: String, config: RepoConfig) {
+        if let Ok(mut configs) = self.configs.lock() {
+            configs.insert(repo, (sha, config));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use octocrab::models::AuthorAssociation;
+    use shared::{github::User, ScoringScale};
+
+    use std::time::Instant;
+
+    use super::{RepoConfig, RepoConfigCache, ScorerGroup, Scorers, StatusReporting};
+
+    #[test]
+    fn parse_full_config() {
+        let config = RepoConfig::parse(
+            r#"
+            scorers = "maintainers"
+            auto_include = false
+            stale_days = 30
+            stale_warning_days = 25
+            intro = "Thanks for contributing!"
+            scale = "tshirt"
+            locale = "zh"
+            status = "checks"
+            status_labels = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            RepoConfig {
+                scorers: Scorers::Group(ScorerGroup::Maintainers),
+                auto_include: false,
+                stale_days: 30,
+                stale_warning_days: 25,
+                intro: Some("Thanks for contributing!".to_string()),
+                scale: Some(ScoringScale::TShirt),
+                locale: Some("zh".to_string()),
+                status: StatusReporting::Checks,
+                status_labels: true,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        assert_eq!(RepoConfig::parse("").unwrap(), RepoConfig::default());
+        assert_eq!(
+            RepoConfig::parse("stale_days = 20").unwrap().scorers,
+            Scorers::Group(ScorerGroup::Everyone)
+        );
+        assert!(RepoConfig::parse("scorers = \"nobody\"").is_err());
+        assert!(RepoConfig::parse("status = \"nowhere\"").is_err());
+    }
+
+    #[test]
+    fn stale_days_are_validated() {
+        assert!(RepoConfig::parse("stale_days = 0").is_err());
+        assert!(RepoConfig::parse("stale_days = 10").is_err());
+        assert!(RepoConfig::parse("stale_days = 11").is_ok());
+        assert!(RepoConfig::parse("stale_days = 7\nstale_warning_days = 7").is_err());
+        assert!(RepoConfig::parse("stale_days = 7\nstale_warning_days = 6").is_ok());
+    }
+
+    #[test]
+    fn scorers_restriction() {
+        let maintainer = User::new("maintainer".to_string(), AuthorAssociation::Member);
+        let contributor = User::new("Contributor".to_string(), AuthorAssociation::Contributor);
+
+        assert!(RepoConfig::default().can_score(&contributor));
+
+        let config = RepoConfig::parse("scorers = \"maintainers\"").unwrap();
+        assert!(config.can_score(&maintainer));
+        assert!(!config.can_score(&contributor));
+
+        let config = RepoConfig::parse("scorers = [\"contributor\"]").unwrap();
+        assert!(!config.can_score(&maintainer));
+        assert!(config.can_score(&contributor));
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_new_commit() {
+        let cache = RepoConfigCache::default();
+        cache.insert(
+            "org/repo".to_string(),
+            "a".to_string(),
+            RepoConfig::default(),
+        );
+
+        assert_eq!(cache.get("org/repo", "a"), Some(RepoConfig::default()));
+        assert_eq!(cache.get("org/repo", "b"), None);
+        assert_eq!(cache.get("org/other", "a"), None);
+    }
+
+    #[test]
+    fn sha_expires() {
+        let cache = RepoConfigCache::default();
+        let now = Instant::now();
+        cache.insert_sha_at("org/repo".to_string(), "a".to_string(), now);
+
+        assert_eq!(cache.sha_at("org/repo", now), Some("a".to_string()));
+        assert_eq!(cache.sha_at("org/other", now), None);
+        assert_eq!(
+            cache.sha_at("org/repo", now + RepoConfigCache::SHA_TTL),
+            None
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use near_sdk::near_bindgen;
+use shared::{SlothAction, SlothActionResult};
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Applies the actions in order. Actions that would panic are rejected with the reason instead,
+    /// so a single outdated action doesn't revert the whole batch
+    pub fn sloth_batch(&mut self, actions: Vec<SlothAction>) -> Vec<SlothActionResult> {
+        self.assert_sloth();
+        events::take_logged();
+
+        actions
+            .into_iter()
+            .map(|action| {
+                if let Err(reason) = self.check_action(&action) {
+                    return SlothActionResult::Rejected {
+                        reason: reason.to_string(),
+                    };
+                }
+                self.apply_action(action);
+                SlothActionResult::Applied {
+                    events: events::take_logged(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    // Uses the same checks as the actions' methods, which panic instead
+    fn check_action(&self, action: &SlothAction) -> Result<(), &'static str> {
+        match action {
+            SlothAction::Include {
+                organization,
+                repo,
+                pr_number,
+                override_exclude,
+                ..
+            } => {
+                self.check_repo_active(organization, repo)?;
+                let pr_id = format!("{organization}/{repo}/{pr_number}");
+                self.check_includable(&pr_id, *override_exclude)?;
+            }
+            SlothAction::Scored {
+                pr_id, user, score, ..
+            } => {
+                let pr = self.check_started_pr(pr_id)?;
+                Self::check_scorable(&pr, user, *score)?;
+            }
+            SlothAction::Merged { pr_id, .. } => {
+                self.check_started_pr(pr_id)?;
+            }
+            SlothAction::Stale { pr_id } => {
+                Self::check_stalable(&self.check_started_pr(pr_id)?)?;
+            }
+            SlothAction::Finalize {
+                pr_id, timestamp, ..
+            } => {
+                let timestamp = timestamp.unwrap_or_else(env::block_timestamp);
+                Self::check_finalizable(&self.check_started_pr(pr_id)?, timestamp)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_action(&mut self, action: SlothAction) {
+        match action {
+            SlothAction::Include {
+                organization,
+                repo,
+                user,
+                pr_number,
+                created_at,
+                override_exclude,
+                scale,
+                github_id,
+            } => self.sloth_include(
+                organization,
+                repo,
+                user,
+                pr_number,
+                created_at,
+                override_exclude,
+                scale,
+                github_id,
+            ),
+            SlothAction::Scored {
+                pr_id,
+                user,
+                score,
+                github_id,
+            } => self.sloth_scored(pr_id, user, score, github_id),
+            SlothAction::Merged { pr_id, merged_at } => self.sloth_merged(pr_id, merged_at),
+            SlothAction::Stale { pr_id } => self.sloth_stale(pr_id),
+            SlothAction::Finalize {
+                pr_id,
+                active_pr,
+                timestamp,
+                closed_issues,
+            } => self.sloth_finalize(pr_id, active_pr, timestamp, closed_issues),
+        }
+    }
+}
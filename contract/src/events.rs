@@ -1,6 +1,18 @@
+use std::cell::RefCell;
+
 use near_sdk::env;
 use shared::Event;
 
+thread_local! {
+    // Events of the current call, so `sloth_batch` can return them per action
+    static LOGGED: RefCell<Vec<Event>> = const { RefCell::new(vec![]) };
+}
+
 pub fn log_event(event: Event) {
     env::log_str(&near_sdk::serde_json::to_string(&event).unwrap());
+    LOGGED.with(|logged| logged.borrow_mut().push(event));
+}
+
+pub fn take_logged() -> Vec<Event> {
+    LOGGED.with(|logged| logged.take())
 }
@@ -0,0 +1,51 @@
+// Cross-contract wrappers generated by `near_bindgen` don't inherit the method level allow,
+// so the lint is allowed for this module only
+#![allow(clippy::too_many_arguments)]
+
+use near_sdk::near_bindgen;
+
+use super::*;
+
+#[near_bindgen]
+impl Contract {
+    pub fn sloth_include(
+        &mut self,
+        organization: String,
+        repo: String,
+        user: String,
+        pr_number: u64,
+        created_at: Timestamp,
+        override_exclude: bool,
+        scale: Option<ScoringScale>,
+        github_id: Option<u64>,
+    ) {
+        self.assert_sloth();
+        self.assert_repo_active(&organization, &repo);
+        let (user_id, _) = self.get_or_create_account_by_id(&user, github_id);
+
+        let pr_id = format!("{organization}/{repo}/{pr_number}");
+
+        self.check_includable(&pr_id, override_exclude)
+            .unwrap_or_else(|e| env::panic_str(e));
+        self.excluded_prs.remove(&pr_id);
+        // Including it again starts from scratch
+        self.stale_prs.remove(&pr_id);
+
+        let timestamp = env::block_timestamp();
+        let mut pr = PRv6::new(
+            organization,
+            repo,
+            pr_number,
+            user.clone(),
+            timestamp,
+            created_at,
+            scale.unwrap_or(self.scoring_scale),
+        );
+        pr.set_user_id(&user, user_id);
+
+        self.apply_to_periods(pr.included_at, user_id, |data| data.pr_opened());
+        self.auto_include_users.insert(user_id);
+        self.index_pr(&pr);
+        self.prs.insert(pr_id, VersionedPR::V6(pr));
+    }
+}
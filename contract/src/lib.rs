@@ -1,19 +1,23 @@
 use near_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
     require,
-    store::{IterableMap, LookupMap, LookupSet, Vector},
+    store::{IterableMap, LookupMap, LookupSet, TreeMap, Vector},
     Timestamp,
 };
 use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
 use shared::{
-    AccountWithPermanentPercentageBonus, AllowedRepos, Event, GithubHandle, IntoEnumIterator, PRId,
-    PRv2, Repo, Streak, StreakId, StreakReward, StreakType, StreakUserData, TimePeriod,
-    TimePeriodString, UserId, UserPeriodDataV2, VersionedAccount, VersionedPR, VersionedStreak,
-    VersionedStreakUserData, VersionedUserPeriodData,
+    AccountWithPermanentPercentageBonus, Adjustment, AllowedRepos, ArchiveSummary, Bounty, Event,
+    FlatBonusStorage, GithubHandle, IntoEnumIterator, PRId, PRv6, ParticipantRating, Provider,
+    RankTier, Repo, Revocation, ScoringScale, Streak, StreakId, StreakReward, StreakType,
+    StreakUserData, TimePeriod, TimePeriodString, UserId, UserPeriodDataDelta, UserPeriodDataV2,
+    VersionedAccount, VersionedBounty, VersionedPR, VersionedStreak, VersionedStreakUserData,
+    VersionedUserPeriodData,
 };
 use types::{Repository, RepositoryStatus, RepositoryV2, VersionedRepository};
 
+pub mod batch;
 pub mod events;
+pub mod include;
 pub mod migrate;
 pub mod mock;
 pub mod storage;
@@ -22,6 +26,9 @@ mod tests;
 pub mod types;
 pub mod views;
 
+pub const MAX_LOCALE_LENGTH: usize = 16;
+pub const MAX_REASON_LENGTH: usize = 256;
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
@@ -41,6 +48,51 @@ pub struct Contract {
 
     // Repo allowlist
     repos: IterableMap<(GithubHandle, GithubHandle), VersionedRepository>,
+
+    // Open bounties per issue
+    bounties: IterableMap<(GithubHandle, GithubHandle, u64), VersionedBounty>,
+
+    // Scale of the new PRs if the repository doesn't configure its own
+    scoring_scale: ScoringScale,
+    // Preferred locale of the bot messages per user
+    user_locales: LookupMap<UserId, String>,
+    // Users that don't want their PRs to be included without mentioning the bot
+    auto_include_opt_outs: LookupSet<UserId>,
+    // PRs removed for inactivity with the time they became stale, so they can be restored
+    stale_prs: IterableMap<PRId, (VersionedPR, Timestamp)>,
+    // Accounts that can revoke finalized PRs, managed by the contract account
+    moderators: LookupSet<AccountId>,
+    revoked_prs: IterableMap<PRId, (VersionedPR, Revocation)>,
+    // Log of the manual corrections of the period data
+    adjustments: Vector<(UserId, Adjustment)>,
+    // GitHub numeric ids survive renames, unlike the handles in `account_ids`
+    github_ids: LookupMap<u64, UserId>,
+    user_github_ids: LookupMap<UserId, u64>,
+    // Executed PRs are archived after a while to release the storage
+    archive_summaries: LookupMap<UserId, ArchiveSummary>,
+    // Indexes of `prs`, so the bot doesn't have to scan all of them every hour
+    unmerged_pr_ids: TreeMap<PRId, ()>,
+    // Merged PRs ordered by the time they can be finalized
+    finalization_queue: TreeMap<(Timestamp, PRId), ()>,
+    // Emergency switch, e.g. if the sloth key leaks. Only the admin can change the data while it's set
+    paused: bool,
+    // Number of the applied `migrate::MIGRATIONS` and the progress of the next one
+    state_version: u32,
+    migration_cursor: u32,
+    // Ranks by the lifetime percentage bonus, sorted by the threshold
+    rank_tiers: Vec<RankTier>,
+    // The last rank announced to the user, so each rank-up is logged once
+    user_ranks: LookupMap<UserId, String>,
+    // Users that included a PR of their own or enabled the auto-include, only they are included automatically
+    auto_include_users: LookupSet<UserId>,
+    // Executed PRs ordered by the merge time, so the oldest ones are archived first
+    archive_queue: TreeMap<(Timestamp, PRId), ()>,
+    // Ids of the archived PRs, so they can't be included again
+    archived_prs: LookupSet<PRId>,
+    // Positions of the user's entries in `adjustments`, so they are read without scanning the whole log
+    user_adjustments: LookupMap<UserId, Vec<u32>>,
+    // Users linked to a GitHub id, so the bot pages over them without walking all of the users
+    linked_user_ids: TreeMap<UserId, ()>,
 }
 
 #[near_bindgen]
@@ -58,6 +110,29 @@ impl Contract {
             streaks: Vector::new(storage::StorageKey::Streaks),
             user_streaks: LookupMap::new(storage::StorageKey::UserStreaks),
             repos: IterableMap::new(storage::StorageKey::ReposNew),
+            bounties: IterableMap::new(storage::StorageKey::Bounties),
+            scoring_scale: ScoringScale::default(),
+            user_locales: LookupMap::new(storage::StorageKey::UserLocales),
+            auto_include_opt_outs: LookupSet::new(storage::StorageKey::AutoIncludeOptOuts),
+            stale_prs: IterableMap::new(storage::StorageKey::StalePRs),
+            moderators: LookupSet::new(storage::StorageKey::Moderators),
+            revoked_prs: IterableMap::new(storage::StorageKey::RevokedPRs),
+            adjustments: Vector::new(storage::StorageKey::Adjustments),
+            github_ids: LookupMap::new(storage::StorageKey::GithubIds),
+            user_github_ids: LookupMap::new(storage::StorageKey::UserGithubIds),
+            archive_summaries: LookupMap::new(storage::StorageKey::ArchiveSummaries),
+            unmerged_pr_ids: TreeMap::new(storage::StorageKey::UnmergedPRIds),
+            finalization_queue: TreeMap::new(storage::StorageKey::FinalizationQueue),
+            paused: false,
+            state_version: migrate::MIGRATIONS.len() as u32,
+            migration_cursor: 0,
+            rank_tiers: RankTier::default_tiers(),
+            user_ranks: LookupMap::new(storage::StorageKey::UserRanks),
+            auto_include_users: LookupSet::new(storage::StorageKey::AutoIncludeUsers),
+            archive_queue: TreeMap::new(storage::StorageKey::ArchiveQueue),
+            archived_prs: LookupSet::new(storage::StorageKey::ArchivedPRs),
+            user_adjustments: LookupMap::new(storage::StorageKey::UserAdjustments),
+            linked_user_ids: TreeMap::new(storage::StorageKey::LinkedUserIds),
         };
 
         for org in allowed_repos {
@@ -144,98 +219,194 @@ impl Contract {
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub fn sloth_include(
+    pub fn sloth_scored(
         &mut self,
-        organization: String,
-        repo: String,
+        pr_id: String,
         user: String,
-        pr_number: u64,
-        created_at: Timestamp,
-        override_exclude: bool,
+        score: u32,
+        github_id: Option<u64>,
     ) {
         self.assert_sloth();
-        self.assert_repo_active(&organization, &repo);
-        let (user_id, _) = self.get_or_create_account(&user);
-
-        let pr_id = format!("{organization}/{repo}/{pr_number}");
 
-        if self.excluded_prs.contains(&pr_id) {
-            if !override_exclude {
-                env::panic_str("Excluded PR cannot be included without override flag")
-            }
-            self.excluded_prs.remove(&pr_id);
+        let mut pr = self
+            .check_started_pr(&pr_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        Self::check_scorable(&pr, &user, score).unwrap_or_else(|e| env::panic_str(e));
+        let old_score = pr.score().unwrap_or_default();
+        if pr.add_score(user.clone(), score).is_none() {
+            // Reward user for scoring the PR.
+            let (scorer_id, _) = self.get_or_create_account_by_id(&user, github_id);
+            pr.set_user_id(&user, scorer_id);
+            self.apply_to_periods(pr.included_at, scorer_id, |data| {
+                data.reward_for_scoring();
+            })
         }
+        let new_score = pr.score().unwrap();
+        let participants = pr.participants_amount();
 
-        // Check if PR already exists
-        let pr = self.prs.get(&pr_id).or(self.executed_prs.get(&pr_id));
-        if pr.is_some() {
-            env::panic_str("PR already exists: {pr_id}")
+        for participant in pr.participants() {
+            let (user_id, _) = self.pr_account(&pr, &participant);
+            let author = participant == pr.author;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_scored(old_score, new_score, participants, author);
+            });
         }
 
-        let timestamp = env::block_timestamp();
-        let pr = PRv2::new(organization, repo, pr_number, user, timestamp, created_at);
-
-        self.apply_to_periods(pr.included_at, user_id, |data| data.pr_opened());
-        self.prs.insert(pr_id, VersionedPR::V2(pr));
+        self.prs.insert(pr_id.clone(), VersionedPR::V6(pr));
     }
 
-    pub fn sloth_scored(&mut self, pr_id: String, user: String, score: u32) {
+    // Retracts the user's vote, e.g. when the score comment was deleted
+    pub fn sloth_unscored(&mut self, pr_id: String, user: String) {
         self.assert_sloth();
 
-        let mut pr: PRv2 = match self.prs.get(&pr_id).cloned() {
+        let mut pr: PRv6 = match self.prs.get(&pr_id).cloned() {
             Some(x) => x.into(),
             None => env::panic_str("PR is not started or already executed"),
         };
-        let (user_id, _) = self.get_or_create_account(&pr.author);
         let old_score = pr.score().unwrap_or_default();
-        if pr.add_score(user.clone(), score).is_none() {
-            // Reward user for scoring the PR.
-            let (scorer_id, _) = self.get_or_create_account(&user);
-            self.apply_to_periods(pr.included_at, scorer_id, |data| {
-                data.reward_for_scoring();
-            })
+        if pr.remove_score(&user).is_none() {
+            env::panic_str("User has not scored the PR");
         }
-        let new_score = pr.score().unwrap();
 
-        self.apply_to_periods(pr.included_at, user_id, |data| {
-            data.pr_scored(old_score, new_score);
+        let (scorer_id, _) = self.pr_account(&pr, &user);
+        self.apply_to_periods(pr.included_at, scorer_id, |data| {
+            data.remove_reward_for_scoring();
         });
 
-        self.prs.insert(pr_id.clone(), VersionedPR::V2(pr));
+        let new_score = pr.score().unwrap_or_default();
+        let participants = pr.participants_amount();
+
+        for participant in pr.participants() {
+            let (user_id, _) = self.pr_account(&pr, &participant);
+            let author = participant == pr.author;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_scored(old_score, new_score, participants, author);
+            });
+        }
+
+        self.prs.insert(pr_id.clone(), VersionedPR::V6(pr));
     }
 
     pub fn sloth_merged(&mut self, pr_id: String, merged_at: Timestamp) {
         self.assert_sloth();
 
-        let mut pr: PRv2 = match self.prs.get(&pr_id).cloned() {
+        let mut pr = self
+            .check_started_pr(&pr_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        self.unindex_pr(&pr);
+        pr.add_merge_info(merged_at);
+        self.index_pr(&pr);
+
+        for participant in pr.participants() {
+            let (user_id, _) = self.pr_account(&pr, &participant);
+            self.apply_to_periods(merged_at, user_id, |data| data.pr_merged());
+        }
+        self.prs.insert(pr_id, VersionedPR::V6(pr));
+    }
+
+    // `github_ids` are the ids of the co-authors in the same order, if they are known
+    pub fn sloth_coauthors(
+        &mut self,
+        pr_id: String,
+        coauthors: Vec<GithubHandle>,
+        github_ids: Option<Vec<Option<u64>>>,
+    ) {
+        self.assert_sloth();
+
+        let mut pr: PRv6 = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
             None => env::panic_str("PR is not started or already executed"),
         };
-        pr.add_merge_info(merged_at);
-        let (user_id, _) = self.get_or_create_account(&pr.author);
+        require!(
+            pr.merged_at.is_none(),
+            "Co-authors cannot be changed for merged PR"
+        );
+        let github_ids = github_ids.unwrap_or_else(|| vec![None; coauthors.len()]);
+        require!(
+            github_ids.len() == coauthors.len(),
+            "Every co-author needs a GitHub id or None"
+        );
+
+        let mut new_coauthors: Vec<(GithubHandle, Option<u64>)> = vec![];
+        for (coauthor, github_id) in coauthors.into_iter().zip(github_ids) {
+            if coauthor != pr.author && new_coauthors.iter().all(|(c, _)| *c != coauthor) {
+                new_coauthors.push((coauthor, github_id));
+            }
+        }
+        require!(
+            new_coauthors.len() <= shared::MAX_COAUTHORS,
+            "Too many co-authors"
+        );
+
+        let score = pr.score().unwrap_or_default();
+        let old_participants = pr.participants();
+        let old_amount = pr.participants_amount();
+        let mut old_ids = vec![];
+        for participant in &old_participants {
+            old_ids.push(self.pr_account(&pr, participant).0);
+        }
+        let mut scorer_ids = vec![];
+        for vote in &pr.score {
+            scorer_ids.push(self.pr_account(&pr, &vote.user).0);
+        }
+
+        pr.coauthors = vec![];
+        for (coauthor, github_id) in new_coauthors {
+            let user_id = match pr.user_id(&coauthor) {
+                Some(user_id) => user_id,
+                None => self.get_or_create_account_by_id(&coauthor, github_id).0,
+            };
+            require!(
+                !scorer_ids.contains(&user_id),
+                "Scorers cannot be co-authors"
+            );
+            pr.set_user_id(&coauthor, user_id);
+            pr.coauthors.push(coauthor);
+        }
+        let new_amount = pr.participants_amount();
+        let mut new_ids = vec![];
+        for participant in pr.participants() {
+            new_ids.push(self.pr_account(&pr, &participant).0);
+        }
+
+        // Take the PR back from old participants and give it to the new ones.
+        // Participants that stay receive the difference in the rating share.
+        let mut user_ids = old_ids.clone();
+        user_ids.extend(new_ids.iter().filter(|id| !old_ids.contains(id)));
+        // The author comes first and doesn't change
+        let author_id = old_ids[0];
+        for user_id in user_ids {
+            let was = old_ids.contains(&user_id);
+            let is = new_ids.contains(&user_id);
+            let author = user_id == author_id;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                if was {
+                    data.pr_closed(score, old_amount, author);
+                }
+                if is {
+                    data.pr_opened();
+                    data.pr_scored(0, score, new_amount, author);
+                }
+            });
+        }
 
-        self.apply_to_periods(merged_at, user_id, |data| data.pr_merged());
-        self.prs.insert(pr_id, VersionedPR::V2(pr));
+        self.prs.insert(pr_id, VersionedPR::V6(pr));
     }
 
     pub fn sloth_exclude(&mut self, pr_id: String) {
         self.assert_sloth();
-        let pr: PRv2 = match self.prs.get(&pr_id).cloned() {
+        let pr: PRv6 = match self.prs.get(&pr_id).cloned() {
             Some(pr) => pr.into(),
             None => env::panic_str("PR is not started or already executed"),
         };
         if pr.merged_at.is_some() {
             env::panic_str("Merged PR cannot be excluded")
         }
-        let (user_id, _) = self.get_or_create_account(&pr.author);
+        self.close_for_participants(&pr);
+        self.unindex_pr(&pr);
 
-        self.apply_to_periods(pr.included_at, user_id, |data| {
-            data.pr_closed(pr.score().unwrap_or_default());
-        });
-
-        for score in pr.score {
-            let (scorer_id, _) = self.get_or_create_account(&score.user);
+        for score in &pr.score {
+            let (scorer_id, _) = self.pr_account(&pr, &score.user);
             self.apply_to_periods(pr.included_at, scorer_id, |data| {
                 data.remove_reward_for_scoring();
             });
@@ -245,6 +416,113 @@ impl Contract {
         self.excluded_prs.insert(pr_id);
     }
 
+    // Sets a bounty for the issue. Zero score removes the bounty
+    pub fn sloth_bounty(
+        &mut self,
+        organization: String,
+        repo: String,
+        issue_number: u64,
+        user: GithubHandle,
+        score: u32,
+        scale: Option<ScoringScale>,
+    ) {
+        self.assert_sloth();
+        self.assert_repo_active(&organization, &repo);
+
+        let key = (organization.clone(), repo.clone(), issue_number);
+        if score == 0 {
+            self.bounties.remove(&key);
+            return;
+        }
+        require!(
+            scale.unwrap_or(self.scoring_scale).is_valid(score),
+            "Score is not on the repository's scale"
+        );
+
+        self.bounties.insert(
+            key,
+            VersionedBounty::V1(Bounty {
+                organization,
+                repo,
+                issue_number,
+                score,
+                created_by: user,
+                created_at: env::block_timestamp(),
+            }),
+        );
+    }
+
+    // Resets the preference to the repository or default locale if `locale` is None
+    pub fn sloth_locale(
+        &mut self,
+        user: GithubHandle,
+        locale: Option<String>,
+        github_id: Option<u64>,
+    ) {
+        self.assert_sloth();
+
+        let (user_id, _) = self.get_or_create_account_by_id(&user, github_id);
+        match locale {
+            Some(locale) => {
+                require!(locale.len() <= MAX_LOCALE_LENGTH, "Locale is too long");
+                self.user_locales.insert(user_id, locale);
+            }
+            None => {
+                self.user_locales.remove(&user_id);
+            }
+        }
+    }
+
+    // Links the account to the GitHub id and moves it to the new handle.
+    // Accounts created before the ids were tracked are found by the old handle
+    pub fn sloth_rename(
+        &mut self,
+        github_id: u64,
+        old_handle: GithubHandle,
+        new_handle: GithubHandle,
+    ) {
+        self.assert_sloth();
+
+        let user_id = match self
+            .github_ids
+            .get(&github_id)
+            .or(self.account_ids.get(&old_handle))
+        {
+            Some(user_id) => *user_id,
+            None => env::panic_str("User is not found"),
+        };
+        if let Some(linked) = self.user_github_ids.get(&user_id) {
+            require!(*linked == github_id, "User is linked to another GitHub id");
+        }
+        self.link_github_id(user_id, github_id);
+        self.rename_account(user_id, new_handle);
+    }
+
+    // Auto-inclusion is enabled by default for the users with an account
+    pub fn sloth_auto_include(
+        &mut self,
+        user: GithubHandle,
+        enabled: bool,
+        github_id: Option<u64>,
+    ) {
+        self.assert_sloth();
+
+        let (user_id, _) = self.get_or_create_account_by_id(&user, github_id);
+        if enabled {
+            self.auto_include_opt_outs.remove(&user_id);
+            self.auto_include_users.insert(user_id);
+        } else {
+            self.auto_include_opt_outs.insert(user_id);
+        }
+    }
+
+    // Applies only to the PRs included after the change
+    pub fn set_scoring_scale(&mut self, scale: ScoringScale) {
+        self.assert_sloth();
+
+        self.scoring_scale = scale;
+    }
+
     pub fn bulk_include_orgs(&mut self, allowed_orgs: Vec<AllowedRepos>) {
         self.assert_sloth();
 
@@ -289,6 +567,10 @@ impl Contract {
 
     pub fn include_repo(&mut self, organization: String, repo: String) {
         self.assert_sloth();
+        require!(
+            Provider::from_organization(&organization).is_some(),
+            "Unknown provider"
+        );
 
         self.repos.insert(
             (organization, repo),
@@ -343,23 +625,55 @@ impl Contract {
     pub fn sloth_stale(&mut self, pr_id: String) {
         self.assert_sloth();
 
-        let pr: PRv2 = match self.prs.get(&pr_id).cloned() {
-            Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
-        };
-        require!(pr.merged_at.is_none(), "Merged PR cannot be stale");
-        let (user_id, _) = self.get_or_create_account(&pr.author);
-        self.apply_to_periods(pr.included_at, user_id, |data| {
-            data.pr_closed(pr.score().unwrap_or_default())
-        });
+        let pr = self
+            .check_started_pr(&pr_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        Self::check_stalable(&pr).unwrap_or_else(|e| env::panic_str(e));
+        self.close_for_participants(&pr);
 
-        for score in pr.score {
-            let (scorer_id, _) = self.get_or_create_account(&score.user);
+        for score in &pr.score {
+            let (scorer_id, _) = self.pr_account(&pr, &score.user);
             self.apply_to_periods(pr.included_at, scorer_id, |data| {
                 data.remove_reward_for_scoring();
             });
         }
+        self.unindex_pr(&pr);
         self.prs.remove(&pr_id);
+        self.stale_prs
+            .insert(pr_id, (VersionedPR::V6(pr), env::block_timestamp()));
+    }
+
+    // Restores the stale PR with its votes, returning the credit taken by `sloth_stale`
+    pub fn sloth_unstale(&mut self, pr_id: String) {
+        self.assert_sloth();
+
+        let pr: PRv6 = match self.stale_prs.remove(&pr_id) {
+            Some((pr, _)) => pr.into(),
+            None => env::panic_str("PR is not stale"),
+        };
+        self.assert_repo_active(&pr.organization, &pr.repo);
+
+        let score = pr.score();
+        let participants = pr.participants_amount();
+        for participant in pr.participants() {
+            let (user_id, _) = self.pr_account(&pr, &participant);
+            let author = participant == pr.author;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_opened();
+                if let Some(score) = score {
+                    data.pr_scored(0, score, participants, author);
+                }
+            });
+        }
+
+        for score in &pr.score {
+            let (scorer_id, _) = self.pr_account(&pr, &score.user);
+            self.apply_to_periods(pr.included_at, scorer_id, |data| {
+                data.reward_for_scoring();
+            });
+        }
+        self.index_pr(&pr);
+        self.prs.insert(pr_id, VersionedPR::V6(pr));
     }
 
     pub fn sloth_finalize(
@@ -367,21 +681,16 @@ impl Contract {
         pr_id: String,
         active_pr: Option<(bool, GithubHandle)>,
         timestamp: Option<Timestamp>,
+        closed_issues: Option<Vec<u64>>,
     ) {
         self.assert_sloth();
 
         let timestamp = timestamp.unwrap_or_else(env::block_timestamp);
 
-        let mut pr: PRv2 = match self.prs.get(&pr_id).cloned() {
-            Some(pr) => pr.into(),
-            None => env::panic_str("PR is not started or already executed"),
-        };
-
-        if !pr.is_ready_to_move(timestamp) {
-            env::panic_str("PR is not ready to be finalized")
-        }
-
-        let (user_id, _) = self.get_or_create_account(&pr.author);
+        let mut pr = self
+            .check_started_pr(&pr_id)
+            .unwrap_or_else(|e| env::panic_str(e));
+        Self::check_finalizable(&pr, timestamp).unwrap_or_else(|e| env::panic_str(e));
 
         let autoscore = if pr.score().is_none() {
             let (is_active, autoscore_user) = active_pr.unwrap_or_default();
@@ -393,69 +702,291 @@ impl Contract {
             None
         };
 
-        self.apply_to_periods(pr.included_at, user_id, |data| {
-            if let Some(autoscore) = autoscore {
-                data.pr_scored(0, autoscore);
+        for issue_number in closed_issues.unwrap_or_default() {
+            let key = (pr.organization.clone(), pr.repo.clone(), issue_number);
+            if let Some(bounty) = self.bounties.remove(&key) {
+                let bounty: Bounty = bounty.into();
+                pr.bounty_rating += bounty.score * 10;
+                events::log_event(Event::BountyClaimed {
+                    issue_number,
+                    score: bounty.score,
+                });
             }
-            data.pr_executed()
-        });
+        }
 
-        let (user_id, mut user) = self.get_or_create_account(&pr.author);
+        let score = pr.score().unwrap_or_default();
+        let participants = pr.participants_amount();
+        let mut ratings = vec![];
+        for participant in pr.participants() {
+            let (user_id, before) = self.pr_account(&pr, &participant);
+            let is_author = participant == pr.author;
+            let base_rating = pr.shared_base_rating(is_author);
+
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                if let Some(autoscore) = autoscore {
+                    data.pr_scored(0, autoscore, participants, is_author);
+                }
+                data.pr_executed(score)
+            });
+            // Streak rewards the PR brought, they can be used by the PR itself right away
+            let earned: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+
+            // Streak events are logged only for the author, as the bot congratulates only them
+            let (used_bonuses, percentage_multiplier) = self.use_streak_bonuses(user_id, is_author);
+            let bonus_points = used_bonuses.iter().map(|bonus| bonus.reward).sum();
+            let rating = pr.participant_rating(is_author, bonus_points, percentage_multiplier);
+
+            if is_author {
+                pr.streak_bonus_rating = bonus_points;
+                pr.percentage_multiplier = percentage_multiplier;
+
+                let pr_number_this_week = self
+                    .sloths_per_period
+                    .get(&(user_id, TimePeriod::Week.time_string(timestamp)))
+                    .map(|s| {
+                        let s: UserPeriodDataV2 = s.clone().into();
+                        s.executed_prs
+                    })
+                    .unwrap_or_default();
+                events::log_event(Event::ExecutedWithRating {
+                    rating,
+                    applied_multiplier: percentage_multiplier,
+                    pr_number_this_week,
+                });
+            } else {
+                events::log_event(Event::CoauthorExecutedWithRating {
+                    github_handle: participant,
+                    rating,
+                    applied_multiplier: percentage_multiplier,
+                });
+            }
 
-        let mut bonus_points = 0;
-        for streak in self.streaks.iter().filter(|s| s.is_active()).cloned() {
-            let streak: Streak = streak.into();
-            let streak_data: StreakUserData = self
-                .user_streaks
-                .get(&(user_id, streak.id))
-                .cloned()
-                .unwrap_or_else(|| VersionedStreakUserData::V1(Default::default()))
-                .into();
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_bonus_rating(rating, base_rating)
+            });
+            ratings.push(ParticipantRating {
+                user_id,
+                rating,
+                used_bonuses,
+                earned_bonuses: earned.flat_bonuses_since(&before),
+                raised_bonuses: earned.percent_bonuses_since(&before),
+            });
+        }
+        pr.autoscored = autoscore.is_some();
+        pr.ratings = ratings;
 
-            let points = user.use_flat_bonus(streak.id, streak_data.amount);
-            bonus_points += points;
+        let full_id: String = pr.pr_id();
+        self.unindex_pr(&pr);
+        self.prs.remove(&full_id);
+        self.archive_queue.insert(Self::archive_key(&pr), ());
+        self.executed_prs.insert(full_id, VersionedPR::V6(pr));
+    }
 
-            if points > 0 {
-                events::log_event(Event::StreakFlatRewarded {
-                    streak_id: streak.id,
-                    streak_number: streak_data.amount,
-                    bonus_rating: points,
+    // Moves up to `limit` executed PRs merged before `before` to the event trail and returns how many were archived
+    pub fn sloth_archive(&mut self, before: Timestamp, limit: u32) -> u32 {
+        self.assert_sloth();
+        require!(
+            before <= env::block_timestamp(),
+            "Only the past PRs can be archived"
+        );
+
+        let keys: Vec<(Timestamp, PRId)> = self
+            .archive_queue
+            .iter()
+            .take_while(|((merged_at, _), _)| *merged_at < before)
+            .take(limit as usize)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &keys {
+            self.archive_queue.remove(key);
+            let (_, pr_id) = key;
+            let Some(pr) = self.executed_prs.remove(pr_id) else {
+                continue;
+            };
+            self.archived_prs.insert(pr_id.clone());
+            let pr: PRv6 = pr.into();
+            let score = pr.score().unwrap_or_default();
+            for participant in pr.participants() {
+                let (user_id, _) = self.pr_account(&pr, &participant);
+                // PRs finalized before the ratings were stored know only the author's bonuses
+                let rating = match pr.ratings.iter().find(|rating| rating.user_id == user_id) {
+                    Some(stored) => stored.rating,
+                    None if participant == pr.author => pr.rating(),
+                    None => pr.participant_rating(false, 0, 0),
+                };
+                let summary = self.archive_summaries.entry(user_id).or_default();
+                summary.executed_prs += 1;
+                summary.total_score += score;
+                summary.total_rating += rating;
+            }
+            events::log_event(Event::Archived { pr });
+        }
+
+        keys.len() as u32
+    }
+
+    #[private]
+    pub fn pause_contract(&mut self) {
+        self.paused = true;
+    }
+
+    #[private]
+    pub fn unpause_contract(&mut self) {
+        self.paused = false;
+    }
+
+    // Ranks are shown by the new thresholds right away, but only the ranks reached afterwards are announced
+    #[private]
+    pub fn set_rank_tiers(&mut self, tiers: Vec<RankTier>) {
+        require!(
+            tiers.iter().all(|tier| !tier.name.is_empty()),
+            "Rank name cannot be empty"
+        );
+        require!(
+            tiers.first().is_none_or(|tier| tier.min_bonus > 0),
+            "Users without the lifetime bonus cannot be ranked"
+        );
+        require!(
+            tiers
+                .windows(2)
+                .all(|pair| pair[0].min_bonus < pair[1].min_bonus),
+            "Rank tiers have to be sorted by the bonus"
+        );
+
+        self.rank_tiers = tiers;
+    }
+
+    #[private]
+    pub fn add_moderator(&mut self, account_id: AccountId) {
+        self.moderators.insert(account_id);
+    }
+
+    #[private]
+    pub fn remove_moderator(&mut self, account_id: AccountId) {
+        self.moderators.remove(&account_id);
+    }
+
+    // Takes back everything the finalized PR gave to its participants and scorers
+    pub fn sloth_revoke(&mut self, pr_id: String, reason: String) {
+        self.assert_moderator();
+        require!(!reason.is_empty(), "Reason is required");
+        require!(reason.len() <= MAX_REASON_LENGTH, "Reason is too long");
+
+        let pr: PRv6 = match self.executed_prs.remove(&pr_id) {
+            Some(pr) => pr.into(),
+            None => env::panic_str("PR is not finalized"),
+        };
+        self.archive_queue.remove(&Self::archive_key(&pr));
+
+        let score = pr.score().unwrap_or_default();
+        if pr.ratings.is_empty() {
+            // Finalized before the ratings were stored. Streak bonuses of co-authors are unknown,
+            // so only their multiplier is taken into account
+            for participant in pr.participants() {
+                let (user_id, account) = self.pr_account(&pr, &participant);
+                let rating = if participant == pr.author {
+                    pr.rating()
+                } else {
+                    pr.participant_rating(false, 0, account.lifetime_percentage_bonus())
+                };
+                self.apply_to_periods(pr.included_at, user_id, |data| {
+                    data.pr_revoked(score, rating)
                 });
             }
         }
+        for participant in &pr.ratings {
+            let user_id = participant.user_id;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_revoked(score, participant.rating)
+            });
 
-        let new_bonus = user.clear_new_flags();
-        if new_bonus > 0 {
-            events::log_event(Event::StreakLifetimeRewarded { reward: new_bonus })
+            let mut account: AccountWithPermanentPercentageBonus =
+                self.users[user_id].clone().into();
+            account.revert_bonuses(
+                &participant.used_bonuses,
+                &participant.earned_bonuses,
+                &participant.raised_bonuses,
+            );
+            self.users[user_id] = VersionedAccount::V1(account);
         }
 
-        let full_id: String = pr.pr_id();
-        pr.streak_bonus_rating = bonus_points;
-        pr.percentage_multiplier = user.lifetime_percentage_bonus();
+        // The autoscore is given by the bot, not by a scorer
+        let scorers = if pr.autoscored {
+            &[][..]
+        } else {
+            &pr.score[..]
+        };
+        for score in scorers {
+            let (scorer_id, _) = self.pr_account(&pr, &score.user);
+            self.apply_to_periods(pr.included_at, scorer_id, |data| {
+                data.remove_reward_for_scoring();
+            });
+        }
 
-        let total_rating = pr.rating();
-        let pr_number_this_week = self
-            .sloths_per_period
-            .get(&(user_id, TimePeriod::Week.time_string(timestamp)))
-            .map(|s| {
-                let s: UserPeriodDataV2 = s.clone().into();
-                s.executed_prs
-            })
-            .unwrap_or_default();
-        events::log_event(Event::ExecutedWithRating {
-            rating: total_rating,
-            applied_multiplier: pr.percentage_multiplier,
-            pr_number_this_week,
+        events::log_event(Event::Revoked {
+            pr_id: pr_id.clone(),
+            reason: reason.clone(),
         });
+        self.revoked_prs.insert(
+            pr_id,
+            (
+                VersionedPR::V6(pr),
+                Revocation {
+                    moderator: env::predecessor_account_id(),
+                    reason,
+                    revoked_at: env::block_timestamp(),
+                },
+            ),
+        );
+    }
 
-        self.apply_to_periods(pr.included_at, user_id, |data| {
-            data.pr_bonus_rating(total_rating, pr.score().unwrap_or_default() * 10)
-        });
+    // Corrects the period data of the user, e.g. after a bug, and records it in the log
+    pub fn sloth_adjust(
+        &mut self,
+        user: GithubHandle,
+        period: TimePeriodString,
+        delta: UserPeriodDataDelta,
+        reason: String,
+    ) {
+        self.assert_moderator();
+        require!(!delta.is_empty(), "Adjustment is empty");
+        require!(!reason.is_empty(), "Reason is required");
+        require!(reason.len() <= MAX_REASON_LENGTH, "Reason is too long");
+        require!(
+            TimePeriod::from_time_string(&period).is_some(),
+            "Unknown period"
+        );
 
-        self.users[user_id] = VersionedAccount::V1(user);
+        let user_id = match self.account_ids.get(&user) {
+            Some(user_id) => *user_id,
+            None => env::panic_str("User is not found"),
+        };
+        let entry = self
+            .sloths_per_period
+            .entry((user_id, period.clone()))
+            .or_insert(VersionedUserPeriodData::V1(Default::default()));
+        let data: UserPeriodDataV2 = entry.clone().into();
+        match delta.apply(&data) {
+            Some(data) => *entry = VersionedUserPeriodData::V2(data),
+            None => env::panic_str("Adjustment is out of range"),
+        }
+        self.calculate_streak(user_id);
 
-        self.prs.remove(&full_id);
-        self.executed_prs.insert(full_id, VersionedPR::V2(pr));
+        self.adjustments.push((
+            user_id,
+            Adjustment {
+                period,
+                delta,
+                reason,
+                moderator: env::predecessor_account_id(),
+                adjusted_at: env::block_timestamp(),
+            },
+        ));
+        self.user_adjustments
+            .entry(user_id)
+            .or_default()
+            .push(self.adjustments.len() - 1);
     }
 }
 
@@ -525,6 +1056,86 @@ impl Contract {
         }
     }
 
+    // Uses flat bonuses of the user and returns them with the lifetime percentage bonus
+    fn use_streak_bonuses(
+        &mut self,
+        user_id: UserId,
+        log_events: bool,
+    ) -> (Vec<FlatBonusStorage>, u32) {
+        let mut user: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+
+        let mut used = vec![];
+        for streak in self.streaks.iter().filter(|s| s.is_active()).cloned() {
+            let streak: Streak = streak.into();
+            let streak_data: StreakUserData = self
+                .user_streaks
+                .get(&(user_id, streak.id))
+                .cloned()
+                .unwrap_or_else(|| VersionedStreakUserData::V1(Default::default()))
+                .into();
+
+            let Some(bonus) = user.use_flat_bonus(streak.id, streak_data.amount) else {
+                continue;
+            };
+            if bonus.reward > 0 && log_events {
+                events::log_event(Event::StreakFlatRewarded {
+                    streak_id: streak.id,
+                    streak_number: streak_data.amount,
+                    bonus_rating: bonus.reward,
+                });
+            }
+            used.push(bonus);
+        }
+
+        let new_bonus = user.clear_new_flags();
+        if new_bonus > 0 && log_events {
+            events::log_event(Event::StreakLifetimeRewarded { reward: new_bonus })
+        }
+
+        let percentage_multiplier = user.lifetime_percentage_bonus();
+        self.users[user_id] = VersionedAccount::V1(user);
+        self.update_rank(user_id, percentage_multiplier, log_events);
+
+        (used, percentage_multiplier)
+    }
+
+    fn rank(&self, bonus: u32) -> Option<String> {
+        RankTier::find(&self.rank_tiers, bonus).map(|(_, tier)| tier.name.clone())
+    }
+
+    // Remembers the rank if the user reached a higher tier than the announced one
+    fn update_rank(&mut self, user_id: UserId, bonus: u32, log_events: bool) {
+        let Some((index, tier)) = RankTier::find(&self.rank_tiers, bonus) else {
+            return;
+        };
+        let rank = tier.name.clone();
+        let announced = self.user_ranks.get(&user_id).and_then(|announced| {
+            self.rank_tiers
+                .iter()
+                .position(|tier| &tier.name == announced)
+        });
+        if announced.is_some_and(|announced| announced >= index) {
+            return;
+        }
+
+        if log_events {
+            events::log_event(Event::RankUp { rank: rank.clone() });
+        }
+        self.user_ranks.insert(user_id, rank);
+    }
+
+    fn close_for_participants(&mut self, pr: &PRv6) {
+        let score = pr.score().unwrap_or_default();
+        let participants = pr.participants_amount();
+        for participant in pr.participants() {
+            let (user_id, _) = self.pr_account(pr, &participant);
+            let author = participant == pr.author;
+            self.apply_to_periods(pr.included_at, user_id, |data| {
+                data.pr_closed(score, participants, author)
+            });
+        }
+    }
+
     pub fn reward_streak(&mut self, user_id: UserId, streak: &Streak, achieved: u32) -> bool {
         let reward = match streak.get_streak_reward(achieved) {
             Some(reward) => reward,
@@ -586,20 +1197,202 @@ impl Contract {
         (user_id, self.users[user_id].clone().into())
     }
 
+    // Accounts of the PR's users are found by the ids stored on the PR, as their handles can be taken
+    // by other users after a rename. The users of the PRs stored before the ids are found by the handle
+    pub fn pr_account(
+        &mut self,
+        pr: &PRv6,
+        handle: &str,
+    ) -> (UserId, AccountWithPermanentPercentageBonus) {
+        match pr.user_id(handle) {
+            Some(user_id) => (user_id, self.users[user_id].clone().into()),
+            None => self.get_or_create_account(handle),
+        }
+    }
+
+    // Follows the renames of the users we know the GitHub id of
+    pub fn get_or_create_account_by_id(
+        &mut self,
+        account_id: &str,
+        github_id: Option<u64>,
+    ) -> (UserId, AccountWithPermanentPercentageBonus) {
+        let Some(github_id) = github_id else {
+            return self.get_or_create_account(account_id);
+        };
+
+        if let Some(user_id) = self.github_ids.get(&github_id).copied() {
+            self.rename_account(user_id, account_id.to_owned());
+            return (user_id, self.users[user_id].clone().into());
+        }
+
+        // The handle was released by another user, who is linked to a different id
+        if let Some(user_id) = self.account_ids.get(account_id) {
+            if self.user_github_ids.contains_key(user_id) {
+                self.account_ids.remove(account_id);
+            }
+        }
+
+        let (user_id, account) = self.get_or_create_account(account_id);
+        self.link_github_id(user_id, github_id);
+        (user_id, account)
+    }
+
+    fn link_github_id(&mut self, user_id: UserId, github_id: u64) {
+        self.github_ids.insert(github_id, user_id);
+        self.user_github_ids.insert(user_id, github_id);
+        self.linked_user_ids.insert(user_id, ());
+    }
+
+    // The old handle keeps pointing to the account, as the stored PRs and votes reference it
+    fn rename_account(&mut self, user_id: UserId, handle: GithubHandle) {
+        let mut account: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+        if account.github_handle == handle {
+            return;
+        }
+
+        self.account_ids.insert(handle.clone(), user_id);
+        account.github_handle = handle;
+        self.users[user_id] = VersionedAccount::V1(account);
+    }
+
+    // Executed PRs are merged, the time is missing only for the broken data
+    pub fn archive_key(pr: &PRv6) -> (Timestamp, PRId) {
+        (pr.merged_at.unwrap_or_default(), pr.pr_id())
+    }
+
+    // Every change of `prs` that adds, merges or removes a PR has to go through these
+    pub fn index_pr(&mut self, pr: &PRv6) {
+        match pr.ready_to_move_timestamp() {
+            Some(ready_at) => self.finalization_queue.insert((ready_at, pr.pr_id()), ()),
+            None => self.unmerged_pr_ids.insert(pr.pr_id(), ()),
+        };
+    }
+
+    pub fn unindex_pr(&mut self, pr: &PRv6) {
+        match pr.ready_to_move_timestamp() {
+            Some(ready_at) => self.finalization_queue.remove(&(ready_at, pr.pr_id())),
+            None => self.unmerged_pr_ids.remove(&pr.pr_id()),
+        };
+    }
+
+    // Handles of the PR's participants and scorers as they are now
+    pub fn with_current_handles(&self, mut pr: PRv6) -> PRv6 {
+        let current = |handle: &GithubHandle| -> GithubHandle {
+            pr.user_id(handle)
+                .or_else(|| self.account_ids.get(handle).copied())
+                .map(|user_id| {
+                    let account: AccountWithPermanentPercentageBonus =
+                        self.users[user_id].clone().into();
+                    account.github_handle
+                })
+                .unwrap_or_else(|| handle.clone())
+        };
+        let author = current(&pr.author);
+        let coauthors = pr.coauthors.iter().map(current).collect();
+        let scorers: Vec<GithubHandle> =
+            pr.score.iter().map(|score| current(&score.user)).collect();
+        pr.author = author;
+        pr.coauthors = coauthors;
+        for (score, scorer) in pr.score.iter_mut().zip(scorers) {
+            score.user = scorer;
+        }
+        pr
+    }
+
     pub fn assert_sloth(&self) {
+        if self.is_frozen() {
+            return self.assert_admin_while_paused();
+        }
         if env::predecessor_account_id() != self.sloth {
             env::panic_str("Only sloth can call this method")
         }
     }
 
+    pub fn assert_moderator(&self) {
+        if self.is_frozen() {
+            return self.assert_admin_while_paused();
+        }
+        if !self.moderators.contains(&env::predecessor_account_id()) {
+            env::panic_str("Only moderator can call this method")
+        }
+    }
+
+    // Paused by the admin or not fully migrated yet
+    pub fn is_frozen(&self) -> bool {
+        self.paused || !self.is_migrated()
+    }
+
+    fn assert_admin_while_paused(&self) {
+        if env::predecessor_account_id() != env::current_account_id() {
+            env::panic_str("Contract is paused")
+        }
+    }
+
     pub fn assert_repo_active(&self, organization: &str, repo: &str) {
+        if let Err(e) = self.check_repo_active(organization, repo) {
+            env::panic_str(e)
+        }
+    }
+
+    pub fn check_repo_active(&self, organization: &str, repo: &str) -> Result<(), &'static str> {
         let repo: Option<_> = self.repos.get(&(organization.to_owned(), repo.to_owned()));
-        if let Some(repo) = repo {
-            if !repo.is_active() {
-                env::panic_str("Repo is not active")
-            }
-        } else {
-            env::panic_str("Repo is not supported")
+        match repo {
+            Some(repo) if !repo.is_active() => Err("Repo is not active"),
+            Some(_) => Ok(()),
+            None => Err("Repo is not supported"),
+        }
+    }
+
+    // The checks below are shared by the methods and `sloth_batch`, so they reject the same actions
+    pub fn check_includable(
+        &self,
+        pr_id: &PRId,
+        override_exclude: bool,
+    ) -> Result<(), &'static str> {
+        if self.revoked_prs.contains_key(pr_id) {
+            return Err("Revoked PR cannot be included");
+        }
+        if self.archived_prs.contains(pr_id) {
+            return Err("Archived PR cannot be included");
+        }
+        if self.excluded_prs.contains(pr_id) && !override_exclude {
+            return Err("Excluded PR cannot be included without override flag");
+        }
+        if self.prs.contains_key(pr_id) || self.executed_prs.contains_key(pr_id) {
+            return Err("PR already exists");
+        }
+        Ok(())
+    }
+
+    pub fn check_started_pr(&self, pr_id: &PRId) -> Result<PRv6, &'static str> {
+        self.prs
+            .get(pr_id)
+            .cloned()
+            .map(Into::into)
+            .ok_or("PR is not started or already executed")
+    }
+
+    pub fn check_scorable(pr: &PRv6, user: &GithubHandle, score: u32) -> Result<(), &'static str> {
+        if pr.participants().contains(user) {
+            return Err("Participants cannot score their own PR");
+        }
+        if !pr.scale.is_valid(score) {
+            return Err("Score is not on the PR's scale");
+        }
+        Ok(())
+    }
+
+    pub fn check_stalable(pr: &PRv6) -> Result<(), &'static str> {
+        if pr.merged_at.is_some() {
+            return Err("Merged PR cannot be stale");
+        }
+        Ok(())
+    }
+
+    pub fn check_finalizable(pr: &PRv6, timestamp: Timestamp) -> Result<(), &'static str> {
+        if !pr.is_ready_to_move(timestamp) {
+            return Err("PR is not ready to be finalized");
         }
+        Ok(())
     }
 }
@@ -1,20 +1,397 @@
 use super::*;
 
+// Upgrades go in two parts:
+// - `migrate` reads the layout of the previous deployment and fills the new fields. It has to stay cheap,
+//   as it runs in the deployment transaction
+// - data changes are registered in `MIGRATIONS` and applied in chunks with `migrate_step`. The bot is
+//   rejected until all of them are applied, as it would see the data half-migrated
+//
+// `state_version` is the number of applied migrations, so new ones are only appended.
+// Fixtures of the older layouts are in `contract/fixtures` and are migrated in the tests. They are never
+// regenerated, a new layout gets a new fixture
+
+pub struct Migration {
+    pub name: &'static str,
+    // Processes up to `limit` items starting at the cursor and returns the next cursor, or None once done.
+    // Running it over the already migrated items has to be harmless
+    step: fn(&mut Contract, cursor: u32, limit: u32) -> Option<u32>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "upgrade_prs",
+        step: |contract, cursor, limit| upgrade_prs(&mut contract.prs, cursor, limit),
+    },
+    Migration {
+        name: "upgrade_executed_prs",
+        step: |contract, cursor, limit| upgrade_prs(&mut contract.executed_prs, cursor, limit),
+    },
+    Migration {
+        name: "index_prs",
+        step: Contract::index_prs,
+    },
+    Migration {
+        name: "store_user_ranks",
+        step: Contract::store_user_ranks,
+    },
+    Migration {
+        name: "register_authors_of_prs",
+        step: |contract, cursor, limit| contract.register_authors(false, cursor, limit),
+    },
+    Migration {
+        name: "register_authors_of_executed_prs",
+        step: |contract, cursor, limit| contract.register_authors(true, cursor, limit),
+    },
+    Migration {
+        name: "link_users_of_prs",
+        step: |contract, cursor, limit| contract.link_pr_users(false, cursor, limit),
+    },
+    Migration {
+        name: "link_users_of_executed_prs",
+        step: |contract, cursor, limit| contract.link_pr_users(true, cursor, limit),
+    },
+    Migration {
+        name: "index_executed_prs",
+        step: Contract::index_executed_prs,
+    },
+    Migration {
+        name: "index_adjustments",
+        step: Contract::index_adjustments,
+    },
+    Migration {
+        name: "index_linked_users",
+        step: Contract::index_linked_users,
+    },
+];
+
+// Layout of the contract before the migrations were versioned
+#[derive(BorshDeserialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldContract {
+    sloth: AccountId,
+    account_ids: LookupMap<GithubHandle, UserId>,
+    users: Vector<VersionedAccount>,
+    sloths_per_period: LookupMap<(UserId, TimePeriodString), VersionedUserPeriodData>,
+    prs: IterableMap<PRId, VersionedPR>,
+    executed_prs: IterableMap<PRId, VersionedPR>,
+    excluded_prs: LookupSet<PRId>,
+    streaks: Vector<VersionedStreak>,
+    user_streaks: LookupMap<(UserId, StreakId), VersionedStreakUserData>,
+    repos: IterableMap<(GithubHandle, GithubHandle), VersionedRepository>,
+}
+
+// Layout of the first deployment with `state_version`
+#[derive(BorshDeserialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldContractV1 {
+    sloth: AccountId,
+    account_ids: LookupMap<GithubHandle, UserId>,
+    users: Vector<VersionedAccount>,
+    sloths_per_period: LookupMap<(UserId, TimePeriodString), VersionedUserPeriodData>,
+    prs: IterableMap<PRId, VersionedPR>,
+    executed_prs: IterableMap<PRId, VersionedPR>,
+    excluded_prs: LookupSet<PRId>,
+    streaks: Vector<VersionedStreak>,
+    user_streaks: LookupMap<(UserId, StreakId), VersionedStreakUserData>,
+    repos: IterableMap<(GithubHandle, GithubHandle), VersionedRepository>,
+    bounties: IterableMap<(GithubHandle, GithubHandle, u64), VersionedBounty>,
+    scoring_scale: ScoringScale,
+    user_locales: LookupMap<UserId, String>,
+    auto_include_opt_outs: LookupSet<UserId>,
+    stale_prs: IterableMap<PRId, (VersionedPR, Timestamp)>,
+    moderators: LookupSet<AccountId>,
+    revoked_prs: IterableMap<PRId, (VersionedPR, Revocation)>,
+    adjustments: Vector<(UserId, Adjustment)>,
+    github_ids: LookupMap<u64, UserId>,
+    user_github_ids: LookupMap<UserId, u64>,
+    archive_summaries: LookupMap<UserId, ArchiveSummary>,
+    unmerged_pr_ids: TreeMap<PRId, ()>,
+    finalization_queue: TreeMap<(Timestamp, PRId), ()>,
+    paused: bool,
+    state_version: u32,
+    migration_cursor: u32,
+}
+
+// The data of the new fields is filled by all of the `MIGRATIONS`
+impl From<OldContract> for OldContractV1 {
+    fn from(state: OldContract) -> Self {
+        Self {
+            sloth: state.sloth,
+            account_ids: state.account_ids,
+            users: state.users,
+            sloths_per_period: state.sloths_per_period,
+            prs: state.prs,
+            executed_prs: state.executed_prs,
+            excluded_prs: state.excluded_prs,
+            streaks: state.streaks,
+            user_streaks: state.user_streaks,
+            repos: state.repos,
+            bounties: IterableMap::new(storage::StorageKey::Bounties),
+            scoring_scale: ScoringScale::default(),
+            user_locales: LookupMap::new(storage::StorageKey::UserLocales),
+            auto_include_opt_outs: LookupSet::new(storage::StorageKey::AutoIncludeOptOuts),
+            stale_prs: IterableMap::new(storage::StorageKey::StalePRs),
+            moderators: LookupSet::new(storage::StorageKey::Moderators),
+            revoked_prs: IterableMap::new(storage::StorageKey::RevokedPRs),
+            adjustments: Vector::new(storage::StorageKey::Adjustments),
+            github_ids: LookupMap::new(storage::StorageKey::GithubIds),
+            user_github_ids: LookupMap::new(storage::StorageKey::UserGithubIds),
+            archive_summaries: LookupMap::new(storage::StorageKey::ArchiveSummaries),
+            unmerged_pr_ids: TreeMap::new(storage::StorageKey::UnmergedPRIds),
+            finalization_queue: TreeMap::new(storage::StorageKey::FinalizationQueue),
+            paused: false,
+            state_version: 0,
+            migration_cursor: 0,
+        }
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     #[init(ignore_state)]
     #[private]
-    pub fn migrate(list: Vec<String>) -> Self {
-        let mut state: Self = env::state_read().unwrap();
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE")
+            .unwrap_or_else(|| env::panic_str("Contract is not initialized"));
+        // Borsh has to read all of the bytes, so only the matching layout is read successfully
+        let state: OldContractV1 = match near_sdk::borsh::from_slice::<OldContractV1>(&state) {
+            Ok(state) => state,
+            Err(_) => match near_sdk::borsh::from_slice::<OldContract>(&state) {
+                Ok(state) => state.into(),
+                Err(_) => env::panic_str("Unknown layout of the state"),
+            },
+        };
+
+        Self {
+            sloth: state.sloth,
+            account_ids: state.account_ids,
+            users: state.users,
+            sloths_per_period: state.sloths_per_period,
+            prs: state.prs,
+            executed_prs: state.executed_prs,
+            excluded_prs: state.excluded_prs,
+            streaks: state.streaks,
+            user_streaks: state.user_streaks,
+            repos: state.repos,
+            bounties: state.bounties,
+            scoring_scale: state.scoring_scale,
+            user_locales: state.user_locales,
+            auto_include_opt_outs: state.auto_include_opt_outs,
+            stale_prs: state.stale_prs,
+            moderators: state.moderators,
+            revoked_prs: state.revoked_prs,
+            adjustments: state.adjustments,
+            github_ids: state.github_ids,
+            user_github_ids: state.user_github_ids,
+            archive_summaries: state.archive_summaries,
+            unmerged_pr_ids: state.unmerged_pr_ids,
+            finalization_queue: state.finalization_queue,
+            paused: state.paused,
+            state_version: state.state_version,
+            migration_cursor: state.migration_cursor,
+            rank_tiers: RankTier::default_tiers(),
+            user_ranks: LookupMap::new(storage::StorageKey::UserRanks),
+            auto_include_users: LookupSet::new(storage::StorageKey::AutoIncludeUsers),
+            archive_queue: TreeMap::new(storage::StorageKey::ArchiveQueue),
+            archived_prs: LookupSet::new(storage::StorageKey::ArchivedPRs),
+            user_adjustments: LookupMap::new(storage::StorageKey::UserAdjustments),
+            linked_user_ids: TreeMap::new(storage::StorageKey::LinkedUserIds),
+        }
+    }
+
+    /// Applies the next chunk of the pending migrations and returns whether all of them are applied
+    #[private]
+    pub fn migrate_step(&mut self, limit: u32) -> bool {
+        require!(limit > 0, "Limit has to be positive");
+        let Some(migration) = MIGRATIONS.get(self.state_version as usize) else {
+            return true;
+        };
+
+        match (migration.step)(self, self.migration_cursor, limit) {
+            Some(cursor) => self.migration_cursor = cursor,
+            None => {
+                env::log_str(&format!("Migration {} is applied", migration.name));
+                self.state_version += 1;
+                self.migration_cursor = 0;
+            }
+        }
+        self.is_migrated()
+    }
+
+    /// Returns the names of the migrations that are not applied yet
+    pub fn pending_migrations(&self) -> Vec<String> {
+        MIGRATIONS
+            .iter()
+            .skip(self.state_version as usize)
+            .map(|migration| migration.name.to_string())
+            .collect()
+    }
+}
 
-        for repo in list {
-            let mut split = repo.split('/');
-            let owner = split.next().unwrap();
-            let name = split.next().unwrap();
+impl Contract {
+    pub fn is_migrated(&self) -> bool {
+        self.state_version as usize >= MIGRATIONS.len()
+    }
 
-            state.repos.remove(&(owner.to_string(), name.to_string()));
+    // Rebuilds the indexes of `prs` that were added after the PRs were stored
+    fn index_prs(&mut self, cursor: u32, limit: u32) -> Option<u32> {
+        let prs: Vec<PRv6> = self
+            .prs
+            .values()
+            .skip(cursor as usize)
+            .take(limit as usize)
+            .cloned()
+            .map(Into::into)
+            .collect();
+        for pr in &prs {
+            self.index_pr(pr);
         }
 
-        state
+        let next = cursor + limit;
+        (next < self.prs.len()).then_some(next)
     }
+
+    // Queues the executed PRs for the archival by the merge time
+    fn index_executed_prs(&mut self, cursor: u32, limit: u32) -> Option<u32> {
+        let keys: Vec<(Timestamp, PRId)> = self
+            .executed_prs
+            .values()
+            .skip(cursor as usize)
+            .take(limit as usize)
+            .map(|pr| Self::archive_key(&pr.clone().into()))
+            .collect();
+        for key in keys {
+            self.archive_queue.insert(key, ());
+        }
+
+        let next = cursor + limit;
+        (next < self.executed_prs.len()).then_some(next)
+    }
+
+    // Groups the logged adjustments by the user
+    fn index_adjustments(&mut self, cursor: u32, limit: u32) -> Option<u32> {
+        let end = cursor.saturating_add(limit).min(self.adjustments.len());
+        for index in cursor..end {
+            let user_id = self.adjustments[index].0;
+            let indices = self.user_adjustments.entry(user_id).or_default();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+
+        (end < self.adjustments.len()).then_some(end)
+    }
+
+    // Collects the users that were linked to a GitHub id before they were indexed
+    fn index_linked_users(&mut self, cursor: u32, limit: u32) -> Option<u32> {
+        let end = cursor.saturating_add(limit).min(self.users.len());
+        for user_id in cursor..end {
+            if self.user_github_ids.contains_key(&user_id) {
+                self.linked_user_ids.insert(user_id, ());
+            }
+        }
+
+        (end < self.users.len()).then_some(end)
+    }
+
+    // Remembers the ranks the users already have, so they aren't announced on the next finalize.
+    // The bonuses that are not congratulated yet are left out to be announced together
+    fn store_user_ranks(&mut self, cursor: u32, limit: u32) -> Option<u32> {
+        let end = cursor.saturating_add(limit).min(self.users.len());
+        for user_id in cursor..end {
+            let account: AccountWithPermanentPercentageBonus = self.users[user_id].clone().into();
+            let announced_bonus = account
+                .permanent_percentage_bonus
+                .iter()
+                .filter(|bonus| !bonus.new)
+                .map(|bonus| bonus.percent)
+                .sum();
+            if let Some(rank) = self.rank(announced_bonus) {
+                self.user_ranks.insert(user_id, rank);
+            }
+        }
+
+        (end < self.users.len()).then_some(end)
+    }
+
+    // Allows the auto-include for the users that included their PRs before it was required
+    fn register_authors(&mut self, executed: bool, cursor: u32, limit: u32) -> Option<u32> {
+        let prs = if executed {
+            &self.executed_prs
+        } else {
+            &self.prs
+        };
+        let total = prs.len();
+        let authors: Vec<GithubHandle> = prs
+            .values()
+            .skip(cursor as usize)
+            .take(limit as usize)
+            .map(|pr| PRv6::from(pr.clone()).author)
+            .collect();
+        for author in authors {
+            if let Some(user_id) = self.account_ids.get(&author).copied() {
+                self.auto_include_users.insert(user_id);
+            }
+        }
+
+        let next = cursor + limit;
+        (next < total).then_some(next)
+    }
+
+    // Stores the accounts of the PR's users while their handles still point to them
+    fn link_pr_users(&mut self, executed: bool, cursor: u32, limit: u32) -> Option<u32> {
+        let prs = if executed {
+            &self.executed_prs
+        } else {
+            &self.prs
+        };
+        let total = prs.len();
+        let mut linked: Vec<(PRId, PRv6)> = prs
+            .iter()
+            .skip(cursor as usize)
+            .take(limit as usize)
+            .map(|(id, pr)| (id.clone(), pr.clone().into()))
+            .collect();
+        for (_, pr) in linked.iter_mut() {
+            let mut handles = pr.participants();
+            handles.extend(pr.score.iter().map(|score| score.user.clone()));
+            for handle in handles {
+                if pr.user_id(&handle).is_some() {
+                    continue;
+                }
+                if let Some(user_id) = self.account_ids.get(&handle).copied() {
+                    pr.set_user_id(&handle, user_id);
+                }
+            }
+        }
+
+        let prs = if executed {
+            &mut self.executed_prs
+        } else {
+            &mut self.prs
+        };
+        for (id, pr) in linked {
+            prs.insert(id, VersionedPR::V6(pr));
+        }
+
+        let next = cursor + limit;
+        (next < total).then_some(next)
+    }
+}
+
+// Rewrites the PRs stored in the older versions, otherwise they are upgraded only when changed
+fn upgrade_prs(prs: &mut IterableMap<PRId, VersionedPR>, cursor: u32, limit: u32) -> Option<u32> {
+    let outdated: Vec<(PRId, PRv6)> = prs
+        .iter()
+        .skip(cursor as usize)
+        .take(limit as usize)
+        .filter(|(_, pr)| !matches!(pr, VersionedPR::V6(_)))
+        .map(|(id, pr)| (id.clone(), pr.clone().into()))
+        .collect();
+    for (id, pr) in outdated {
+        prs.insert(id, VersionedPR::V6(pr));
+    }
+
+    let next = cursor + limit;
+    (next < prs.len()).then_some(next)
 }
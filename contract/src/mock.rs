@@ -51,23 +51,25 @@ impl Contract {
         pr_id: &str,
         timestamp: Timestamp,
     ) {
-        let pr = PRv2::new(
+        let pr = PRv6::new(
             "race-of-sloths".to_owned(),
             "mock".to_owned(),
             pr_number,
             name.to_string(),
             timestamp,
             timestamp,
+            ScoringScale::default(),
         );
         // Simulate PR opening
-        self.prs.insert(pr_id.to_string(), VersionedPR::V2(pr));
+        self.index_pr(&pr);
+        self.prs.insert(pr_id.to_string(), VersionedPR::V6(pr));
         self.apply_to_periods(timestamp, user_id, |data: &mut VersionedUserPeriodData| {
             data.pr_opened()
         });
 
         // Simulate scoring
         let score = 10; // Example fixed score
-        self.sloth_scored(pr_id.to_string(), "reviewer".to_string(), score);
+        self.sloth_scored(pr_id.to_string(), "reviewer".to_string(), score, None);
 
         // Simulate merging
         let merged_at = timestamp + 1000000000; // Example: Merge 1,000 seconds later
@@ -77,6 +79,7 @@ impl Contract {
             pr_id.to_string(),
             None,
             Some(merged_at + SCORE_TIMEOUT_IN_NANOSECONDS + 1),
+            None,
         );
     }
 }
@@ -18,4 +18,22 @@ pub enum StorageKey {
     _RESERVED3,
     MergedPRs,
     PRs,
+    Bounties,
+    UserLocales,
+    AutoIncludeOptOuts,
+    StalePRs,
+    Moderators,
+    RevokedPRs,
+    Adjustments,
+    GithubIds,
+    UserGithubIds,
+    ArchiveSummaries,
+    UnmergedPRIds,
+    FinalizationQueue,
+    UserRanks,
+    AutoIncludeUsers,
+    ArchiveQueue,
+    ArchivedPRs,
+    UserAdjustments,
+    LinkedUserIds,
 }
@@ -1,5 +1,5 @@
 use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, VMContext};
-use shared::{PRv2, SCORE_TIMEOUT_IN_NANOSECONDS};
+use shared::{PRv6, SlothAction, SlothActionResult, SCORE_TIMEOUT_IN_NANOSECONDS};
 
 use super::*;
 
@@ -56,12 +56,19 @@ impl ContractExt {
             pr_id,
             started_at,
             true,
+            None,
+            None,
         );
     }
 
     pub fn score(&mut self, pr_id: u64, id: u8, score: u32) {
         self.contract
-            .sloth_scored(pr_id_str(pr_id), github_handle(id), score);
+            .sloth_scored(pr_id_str(pr_id), github_handle(id), score, None);
+    }
+
+    pub fn unscore(&mut self, pr_id: u64, id: u8) {
+        self.contract
+            .sloth_unscored(pr_id_str(pr_id), github_handle(id));
     }
 
     pub fn merge(&mut self, pr_id: u64, merged_at: u64) {
@@ -76,8 +83,48 @@ impl ContractExt {
         self.contract.sloth_stale(pr_id_str(pr_id));
     }
 
+    pub fn unstale(&mut self, pr_id: u64) {
+        self.contract.sloth_unstale(pr_id_str(pr_id));
+    }
+
+    pub fn revoke(&mut self, pr_id: u64, reason: &str) {
+        self.contract
+            .sloth_revoke(pr_id_str(pr_id), reason.to_string());
+    }
+
+    pub fn add_moderator(&mut self, account_id: AccountId) {
+        let predecessor = self.context.predecessor_account_id.clone();
+        self.context.predecessor_account_id = self.context.current_account_id.clone();
+        testing_env!(self.context.clone());
+        self.contract.add_moderator(account_id);
+        self.context.predecessor_account_id = predecessor;
+        testing_env!(self.context.clone());
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let predecessor = self.context.predecessor_account_id.clone();
+        self.context.predecessor_account_id = self.context.current_account_id.clone();
+        testing_env!(self.context.clone());
+        if paused {
+            self.contract.pause_contract();
+        } else {
+            self.contract.unpause_contract();
+        }
+        self.context.predecessor_account_id = predecessor;
+        testing_env!(self.context.clone());
+    }
+
     pub fn finalize(&mut self, pr_id: u64) {
-        self.contract.sloth_finalize(pr_id_str(pr_id), None, None)
+        self.contract
+            .sloth_finalize(pr_id_str(pr_id), None, None, None)
+    }
+
+    pub fn coauthors(&mut self, pr_id: u64, ids: &[u8]) {
+        self.contract.sloth_coauthors(
+            pr_id_str(pr_id),
+            ids.iter().map(|id| github_handle(*id)).collect(),
+            None,
+        );
     }
 }
 
@@ -86,25 +133,25 @@ fn success_streak_flow() {
     let mut contract = ContractExt::new();
 
     contract.include_sloth_common_repo(0, 0, 0);
-    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 1);
+    assert_eq!(contract.contract.unmerged_prs(None, 50).len(), 1);
 
-    contract.score(0, 0, 13);
+    contract.score(0, 2, 13);
 
     contract.merge(0, 10);
-    assert_eq!(contract.contract.unmerged_prs(0, 50).len(), 0);
+    assert_eq!(contract.contract.unmerged_prs(None, 50).len(), 0);
     contract.score(0, 1, 8);
 
     contract.context.block_timestamp = 11;
     testing_env!(contract.context.clone());
-    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 0);
+    assert_eq!(contract.contract.unfinalized_prs(None, 50).len(), 0);
 
     contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
     testing_env!(contract.context.clone());
-    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 1);
+    assert_eq!(contract.contract.unfinalized_prs(None, 50).len(), 1);
 
     contract.finalize(0);
 
-    assert_eq!(contract.contract.unfinalized_prs(0, 50).len(), 0);
+    assert_eq!(contract.contract.unfinalized_prs(None, 50).len(), 0);
     let user = contract
         .contract
         .user(&github_handle(0), vec!["all-time".to_string()])
@@ -125,7 +172,7 @@ fn success_streak_flow() {
         .get(&pr_id_str(0))
         .unwrap()
         .clone();
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.score(), Some(10));
     assert_eq!(pr.rating(), 100 + 10 + 10);
@@ -134,7 +181,8 @@ fn success_streak_flow() {
     // New pr with 10 points for monthly streak
 
     contract.include_sloth_common_repo(0, 1, 0);
-    contract.score(1, 1, 10);
+    contract.score(1, 2, 13);
+    contract.score(1, 1, 8);
     contract.merge(1, 1);
     contract.finalize(1);
 
@@ -144,7 +192,7 @@ fn success_streak_flow() {
         .get(&pr_id_str(1))
         .unwrap()
         .clone();
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.score(), Some(10));
     assert_eq!(pr.rating(), 100);
@@ -163,6 +211,8 @@ fn success_streak_flow() {
 #[test]
 fn streak_calculation() {
     let mut contract = ContractExt::new();
+    // Votes of 10 points are on the linear scale
+    contract.contract.set_scoring_scale(ScoringScale::Linear);
 
     let mut current_time = 0;
     for i in 0..12 {
@@ -195,6 +245,8 @@ fn streak_is_not_lost_by_stale() {
     let (mut contract, time) = {
         //Setup
         let mut contract = ContractExt::new();
+        // Votes of 10 points are on the linear scale
+        contract.contract.set_scoring_scale(ScoringScale::Linear);
 
         let mut current_time = 0;
         for i in 0..12 {
@@ -307,6 +359,8 @@ fn user_had_a_streak_then_lost_then_again_get_it() {
 #[test]
 fn streak_crashed_in_middle() {
     let mut contract = ContractExt::new();
+    // Votes of 10 points are on the linear scale
+    contract.contract.set_scoring_scale(ScoringScale::Linear);
 
     let mut current_time = 0;
     for i in 0..8 {
@@ -383,6 +437,44 @@ fn notallowlisted_org() {
     contract.score(0, 1, 13);
 }
 
+#[test]
+fn gitlab_prs_are_namespaced() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .include_repo("gitlab:NEAR-DevHub".to_string(), "devbot".to_string());
+    contract.include_sloth_with_org("gitlab:NEAR-DevHub", 0, 0, 0);
+    contract.include_sloth_common_repo(0, 0, 0);
+
+    let gitlab_id = "gitlab:NEAR-DevHub/devbot/0".to_string();
+    assert!(contract.contract.prs.get(&gitlab_id).is_some());
+    assert!(contract.contract.prs.get(&pr_id_str(0)).is_some());
+
+    contract
+        .contract
+        .sloth_scored(gitlab_id.clone(), github_handle(1), 5, None);
+    let pr: PRv6 = contract
+        .contract
+        .prs
+        .get(&gitlab_id)
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.organization, "gitlab:NEAR-DevHub");
+    assert_eq!(pr.score(), Some(5));
+}
+
+#[test]
+#[should_panic(expected = "Unknown provider")]
+fn unknown_provider_repo() {
+    let mut contract = ContractExt::new();
+
+    contract
+        .contract
+        .include_repo("bitbucket:NEAR-DevHub".to_string(), "devbot".to_string());
+}
+
 #[test]
 #[should_panic(expected = "PR is not started or already executed")]
 fn not_started_pr() {
@@ -415,7 +507,7 @@ fn cannot_double_streak_reward() {
         .get(&pr_id_str(2))
         .unwrap()
         .clone();
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.streak_bonus_rating, 10);
     assert_eq!(pr.rating(), 50 + 10);
@@ -466,7 +558,7 @@ fn cannot_double_percent_reward() {
         .unwrap()
         .clone();
 
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.rating(), 53);
 
@@ -501,7 +593,7 @@ fn monthly_streak_awarded_imediatly() {
         .unwrap()
         .clone();
 
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.streak_bonus_rating, 10 + 10);
 }
@@ -524,7 +616,7 @@ fn non_scored_pr_gets_autoscored() {
         .unwrap()
         .clone();
 
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
 
     assert_eq!(pr.score(), Some(1));
 }
@@ -540,7 +632,7 @@ fn active_non_scored_pr_gets_autoscored() {
     testing_env!(contract.context.clone());
     contract
         .contract
-        .sloth_finalize(pr_id_str(0), Some((true, "admin".to_string())), None);
+        .sloth_finalize(pr_id_str(0), Some((true, "admin".to_string())), None, None);
 
     let pr = contract
         .contract
@@ -549,7 +641,1302 @@ fn active_non_scored_pr_gets_autoscored() {
         .unwrap()
         .clone();
 
-    let pr: PRv2 = pr.into();
+    let pr: PRv6 = pr.into();
+
+    assert_eq!(pr.score(), Some(2));
+}
+
+#[test]
+fn coauthors_split_rating() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.coauthors(0, &[1]);
+    contract.score(0, 3, 8);
+
+    for id in [0, 1] {
+        let user = contract
+            .contract
+            .user(&github_handle(id), vec!["all-time".to_string()])
+            .unwrap();
+        assert_eq!(user.period_data[0].1.prs_opened, 1);
+        assert_eq!(user.period_data[0].1.total_score, 8);
+        assert_eq!(user.period_data[0].1.total_rating, 40);
+    }
+
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let pr: PRv6 = contract
+        .contract
+        .executed_prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.coauthors, vec![github_handle(1)]);
+    // Half of the base rating and both weekly and monthly streak bonuses
+    assert_eq!(pr.rating(), 40 + 10 + 10);
+
+    for id in [0, 1] {
+        let user = contract
+            .contract
+            .user(&github_handle(id), vec!["all-time".to_string()])
+            .unwrap();
+        assert_eq!(user.period_data[0].1.prs_merged, 1);
+        assert_eq!(user.period_data[0].1.executed_prs, 1);
+        assert_eq!(user.period_data[0].1.total_rating, 40 + 10 + 10);
+        assert_eq!(user.streaks[0].1.amount, 1);
+        assert_eq!(user.streaks[1].1.amount, 1);
+    }
+}
+
+#[test]
+fn coauthors_can_be_changed_before_merge() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 3, 8);
+    contract.coauthors(0, &[1, 2]);
+
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.prs_opened, 1);
+    // Co-authors receive 26 each and the author gets the remainder
+    assert_eq!(author.period_data[0].1.total_rating, 28);
+    let coauthor = contract
+        .contract
+        .user(&github_handle(2), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(coauthor.period_data[0].1.total_rating, 26);
+
+    contract.coauthors(0, &[0, 1, 1]);
+
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_rating, 40);
+
+    let removed = contract
+        .contract
+        .user(&github_handle(2), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(removed.period_data[0].1.prs_opened, 0);
+    assert_eq!(removed.period_data[0].1.total_score, 0);
+    assert_eq!(removed.period_data[0].1.total_rating, 0);
+
+    contract.stale(0);
+
+    let coauthor = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(coauthor.period_data[0].1.prs_opened, 0);
+    assert_eq!(coauthor.period_data[0].1.total_rating, 0);
+}
+
+#[test]
+#[should_panic(expected = "Co-authors cannot be changed for merged PR")]
+fn coauthors_cannot_be_changed_after_merge() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+    contract.coauthors(0, &[1]);
+}
+
+#[test]
+#[should_panic(expected = "Participants cannot score their own PR")]
+fn coauthor_cannot_score() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.coauthors(0, &[1]);
+    contract.score(0, 1, 13);
+}
+
+#[test]
+#[should_panic(expected = "Scorers cannot be co-authors")]
+fn scorer_cannot_become_coauthor() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 13);
+    contract.coauthors(0, &[1]);
+}
+
+#[test]
+fn unscore_reverts_vote_and_reward() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.score(0, 2, 2);
+    contract.unscore(0, 1);
 
+    let pr: PRv6 = contract
+        .contract
+        .prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
     assert_eq!(pr.score(), Some(2));
+
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_score, 2);
+    assert_eq!(author.period_data[0].1.total_rating, 20);
+
+    let scorer = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(scorer.period_data[0].1.prs_scored, 0);
+    assert_eq!(scorer.period_data[0].1.total_rating, 0);
+
+    contract.unscore(0, 2);
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_score, 0);
+    assert_eq!(author.period_data[0].1.total_rating, 0);
+
+    // The vote can be given again after retraction
+    contract.score(0, 1, 5);
+    let scorer = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(scorer.period_data[0].1.prs_scored, 1);
+    assert_eq!(scorer.period_data[0].1.total_rating, 25);
+
+    // The retracted votes don't count as the largest score
+    contract.merge(0, 0);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 1;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.largest_score, 5);
+}
+
+#[test]
+#[should_panic(expected = "User has not scored the PR")]
+fn unscore_without_vote() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.unscore(0, 2);
+}
+
+#[test]
+fn bounty_is_claimed_on_finalize() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_bounty(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        5,
+        github_handle(3),
+        13,
+        None,
+    );
+    contract.contract.sloth_bounty(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        6,
+        github_handle(3),
+        3,
+        None,
+    );
+    assert_eq!(contract.contract.bounties(0, 50).len(), 2);
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 5);
+    contract.merge(0, 10);
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract
+        .contract
+        .sloth_finalize(pr_id_str(0), None, None, Some(vec![5, 7]));
+
+    let pr: PRv6 = contract
+        .contract
+        .executed_prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.bounty_rating, 130);
+    assert_eq!(pr.rating(), 50 + 130 + 10);
+
+    let bounties = contract.contract.bounties(0, 50);
+    assert_eq!(bounties.len(), 1);
+    assert_eq!(bounties[0].issue_number, 6);
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.total_rating, 50 + 130 + 10);
+}
+
+#[test]
+fn bounty_can_be_cancelled() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_bounty(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        5,
+        github_handle(3),
+        13,
+        None,
+    );
+    contract.contract.sloth_bounty(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        5,
+        github_handle(3),
+        0,
+        Some(ScoringScale::Linear),
+    );
+
+    assert!(contract.contract.bounties(0, 50).is_empty());
+}
+
+#[test]
+fn scoring_scale_is_stored_on_pr() {
+    let mut contract = ContractExt::new();
+
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert_eq!(info.scale, ScoringScale::Fibonacci);
+
+    contract.contract.set_scoring_scale(ScoringScale::Linear);
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_include(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        github_handle(0),
+        1,
+        0,
+        true,
+        Some(ScoringScale::TShirt),
+        None,
+    );
+
+    let pr: PRv6 = contract
+        .contract
+        .prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    assert_eq!(pr.scale, ScoringScale::Linear);
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 1);
+    assert_eq!(info.scale, ScoringScale::TShirt);
+
+    // Changing the default doesn't affect the included PRs
+    contract.contract.set_scoring_scale(ScoringScale::Fibonacci);
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert_eq!(info.scale, ScoringScale::Linear);
+}
+
+#[test]
+#[should_panic(expected = "Score is not on the PR's scale")]
+fn score_has_to_be_on_pr_scale() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.set_scoring_scale(ScoringScale::Linear);
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 13);
+}
+
+#[test]
+#[should_panic(expected = "Score is not on the repository's scale")]
+fn bounty_has_to_be_on_repo_scale() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_bounty(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        5,
+        github_handle(3),
+        3,
+        Some(ScoringScale::TShirt),
+    );
+}
+
+#[test]
+fn user_locale_preference() {
+    let mut contract = ContractExt::new();
+
+    assert_eq!(contract.contract.user_locale(github_handle(0)), None);
+
+    contract
+        .contract
+        .sloth_locale(github_handle(0), Some("es".to_string()), None);
+    assert_eq!(
+        contract.contract.user_locale(github_handle(0)),
+        Some("es".to_string())
+    );
+    assert_eq!(contract.contract.user_locale(github_handle(1)), None);
+
+    contract.contract.sloth_locale(github_handle(0), None, None);
+    assert_eq!(contract.contract.user_locale(github_handle(0)), None);
+}
+
+#[test]
+#[should_panic(expected = "Locale is too long")]
+fn user_locale_too_long() {
+    let mut contract = ContractExt::new();
+
+    contract.contract.sloth_locale(
+        github_handle(0),
+        Some("a".repeat(MAX_LOCALE_LENGTH + 1)),
+        None,
+    );
+}
+
+#[test]
+fn user_auto_include_opt_out() {
+    let mut contract = ContractExt::new();
+
+    assert!(!contract.contract.user_auto_include(github_handle(0)));
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    assert!(contract.contract.user_auto_include(github_handle(0)));
+    // Scorers and co-authors didn't join the race themselves
+    contract.score(0, 1, 5);
+    contract.coauthors(0, &[2]);
+    assert!(!contract.contract.user_auto_include(github_handle(1)));
+    assert!(!contract.contract.user_auto_include(github_handle(2)));
+
+    contract
+        .contract
+        .sloth_auto_include(github_handle(0), false, None);
+    assert!(!contract.contract.user_auto_include(github_handle(0)));
+
+    contract
+        .contract
+        .sloth_auto_include(github_handle(0), true, None);
+    assert!(contract.contract.user_auto_include(github_handle(0)));
+}
+
+#[test]
+fn stale_pr_is_restored_with_credit() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    let before = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+
+    contract.stale(0);
+    let stale = contract.contract.stale_prs(0, 10);
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].0.number, 0);
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.prs_opened, 0);
+    assert_eq!(author.period_data[0].1.total_score, 0);
+
+    contract.unstale(0);
+    assert!(contract.contract.stale_prs(0, 10).is_empty());
+    assert!(
+        contract
+            .contract
+            .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0)
+            .exist
+    );
+
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data, before.period_data);
+    let scorer = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(scorer.period_data[0].1.prs_scored, 1);
+}
+
+#[test]
+#[should_panic(expected = "PR is not stale")]
+fn only_stale_pr_can_be_restored() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.stale(0);
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.unstale(0);
+}
+
+#[test]
+fn revoke_takes_back_finalized_pr() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    contract.revoke(0, "Scoring ring");
+
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    let data = &author.period_data[0].1;
+    assert_eq!(data.prs_opened, 0);
+    assert_eq!(data.prs_merged, 0);
+    assert_eq!(data.executed_prs, 0);
+    assert_eq!(data.total_score, 0);
+    assert_eq!(data.total_rating, 0);
+    let scorer = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(scorer.period_data[0].1.prs_scored, 0);
+    assert_eq!(scorer.period_data[0].1.total_rating, 0);
+
+    let revoked = contract.contract.revoked_prs(0, 10);
+    assert_eq!(revoked.len(), 1);
+    assert_eq!(revoked[0].1.reason, "Scoring ring");
+    assert_eq!(revoked[0].1.moderator, admin());
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(!info.exist);
+    assert!(info.excluded);
+}
+
+#[test]
+fn revoke_reverts_streak_bonuses() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+    contract.contract.create_streak(
+        "Great PR".to_string(),
+        TimePeriod::Month,
+        vec![StreakType::LargestScore(8)],
+        vec![StreakReward::PermanentPercentageBonus(5)],
+    );
+
+    // The weekly flat bonus is earned on include, the monthly one and the lifetime bonus on finalize
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let (_, account) = contract.contract.get_or_create_account(&github_handle(0));
+    assert!(account.flat_bonus.is_empty());
+    assert_eq!(account.lifetime_percentage_bonus(), 5);
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_rating, 105);
+
+    contract.revoke(0, "Scoring ring");
+
+    // The weekly bonus was earned before the finalization, so it's given back
+    let (_, account) = contract.contract.get_or_create_account(&github_handle(0));
+    assert_eq!(account.flat_bonus.len(), 1);
+    assert_eq!(account.flat_bonus[0].streak_id, 0);
+    assert_eq!(account.flat_bonus[0].reward, 10);
+    assert_eq!(account.lifetime_percentage_bonus(), 0);
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_rating, 0);
+}
+
+#[test]
+fn revoke_keeps_autoscore_user_untouched() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.contract.sloth_finalize(
+        pr_id_str(0),
+        Some((true, "sloth-bot".to_string())),
+        None,
+        None,
+    );
+    contract.revoke(0, "Trivial PR");
+
+    assert!(contract
+        .contract
+        .user(&"sloth-bot".to_string(), vec![])
+        .is_none());
+    let author = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(author.period_data[0].1.total_score, 0);
+    assert_eq!(author.period_data[0].1.total_rating, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only moderator can call this method")]
+fn revoke_is_moderator_only() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    contract.revoke(0, "Scoring ring");
+}
+
+#[test]
+#[should_panic(expected = "Revoked PR cannot be included")]
+fn revoked_pr_cannot_be_included_again() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    contract.revoke(0, "Trivial PR");
+    contract.include_sloth_common_repo(0, 0, 0);
+}
+
+#[test]
+fn adjustment_is_applied_and_logged() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(0, 1, 0);
+    contract.contract.sloth_adjust(
+        github_handle(0),
+        "all-time".to_string(),
+        UserPeriodDataDelta {
+            prs_opened: -1,
+            ..Default::default()
+        },
+        "Double count".to_string(),
+    );
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.prs_opened, 1);
+
+    let adjustments = contract.contract.user_adjustments(github_handle(0), 0, 10);
+    assert_eq!(adjustments.len(), 1);
+    assert_eq!(adjustments[0].reason, "Double count");
+    assert_eq!(adjustments[0].period, "all-time");
+    assert!(contract
+        .contract
+        .user_adjustments(github_handle(1), 0, 10)
+        .is_empty());
+    assert_eq!(contract.contract.adjustments(0, 10)[0].0, github_handle(0));
+}
+
+#[test]
+fn user_adjustments_are_paged() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(1, 1, 0);
+    for (id, reason) in [(0, "a"), (1, "b"), (0, "c"), (0, "d")] {
+        contract.contract.sloth_adjust(
+            github_handle(id),
+            "all-time".to_string(),
+            UserPeriodDataDelta {
+                prs_merged: 1,
+                ..Default::default()
+            },
+            reason.to_string(),
+        );
+    }
+
+    let reasons = |from, limit| {
+        contract
+            .contract
+            .user_adjustments(github_handle(0), from, limit)
+            .into_iter()
+            .map(|adjustment| adjustment.reason)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(reasons(0, 2), vec!["a", "c"]);
+    assert_eq!(reasons(2, 2), vec!["d"]);
+    assert!(reasons(3, 2).is_empty());
+    assert_eq!(
+        contract.contract.user_adjustments(github_handle(1), 0, 10)[0].reason,
+        "b"
+    );
+}
+
+#[test]
+#[should_panic(expected = "Adjustment is out of range")]
+fn adjustment_cannot_underflow() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_adjust(
+        github_handle(0),
+        "all-time".to_string(),
+        UserPeriodDataDelta {
+            prs_opened: -2,
+            ..Default::default()
+        },
+        "Double count".to_string(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "User is not found")]
+fn adjustment_requires_existing_user() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.contract.sloth_adjust(
+        github_handle(0),
+        "all-time".to_string(),
+        UserPeriodDataDelta {
+            total_rating: 10,
+            ..Default::default()
+        },
+        "Typo in the handle".to_string(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unknown period")]
+fn adjustment_requires_known_period() {
+    let mut contract = ContractExt::new();
+    contract.add_moderator(admin());
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.contract.sloth_adjust(
+        github_handle(0),
+        "alltime".to_string(),
+        UserPeriodDataDelta {
+            total_rating: 10,
+            ..Default::default()
+        },
+        "Typo in the period".to_string(),
+    );
+}
+
+#[test]
+fn renamed_user_keeps_history() {
+    let mut contract = ContractExt::new();
+
+    let include = |contract: &mut ContractExt, handle: &str, pr_id: u64| {
+        contract.contract.sloth_include(
+            "NEAR-DevHub".to_string(),
+            "devbot".to_string(),
+            handle.to_string(),
+            pr_id,
+            0,
+            true,
+            None,
+            Some(42),
+        );
+    };
+    include(&mut contract, "old-name", 0);
+    include(&mut contract, "new-name", 1);
+
+    let user = contract
+        .contract
+        .user(&"new-name".to_string(), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.name, "new-name");
+    assert_eq!(user.period_data[0].1.prs_opened, 2);
+    assert_eq!(
+        contract
+            .contract
+            .user(&"old-name".to_string(), vec![])
+            .unwrap()
+            .id,
+        user.id
+    );
+
+    // Stored PRs are reported with the current handle
+    let prs = contract.contract.prs(10, 0);
+    assert!(prs.iter().all(|(pr, _)| pr.author == "new-name"));
+}
+
+#[test]
+fn rename_links_existing_account() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract
+        .contract
+        .sloth_rename(42, github_handle(0), "new-name".to_string());
+
+    let user = contract
+        .contract
+        .user(&"new-name".to_string(), vec![])
+        .unwrap();
+    assert_eq!(user.name, "new-name");
+
+    // Somebody else takes the released handle
+    contract.contract.sloth_include(
+        "NEAR-DevHub".to_string(),
+        "devbot".to_string(),
+        github_handle(0),
+        1,
+        0,
+        true,
+        None,
+        Some(7),
+    );
+    let other = contract.contract.user(&github_handle(0), vec![]).unwrap();
+    assert_ne!(other.id, user.id);
+}
+
+#[test]
+fn reused_handle_does_not_take_credit() {
+    let mut contract = ContractExt::new();
+
+    let include = |contract: &mut ContractExt, pr_id: u64, github_id: u64| {
+        contract.contract.sloth_include(
+            "NEAR-DevHub".to_string(),
+            "devbot".to_string(),
+            github_handle(0),
+            pr_id,
+            0,
+            true,
+            Some(ScoringScale::Linear),
+            Some(github_id),
+        );
+    };
+    include(&mut contract, 0, 42);
+    contract
+        .contract
+        .sloth_rename(42, github_handle(0), "new-name".to_string());
+    include(&mut contract, 1, 7);
+
+    contract.score(0, 1, 10);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = WEEK_IN_NANOSECONDS;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let original = contract
+        .contract
+        .user(&"new-name".to_string(), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(original.period_data[0].1.total_score, 10);
+    assert_eq!(original.period_data[0].1.prs_merged, 1);
+    let other = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_ne!(other.id, original.id);
+    assert_eq!(other.period_data[0].1.total_score, 0);
+    assert_eq!(other.period_data[0].1.prs_opened, 1);
+
+    let prs = contract.contract.prs(10, 0);
+    let author = |number: u64| {
+        prs.iter()
+            .find(|(pr, _)| pr.number == number)
+            .map(|(pr, _)| pr.author.clone())
+            .unwrap()
+    };
+    assert_eq!(author(0), "new-name");
+    assert_eq!(author(1), github_handle(0));
+
+    assert_eq!(
+        contract.contract.linked_users(0, 10),
+        vec![
+            (original.id, 42, "new-name".to_string()),
+            (other.id, 7, github_handle(0)),
+        ]
+    );
+    let first = contract.contract.linked_users(0, 1);
+    assert_eq!(first, vec![(original.id, 42, "new-name".to_string())]);
+    assert_eq!(
+        contract.contract.linked_users(first[0].0 + 1, 10),
+        vec![(other.id, 7, github_handle(0))]
+    );
+}
+
+#[test]
+fn executed_prs_are_archived() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(0, 1, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 10);
+    contract.merge(1, WEEK_IN_NANOSECONDS);
+    contract.context.block_timestamp = 2 * WEEK_IN_NANOSECONDS;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    contract.finalize(1);
+    let before = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10), 1);
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10), 0);
+
+    let prs = contract.contract.prs(10, 0);
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0].0.number, 1);
+
+    let summary = contract
+        .contract
+        .user_archive_summary(github_handle(0))
+        .unwrap();
+    assert_eq!(summary.executed_prs, 1);
+    assert_eq!(summary.total_score, 8);
+
+    // Period data is not affected
+    let after = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(after.period_data, before.period_data);
+}
+
+#[test]
+fn coauthor_streak_bonus_is_archived() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.coauthors(0, &[1]);
+    contract.score(0, 2, 8);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = WEEK_IN_NANOSECONDS;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+
+    let pr: PRv6 = contract
+        .contract
+        .executed_prs
+        .get(&pr_id_str(0))
+        .unwrap()
+        .clone()
+        .into();
+    let coauthor = contract
+        .contract
+        .user(&github_handle(1), vec!["all-time".to_string()])
+        .unwrap();
+    let stored = pr
+        .ratings
+        .iter()
+        .find(|rating| rating.user_id == coauthor.id)
+        .unwrap();
+    // The co-author got a streak bonus on top of the shared rating
+    assert!(!stored.used_bonuses.is_empty());
+    assert!(stored.rating > pr.participant_rating(false, 0, 0));
+
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10), 1);
+    let summary = contract
+        .contract
+        .user_archive_summary(github_handle(1))
+        .unwrap();
+    assert_eq!(summary.total_rating, stored.rating);
+    assert_eq!(summary.total_rating, coauthor.period_data[0].1.total_rating);
+}
+
+#[test]
+fn oldest_merged_prs_are_archived_first() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.include_sloth_common_repo(0, 1, 0);
+    contract.merge(1, 20);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = 2 * WEEK_IN_NANOSECONDS;
+    testing_env!(contract.context.clone());
+    contract.finalize(1);
+    contract.finalize(0);
+
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 1), 1);
+    let prs = contract.contract.prs(10, 0);
+    assert_eq!(prs.len(), 1);
+    assert_eq!(prs[0].0.number, 1);
+
+    // Archived PR is gone from the storage, but it can't be included again
+    let results = contract.contract.sloth_batch(vec![SlothAction::Include {
+        organization: "NEAR-DevHub".to_string(),
+        repo: "devbot".to_string(),
+        user: github_handle(0),
+        pr_number: 0,
+        created_at: 0,
+        override_exclude: true,
+        scale: None,
+        github_id: None,
+    }]);
+    assert!(matches!(
+        &results[..],
+        [SlothActionResult::Rejected { reason }] if reason == "Archived PR cannot be included"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Archived PR cannot be included")]
+fn archived_pr_cannot_be_included() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = 2 * WEEK_IN_NANOSECONDS;
+    testing_env!(contract.context.clone());
+    contract.finalize(0);
+    contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10);
+
+    contract.include_sloth_common_repo(0, 0, 0);
+}
+
+#[test]
+fn pr_indexes_follow_pr_lifecycle() {
+    let mut contract = ContractExt::new();
+
+    for pr_id in 0..4 {
+        contract.include_sloth_common_repo(0, pr_id, 0);
+    }
+    contract.exclude(3);
+    contract.stale(2);
+    let numbers = |prs: Vec<PRv6>| prs.iter().map(|pr| pr.number).collect::<Vec<_>>();
+    assert_eq!(numbers(contract.contract.unmerged_prs(None, 50)), [0, 1]);
+
+    contract.unstale(2);
+    contract.merge(1, 10);
+    contract.merge(0, 20);
+    contract.merge(2, WEEK_IN_NANOSECONDS);
+    assert!(contract.contract.unmerged_prs(None, 50).is_empty());
+
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 21;
+    testing_env!(contract.context.clone());
+    let page = contract.contract.unfinalized_prs(None, 1);
+    assert_eq!(numbers(page.clone()), [1]);
+    let cursor = (page[0].ready_to_move_timestamp().unwrap(), page[0].pr_id());
+    assert_eq!(
+        numbers(contract.contract.unfinalized_prs(Some(cursor), 50)),
+        [0]
+    );
+
+    contract.finalize(1);
+    assert_eq!(numbers(contract.contract.unfinalized_prs(None, 50)), [0]);
+}
+
+#[test]
+fn batch_rejects_failing_actions_only() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.score(0, 1, 8);
+    contract.merge(0, 10);
+    contract.context.block_timestamp = SCORE_TIMEOUT_IN_NANOSECONDS + 11;
+    testing_env!(contract.context.clone());
+
+    let results = contract.contract.sloth_batch(vec![
+        SlothAction::Include {
+            organization: "NEAR-DevHub".to_string(),
+            repo: "devbot".to_string(),
+            user: github_handle(2),
+            pr_number: 1,
+            created_at: 0,
+            override_exclude: false,
+            scale: None,
+            github_id: None,
+        },
+        SlothAction::Merged {
+            pr_id: pr_id_str(5),
+            merged_at: 10,
+        },
+        SlothAction::Stale {
+            pr_id: pr_id_str(0),
+        },
+        SlothAction::Finalize {
+            pr_id: pr_id_str(0),
+            active_pr: None,
+            timestamp: None,
+            closed_issues: None,
+        },
+    ]);
+
+    assert!(matches!(
+        &results[0],
+        SlothActionResult::Applied { events } if matches!(events[..], [Event::NewSloth { .. }])
+    ));
+    assert!(matches!(
+        &results[1],
+        SlothActionResult::Rejected { reason } if reason == "PR is not started or already executed"
+    ));
+    assert!(matches!(
+        &results[2],
+        SlothActionResult::Rejected { reason } if reason == "Merged PR cannot be stale"
+    ));
+    assert!(matches!(
+        &results[3],
+        SlothActionResult::Applied { events }
+            if events.iter().any(|e| matches!(e, Event::ExecutedWithRating { .. }))
+    ));
+
+    assert_eq!(contract.contract.unmerged_prs(None, 50).len(), 1);
+    assert!(
+        contract
+            .contract
+            .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0)
+            .executed
+    );
+}
+
+#[test]
+#[should_panic(expected = "Contract is paused")]
+fn paused_contract_rejects_sloth() {
+    let mut contract = ContractExt::new();
+
+    contract.set_paused(true);
+    contract.include_sloth_common_repo(0, 0, 0);
+}
+
+#[test]
+fn admin_can_change_paused_contract() {
+    let mut contract = ContractExt::new();
+
+    contract.include_sloth_common_repo(0, 0, 0);
+    contract.set_paused(true);
+    let info = contract
+        .contract
+        .check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.paused_contract);
+    assert!(!info.migrating_contract);
+    assert!(contract.contract.is_paused());
+    assert!(info.exist);
+
+    contract.context.predecessor_account_id = contract.context.current_account_id.clone();
+    testing_env!(contract.context.clone());
+    contract.exclude(0);
+
+    contract.set_paused(false);
+    contract.context.predecessor_account_id = admin();
+    testing_env!(contract.context.clone());
+    contract.include_sloth_common_repo(0, 1, 0);
+    assert!(!contract.contract.is_paused());
+}
+
+#[test]
+fn rank_up_is_logged_once() {
+    let mut contract = ContractExt::new();
+    let rank_ups = || {
+        events::take_logged()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::RankUp { rank } => Some(rank),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let user_rank = |contract: &ContractExt| {
+        contract
+            .contract
+            .user(&github_handle(0), vec![])
+            .unwrap()
+            .rank
+    };
+
+    // The weekly streak gives the lifetime bonus on the way
+    rank_ups();
+    let mut start = 0;
+    for i in 0..6 {
+        contract.include_sloth_common_repo(0, i, start);
+        contract.merge(i, start + 1);
+
+        start += WEEK_IN_NANOSECONDS + 1;
+        contract.context.block_timestamp = start;
+        testing_env!(contract.context.clone());
+        contract.finalize(i);
+    }
+    assert_eq!(rank_ups(), vec!["Bronze".to_string()]);
+    assert_eq!(user_rank(&contract), Some("Bronze".to_string()));
+
+    contract.include_sloth_common_repo(0, 6, start);
+    contract.merge(6, start + 1);
+    contract.context.block_timestamp = start + SCORE_TIMEOUT_IN_NANOSECONDS + 2;
+    testing_env!(contract.context.clone());
+    contract.finalize(6);
+    assert!(rank_ups().is_empty());
+
+    // The view follows the new thresholds, the event waits for the next finalize
+    let sloth = contract.context.predecessor_account_id.clone();
+    contract.context.predecessor_account_id = contract.context.current_account_id.clone();
+    testing_env!(contract.context.clone());
+    contract
+        .contract
+        .set_rank_tiers(vec![RankTier::new("Sloth", 1), RankTier::new("Rust", 5)]);
+    contract.context.predecessor_account_id = sloth;
+    testing_env!(contract.context.clone());
+    assert_eq!(user_rank(&contract), Some("Rust".to_string()));
+
+    contract.include_sloth_common_repo(0, 7, start);
+    contract.merge(7, start + 1);
+    contract.finalize(7);
+    assert_eq!(rank_ups(), vec!["Rust".to_string()]);
+}
+
+#[test]
+#[should_panic(expected = "Rank tiers have to be sorted by the bonus")]
+fn rank_tiers_are_sorted() {
+    let mut contract = ContractExt::new();
+    contract.context.predecessor_account_id = contract.context.current_account_id.clone();
+    testing_env!(contract.context.clone());
+    contract
+        .contract
+        .set_rank_tiers(vec![RankTier::new("Gold", 15), RankTier::new("Silver", 10)]);
+}
+
+// Storage snapshot of an older deployment, see `migrate.rs`
+fn load_state_fixture(fixture: &str) {
+    use near_sdk::base64::{engine::general_purpose::STANDARD, Engine};
+
+    let storage: std::collections::BTreeMap<String, String> =
+        near_sdk::serde_json::from_str(fixture).unwrap();
+    for (key, value) in storage {
+        env::storage_write(
+            &STANDARD.decode(key).unwrap(),
+            &STANDARD.decode(value).unwrap(),
+        );
+    }
+}
+
+// Deploys the current code over the fixture and applies the pending migrations
+fn migrate_state_fixture(fixture: &str, applied: usize) -> ContractExt {
+    let mut context = VMContextBuilder::new().build();
+    context.predecessor_account_id = admin();
+    testing_env!(context.clone());
+    load_state_fixture(fixture);
+
+    let contract = Contract::migrate();
+    assert!(contract.is_migrating());
+    assert!(!contract.is_paused());
+    let info = contract.check_info("NEAR-DevHub".to_string(), "devbot".to_string(), 0);
+    assert!(info.migrating_contract);
+    assert!(!info.paused_contract);
+    assert_eq!(
+        contract.pending_migrations().len(),
+        migrate::MIGRATIONS.len() - applied
+    );
+
+    let mut contract = ContractExt { contract, context };
+    let mut steps = 0;
+    while !contract.contract.migrate_step(1) {
+        steps += 1;
+        assert!(steps < 100, "Migrations don't finish");
+    }
+    assert!(contract.contract.pending_migrations().is_empty());
+    assert!(!contract.contract.is_migrating());
+    contract
+}
+
+#[test]
+fn state_v0_is_migrated() {
+    let mut contract = migrate_state_fixture(include_str!("../fixtures/state_v0.json"), 0);
+
+    assert!(matches!(
+        contract.contract.executed_prs.get(&pr_id_str(0)),
+        Some(VersionedPR::V6(_))
+    ));
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.executed_prs, 1);
+    assert_eq!(user.period_data[0].1.prs_opened, 2);
+    assert!(contract.contract.user_auto_include(github_handle(0)));
+    assert_eq!(contract.contract.scoring_scale(), ScoringScale::default());
+
+    let unmerged = contract.contract.unmerged_prs(None, 50);
+    assert_eq!(unmerged.len(), 1);
+    assert_eq!(unmerged[0].number, 2);
+
+    // The bot continues where the old deployment stopped
+    contract.context.block_timestamp = WEEK_IN_NANOSECONDS + SCORE_TIMEOUT_IN_NANOSECONDS + 1;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.unfinalized_prs(None, 50).len(), 1);
+    contract.finalize(1);
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10), 1);
+    contract.include_sloth_common_repo(1, 4, 0);
+}
+
+#[test]
+fn state_v1_is_migrated() {
+    let mut contract = migrate_state_fixture(include_str!("../fixtures/state_v1.json"), 3);
+
+    let user = contract
+        .contract
+        .user(&github_handle(0), vec!["all-time".to_string()])
+        .unwrap();
+    assert_eq!(user.period_data[0].1.executed_prs, 1);
+    assert!(contract.contract.user_auto_include(github_handle(0)));
+    assert_eq!(
+        contract.contract.user_locale(github_handle(0)),
+        Some("es".to_string())
+    );
+
+    let unmerged = contract.contract.unmerged_prs(None, 50);
+    assert_eq!(unmerged.len(), 1);
+    assert_eq!(unmerged[0].number, 2);
+
+    contract.context.block_timestamp = WEEK_IN_NANOSECONDS + SCORE_TIMEOUT_IN_NANOSECONDS + 1;
+    testing_env!(contract.context.clone());
+    assert_eq!(contract.contract.unfinalized_prs(None, 50).len(), 1);
+    contract.finalize(1);
+    contract.unstale(3);
+    assert_eq!(contract.contract.unmerged_prs(None, 50).len(), 2);
+    assert_eq!(contract.contract.sloth_archive(WEEK_IN_NANOSECONDS, 10), 1);
+}
+
+#[test]
+#[should_panic(expected = "Contract is paused")]
+fn sloth_waits_for_migrations() {
+    let mut context = VMContextBuilder::new().build();
+    context.predecessor_account_id = admin();
+    testing_env!(context.clone());
+    load_state_fixture(include_str!("../fixtures/state_v0.json"));
+
+    let mut contract = Contract::migrate();
+    contract.migrate_step(1);
+    contract.sloth_merged(pr_id_str(2), 10);
 }
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Bound};
 
 use near_sdk::near_bindgen;
-use shared::{PRInfo, User, UserId, UserPeriodDataV2};
+use shared::{Bounty, PRInfo, User, UserId, UserPeriodDataV2};
 
 use super::*;
 
@@ -11,7 +11,7 @@ impl Contract {
         let pr_id = format!("{}/{}/{}", organization, repo, issue_id);
         let executed_pr = self.executed_prs.get(&pr_id);
         let pr: Option<&VersionedPR> = self.prs.get(&pr_id).or(executed_pr);
-        let pr: Option<PRv2> = pr.cloned().map(|pr| pr.into());
+        let pr: Option<PRv6> = pr.cloned().map(|pr| pr.into());
         let repo_allowed = self.repos.get(&(organization, repo));
 
         PRInfo {
@@ -28,45 +28,137 @@ impl Contract {
                 .map(|pr| pr.merged_at.is_some())
                 .unwrap_or_default(),
             executed: executed_pr.is_some(),
-            excluded: self.excluded_prs.contains(&pr_id),
+            excluded: self.excluded_prs.contains(&pr_id) || self.revoked_prs.contains_key(&pr_id),
             votes: pr.as_ref().map(|pr| pr.score.clone()).unwrap_or_default(),
+            ready_to_move_timestamp: pr.as_ref().and_then(|pr| pr.ready_to_move_timestamp()),
+            scale: pr.as_ref().map(|pr| pr.scale).unwrap_or(self.scoring_scale),
+            coauthors: pr.map(|pr| pr.coauthors).unwrap_or_default(),
+            paused_contract: self.paused,
+            migrating_contract: !self.is_migrated(),
         }
     }
 
+    /// Whether the contract is paused by the admin
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether some of the migrations are not applied yet
+    pub fn is_migrating(&self) -> bool {
+        !self.is_migrated()
+    }
+
+    pub fn scoring_scale(&self) -> ScoringScale {
+        self.scoring_scale
+    }
+
+    pub fn rank_tiers(&self) -> Vec<RankTier> {
+        self.rank_tiers.clone()
+    }
+
     /// Returns a list of PRs with the execution status
-    pub fn prs(&self, limit: u64, page: u64) -> Vec<(PRv2, bool)> {
+    pub fn prs(&self, limit: u64, page: u64) -> Vec<(PRv6, bool)> {
         self.prs
             .into_iter()
             .chain(self.executed_prs.iter())
             .skip((page * limit) as usize)
             .take(limit as usize)
-            .map(|(id, pr)| (pr.clone().into(), !self.prs.contains_key(id)))
+            .map(|(id, pr)| {
+                (
+                    self.with_current_handles(pr.clone().into()),
+                    !self.prs.contains_key(id),
+                )
+            })
             .collect()
     }
 
-    pub fn unmerged_prs(&self, page: u64, limit: u64) -> Vec<PRv2> {
-        self.prs
-            .values()
-            .filter(|pr| !pr.is_merged())
-            .skip((page * limit) as usize)
+    /// Returns unmerged PRs ordered by id, starting after the `from` id
+    pub fn unmerged_prs(&self, from: Option<PRId>, limit: u64) -> Vec<PRv6> {
+        let from = from.as_ref().map_or(Bound::Unbounded, Bound::Excluded);
+        self.unmerged_pr_ids
+            .range::<_, PRId>((from, Bound::Unbounded))
             .take(limit as usize)
+            .filter_map(|(pr_id, _)| self.prs.get(pr_id))
             .cloned()
             .map(Into::into)
             .collect()
     }
 
-    pub fn unfinalized_prs(&self, page: u64, limit: u64) -> Vec<PRv2> {
+    /// Returns PRs ready to be finalized in the order they became ready, starting after the `from` cursor.
+    /// The cursor of a PR is its `ready_to_move_timestamp` and id
+    pub fn unfinalized_prs(&self, from: Option<(Timestamp, PRId)>, limit: u64) -> Vec<PRv6> {
         let timestamp = env::block_timestamp();
-        self.prs
-            .values()
-            .filter(|pr| pr.is_ready_to_move(timestamp))
-            .skip((page * limit) as usize)
+        let from = from.as_ref().map_or(Bound::Unbounded, Bound::Excluded);
+        self.finalization_queue
+            .range::<_, (Timestamp, PRId)>((from, Bound::Unbounded))
+            .take_while(|((ready_at, _), _)| *ready_at < timestamp)
             .take(limit as usize)
+            .filter_map(|((_, pr_id), _)| self.prs.get(pr_id))
             .cloned()
             .map(Into::into)
             .collect()
     }
 
+    /// Returns PRs removed for inactivity with the time they became stale
+    pub fn stale_prs(&self, page: u64, limit: u64) -> Vec<(PRv6, Timestamp)> {
+        self.stale_prs
+            .values()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .map(|(pr, stale_at)| (pr.clone().into(), *stale_at))
+            .collect()
+    }
+
+    /// Returns finalized PRs taken back by moderators with the reason
+    pub fn revoked_prs(&self, page: u64, limit: u64) -> Vec<(PRv6, Revocation)> {
+        self.revoked_prs
+            .values()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .map(|(pr, revocation)| (pr.clone().into(), revocation.clone()))
+            .collect()
+    }
+
+    /// Returns the manual corrections of the user's period data, oldest first, skipping the first `from` of them
+    pub fn user_adjustments(&self, user: GithubHandle, from: u64, limit: u64) -> Vec<Adjustment> {
+        let Some(indices) = self
+            .account_ids
+            .get(&user)
+            .and_then(|user_id| self.user_adjustments.get(user_id))
+        else {
+            return vec![];
+        };
+        indices
+            .iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|index| self.adjustments[*index].1.clone())
+            .collect()
+    }
+
+    /// Returns the log of the manual corrections with the users they were applied to
+    pub fn adjustments(&self, page: u64, limit: u64) -> Vec<(GithubHandle, Adjustment)> {
+        self.adjustments
+            .iter()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .map(|(user_id, adjustment)| {
+                let user: AccountWithPermanentPercentageBonus = self.users[*user_id].clone().into();
+                (user.github_handle, adjustment.clone())
+            })
+            .collect()
+    }
+
+    /// Returns what the archived PRs of the user contributed
+    pub fn user_archive_summary(&self, user: GithubHandle) -> Option<ArchiveSummary> {
+        let user_id = self.account_ids.get(&user)?;
+        self.archive_summaries.get(user_id).cloned()
+    }
+
+    pub fn is_moderator(&self, account_id: AccountId) -> bool {
+        self.moderators.contains(&account_id)
+    }
+
     pub fn user_streaks(&self, user_id: UserId) -> Vec<(StreakId, StreakUserData)> {
         self.streaks
             .into_iter()
@@ -91,6 +183,20 @@ impl Contract {
         self.user_by_id(user, periods)
     }
 
+    pub fn user_locale(&self, user: GithubHandle) -> Option<String> {
+        let user_id = self.account_ids.get(&user)?;
+        self.user_locales.get(user_id).cloned()
+    }
+
+    // Only the users that joined the race themselves are included automatically.
+    // Scorers and co-authors have accounts too, but they never asked for it
+    pub fn user_auto_include(&self, user: GithubHandle) -> bool {
+        self.account_ids.get(&user).is_some_and(|user_id| {
+            self.auto_include_users.contains(user_id)
+                && !self.auto_include_opt_outs.contains(user_id)
+        })
+    }
+
     pub fn user_by_id(&self, user_id: UserId, periods: Vec<TimePeriodString>) -> Option<User> {
         let u: AccountWithPermanentPercentageBonus = self.users.get(user_id)?.clone().into();
         let percentage_bonus = u.lifetime_percentage_bonus();
@@ -109,6 +215,7 @@ impl Contract {
                 })
                 .collect(),
             streaks: self.user_streaks(user_id),
+            rank: self.rank(percentage_bonus),
         })
     }
 
@@ -118,6 +225,21 @@ impl Contract {
             .collect()
     }
 
+    /// Returns the users linked to a GitHub id with their current handles, starting at the `from` user id.
+    /// The bot follows the renames with it
+    pub fn linked_users(&self, from: UserId, limit: u64) -> Vec<(UserId, u64, GithubHandle)> {
+        self.linked_user_ids
+            .range(from..)
+            .take(limit as usize)
+            .filter_map(|(user_id, _)| {
+                let github_id = *self.user_github_ids.get(user_id)?;
+                let account: AccountWithPermanentPercentageBonus =
+                    self.users[*user_id].clone().into();
+                Some((*user_id, github_id, account.github_handle))
+            })
+            .collect()
+    }
+
     pub fn users_by_name(
         &self,
         users: Vec<GithubHandle>,
@@ -153,4 +275,15 @@ impl Contract {
 
         repos.into_values().collect()
     }
+
+    /// Returns a list of open bounties
+    pub fn bounties(&self, page: u64, limit: u64) -> Vec<Bounty> {
+        self.bounties
+            .values()
+            .skip((page * limit) as usize)
+            .take(limit as usize)
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
 }
@@ -0,0 +1,23 @@
+-- Votes of the PRs, used to find scoring rings
+CREATE TABLE IF NOT EXISTS pr_votes (
+    pull_request_id INTEGER REFERENCES pull_requests(id) ON DELETE CASCADE,
+    user_login TEXT NOT NULL,
+    score INTEGER NOT NULL,
+    PRIMARY KEY (pull_request_id, user_login)
+);
+
+ALTER TABLE
+    pull_requests
+ADD
+    COLUMN additions INTEGER,
+ADD
+    COLUMN deletions INTEGER;
+
+CREATE TABLE IF NOT EXISTS moderation_flags (
+    id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    details TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (kind, subject)
+);
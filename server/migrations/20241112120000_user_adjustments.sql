@@ -0,0 +1,9 @@
+-- Manual corrections of the period data, `id` is the position in the contract log
+CREATE TABLE IF NOT EXISTS user_adjustments (
+    id INTEGER PRIMARY KEY,
+    user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    period_type TEXT NOT NULL,
+    changes TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    adjusted_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Rank tier of the user as calculated by the contract, NULL if the user is not ranked
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS rank TEXT;
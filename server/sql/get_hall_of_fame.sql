@@ -3,6 +3,7 @@ SELECT
     u.login,
     u.full_name,
     u.permanent_bonus,
+    u.rank,
     upd.total_rating
 FROM
     users AS u
@@ -19,7 +19,8 @@ SELECT
         ORDER BY
             total_rating DESC
     ) as place,
-    users.permanent_bonus as permanent_bonus
+    users.permanent_bonus as permanent_bonus,
+    users.rank as rank
 FROM
     user_period_data
     JOIN users ON users.id = user_period_data.user_id
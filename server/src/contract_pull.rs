@@ -36,9 +36,15 @@ async fn fetch_and_store_users(
         .context("Failed to fetch users")?;
 
     for user in users {
-        let user_id = DB::upsert_user(tx, user.id, &user.name, user.percentage_bonus)
-            .await
-            .with_context(|| format!("Failed to upsert user with id: {}", user.id))?;
+        let user_id = DB::upsert_user(
+            tx,
+            user.id,
+            &user.name,
+            user.percentage_bonus,
+            user.rank.as_deref(),
+        )
+        .await
+        .with_context(|| format!("Failed to upsert user with id: {}", user.id))?;
         for (period, data) in user.period_data {
             DB::upsert_user_period_data(tx, period, &data, user_id)
                 .await
@@ -61,6 +67,28 @@ async fn fetch_and_store_users(
     Ok(())
 }
 
+async fn fetch_and_store_adjustments(
+    near_client: &NearClient,
+    tx: &mut Transaction<'static, Postgres>,
+) -> anyhow::Result<()> {
+    let adjustments = near_client
+        .adjustments_all()
+        .await
+        .context("Failed to fetch adjustments")?;
+
+    // The log is append-only, so the position identifies the adjustment
+    for (id, (user, adjustment)) in adjustments.into_iter().enumerate() {
+        let user_id = DB::get_user_id(tx, &user)
+            .await
+            .context("Failed on getting user id")?;
+        DB::upsert_user_adjustment(tx, id as i32, user_id, &adjustment)
+            .await
+            .with_context(|| format!("Failed to upsert adjustment for user id: {}", user_id))?;
+    }
+
+    Ok(())
+}
+
 async fn fetch_and_store_prs(
     telegram: &Arc<TelegramSubscriber>,
     near_client: &NearClient,
@@ -71,7 +99,16 @@ async fn fetch_and_store_prs(
         .await
         .context("Failed to fetch PRs from near_client")?;
 
+    let revoked: Vec<_> = near_client
+        .revoked_prs_all()
+        .await
+        .context("Failed to fetch revoked PRs from near_client")?
+        .into_iter()
+        .map(|(pr, _)| pr)
+        .collect();
+
     DB::remove_non_existent_prs(tx, &prs).await?;
+    DB::remove_prs(tx, &revoked).await?;
 
     for (pr, executed) in prs {
         let Some((_, repo_id)) = DB::get_organization_repo_id(tx, &pr.organization, &pr.repo)
@@ -92,7 +129,7 @@ async fn fetch_and_store_prs(
             .await
             .context("Failed on getting user id")?;
 
-        DB::upsert_pull_request(
+        let pull_request_id = DB::upsert_pull_request(
             tx,
             repo_id,
             pr.number as i32,
@@ -109,6 +146,10 @@ async fn fetch_and_store_prs(
         )
         .await
         .context("Failed on upserting PR")?;
+
+        DB::replace_pr_votes(tx, pull_request_id, &pr.score)
+            .await
+            .context("Failed on storing PR votes")?;
     }
 
     Ok(())
@@ -147,6 +188,12 @@ async fn fetch_and_store_all_data(
         .context("Failed to fetch and store users")?;
     tx.commit().await?;
 
+    let mut tx = db.begin().await?;
+    fetch_and_store_adjustments(near_client, &mut tx)
+        .await
+        .context("Failed to fetch and store adjustments")?;
+    tx.commit().await?;
+
     let mut tx = db.begin().await?;
     fetch_and_store_repos(near_client, &mut tx)
         .await
@@ -4,7 +4,8 @@ use rocket::{
 };
 use rocket_db_pools::Database;
 use shared::{
-    AllowedRepos, PRv2, Repo, StreakUserData, TimePeriod, TimePeriodString, UserPeriodDataV2,
+    Adjustment, AllowedRepos, PRv6, Repo, Score, StreakUserData, TimePeriod, TimePeriodString,
+    UserPeriodDataV2,
 };
 use sqlx::{PgPool, Postgres, Transaction};
 
@@ -17,8 +18,9 @@ pub mod types;
 use types::{HallOfFameRecord, LeaderboardRecord, Statistics};
 
 use self::types::{
-    RepoLeaderboardRecord, RepoRecord, StreakRecord, User, UserCachedMetadata,
-    UserContributionRecord, UserPeriodRecord, UserRecord,
+    AdjustmentRecord, DiffStatsRecord, ModerationFlagRecord, RepoLeaderboardRecord, RepoRecord,
+    StreakRecord, User, UserCachedMetadata, UserContributionRecord, UserPeriodRecord, UserRecord,
+    VoteRecord,
 };
 
 impl DB {
@@ -27,17 +29,35 @@ impl DB {
         user_id: u32,
         user: &str,
         percent: u32,
+        rank: Option<&str>,
     ) -> anyhow::Result<i32> {
-        // First try to update the user
+        // Logins are unique, so the handle is taken away from the user that had it before.
+        // It happens when users swap handles or the handle of a renamed user is reused.
+        // That user gets the new handle once it's synced as well
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET login = login || '#' || id
+            WHERE login = $2 AND id <> $1
+            "#,
+            user_id as i32,
+            user
+        )
+        .execute(tx.as_mut())
+        .await?;
+
+        // First try to update the user, the login changes if the user was renamed
         let rec = sqlx::query!(
             r#"
             UPDATE users
-            SET permanent_bonus = $2
+            SET permanent_bonus = $2, login = $3, rank = $4
             WHERE id = $1
             RETURNING id
             "#,
             user_id as i32,
-            percent as i32
+            percent as i32,
+            user,
+            rank
         )
         .fetch_optional(tx.as_mut())
         .await?;
@@ -48,14 +68,15 @@ impl DB {
         } else {
             let rec = sqlx::query!(
                 r#"
-                INSERT INTO users (id, login, permanent_bonus)
-                VALUES ($1, $2, $3)
+                INSERT INTO users (id, login, permanent_bonus, rank)
+                VALUES ($1, $2, $3, $4)
                 ON CONFLICT (id) DO NOTHING
                 RETURNING id
                 "#,
                 user_id as i32,
                 user,
-                percent as i32
+                percent as i32,
+                rank
             )
             .fetch_one(tx.as_mut())
             .await?;
@@ -64,6 +85,30 @@ impl DB {
         }
     }
 
+    pub async fn upsert_user_adjustment(
+        tx: &mut Transaction<'static, Postgres>,
+        id: i32,
+        user_id: i32,
+        adjustment: &Adjustment,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_adjustments (id, user_id, period_type, changes, reason, adjusted_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+            id,
+            user_id,
+            adjustment.period,
+            adjustment.delta.to_string(),
+            adjustment.reason,
+            chrono::DateTime::from_timestamp_nanos(adjustment.adjusted_at as i64).naive_utc()
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_user_full_name(
         tx: &mut Transaction<'static, Postgres>,
         user: &str,
@@ -204,17 +249,18 @@ impl DB {
 
     pub async fn remove_non_existent_prs(
         tx: &mut Transaction<'static, Postgres>,
-        prs: &[(PRv2, bool)],
+        prs: &[(PRv6, bool)],
     ) -> anyhow::Result<()> {
         let pr_keys: Vec<(String, String, i32)> = prs
             .iter()
             .map(|(pr, _)| (pr.organization.clone(), pr.repo.clone(), pr.number as i32))
             .collect();
 
+        // Executed PRs are archived in the contract, so they are removed only when revoked
         sqlx::query!(
             r#"
             DELETE FROM pull_requests
-            WHERE (repo_id, number) NOT IN (
+            WHERE NOT executed AND (repo_id, number) NOT IN (
                 SELECT r.id, p.number
                 FROM unnest($1::text[], $2::text[], $3::int[]) AS p(org, repo, number)
                 JOIN organizations o ON o.login = p.org
@@ -240,6 +286,32 @@ impl DB {
         Ok(())
     }
 
+    pub async fn remove_prs(
+        tx: &mut Transaction<'static, Postgres>,
+        prs: &[PRv6],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM pull_requests
+            WHERE (repo_id, number) IN (
+                SELECT r.id, p.number
+                FROM unnest($1::text[], $2::text[], $3::int[]) AS p(org, repo, number)
+                JOIN organizations o ON o.login = p.org
+                JOIN repos r ON r.organization_id = o.id AND r.name = p.repo
+            )
+            "#,
+            &prs.iter()
+                .map(|pr| pr.organization.clone())
+                .collect::<Vec<_>>(),
+            &prs.iter().map(|pr| pr.repo.clone()).collect::<Vec<_>>(),
+            &prs.iter().map(|pr| pr.number as i32).collect::<Vec<_>>(),
+        )
+        .execute(tx.as_mut())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn remove_non_existent_repos(
         tx: &mut Transaction<'static, Postgres>,
         repos: &[AllowedRepos],
@@ -480,15 +552,15 @@ impl DB {
         login: &str,
         place_strings: &[String],
     ) -> anyhow::Result<Option<UserRecord>> {
-        let (user_rec, full_name, percent) = match sqlx::query!(
-            "SELECT id, full_name, permanent_bonus FROM users
+        let (user_rec, full_name, percent, rank) = match sqlx::query!(
+            "SELECT id, full_name, permanent_bonus, rank FROM users
             WHERE login = $1",
             login
         )
         .fetch_optional(&self.0)
         .await?
         {
-            Some(rec) => (rec.id, rec.full_name, rec.permanent_bonus),
+            Some(rec) => (rec.id, rec.full_name, rec.permanent_bonus, rec.rank),
             None => return Ok(None),
         };
 
@@ -531,6 +603,19 @@ impl DB {
         .fetch_optional(&self.0)
         .await?;
 
+        let adjustments = sqlx::query_as!(
+            AdjustmentRecord,
+            r#"
+            SELECT period_type, changes, reason, adjusted_at
+            FROM user_adjustments
+            WHERE user_id = $1
+            ORDER BY id ASC
+            "#,
+            user_rec
+        )
+        .fetch_all(&self.0)
+        .await?;
+
         let user = UserRecord {
             id: user_rec,
             first_contribution: first_contribution
@@ -539,9 +624,11 @@ impl DB {
             login: login.to_string(),
             name: full_name,
             lifetime_percent: percent,
+            rank,
             period_data: period_data_recs,
             streaks: streak_recs,
             leaderboard_places,
+            adjustments,
         };
 
         Ok(Some(user))
@@ -922,6 +1009,138 @@ impl DB {
 
         Ok((rec, total.id.unwrap_or_default() as u64))
     }
+
+    pub async fn replace_pr_votes(
+        tx: &mut Transaction<'static, Postgres>,
+        pull_request_id: i32,
+        votes: &[Score],
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"DELETE FROM pr_votes WHERE pull_request_id = $1"#,
+            pull_request_id
+        )
+        .execute(tx.as_mut())
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO pr_votes (pull_request_id, user_login, score)
+            SELECT $1, v.user_login, v.score
+            FROM unnest($2::text[], $3::int[]) AS v(user_login, score)
+            ON CONFLICT (pull_request_id, user_login) DO UPDATE SET score = EXCLUDED.score
+            "#,
+            pull_request_id,
+            &votes.iter().map(|v| v.user.clone()).collect::<Vec<_>>(),
+            &votes.iter().map(|v| v.score as i32).collect::<Vec<_>>(),
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_votes(&self) -> anyhow::Result<Vec<VoteRecord>> {
+        let rec = sqlx::query_as!(
+            VoteRecord,
+            r#"
+            SELECT v.pull_request_id as "pull_request_id!", u.login as author, v.user_login as voter, v.score
+            FROM pr_votes v
+            JOIN pull_requests pr ON pr.id = v.pull_request_id
+            JOIN users u ON u.id = pr.author_id
+            "#,
+        )
+        .fetch_all(&self.0)
+        .await?;
+        Ok(rec)
+    }
+
+    // PRs with diff stats, or without them if `missing` is set
+    pub async fn get_prs_diff_stats(&self, missing: bool) -> anyhow::Result<Vec<DiffStatsRecord>> {
+        let rec = sqlx::query_as!(
+            DiffStatsRecord,
+            r#"
+            SELECT pr.id, o.login as organization, r.name as repo, pr.number, u.login as author, pr.additions, pr.deletions
+            FROM pull_requests pr
+            JOIN repos r ON pr.repo_id = r.id
+            JOIN organizations o ON r.organization_id = o.id
+            JOIN users u ON pr.author_id = u.id
+            WHERE (pr.additions IS NULL) = $1
+            "#,
+            missing
+        )
+        .fetch_all(&self.0)
+        .await?;
+        Ok(rec)
+    }
+
+    pub async fn update_pr_diff_stats(
+        tx: &mut Transaction<'static, Postgres>,
+        pull_request_id: i32,
+        additions: u32,
+        deletions: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE pull_requests
+            SET additions = $2, deletions = $3
+            WHERE id = $1
+            "#,
+            pull_request_id,
+            additions as i32,
+            deletions as i32
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+
+    // Returns false if the same item was flagged before
+    pub async fn insert_moderation_flag(
+        &self,
+        kind: &str,
+        subject: &str,
+        details: &str,
+    ) -> anyhow::Result<bool> {
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO moderation_flags (kind, subject, details)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (kind, subject) DO NOTHING
+            RETURNING id
+            "#,
+            kind,
+            subject,
+            details
+        )
+        .fetch_optional(&self.0)
+        .await?;
+        Ok(rec.is_some())
+    }
+
+    pub async fn get_moderation_flags(
+        &self,
+        page: i64,
+        limit: i64,
+    ) -> anyhow::Result<(Vec<ModerationFlagRecord>, u64)> {
+        let rec = sqlx::query_as!(
+            ModerationFlagRecord,
+            r#"
+            SELECT id, kind, subject, details, created_at
+            FROM moderation_flags
+            ORDER BY created_at DESC, id DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            page * limit
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        let total = sqlx::query!(r#"SELECT COUNT(*) as count FROM moderation_flags"#)
+            .fetch_one(&self.0)
+            .await?;
+
+        Ok((rec, total.count.unwrap_or_default() as u64))
+    }
 }
 
 async fn run_migrations(rocket: Rocket<Build>) -> fairing::Result {
@@ -944,3 +1163,43 @@ pub fn stage() -> AdHoc {
             .attach(AdHoc::try_on_ignite("SQLx Migrations", run_migrations))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::DB;
+
+    async fn login(pool: &PgPool, user_id: i32) -> String {
+        sqlx::query_scalar("SELECT login FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[ignore = "needs a database in DATABASE_URL"]
+    #[sqlx::test(migrations = "./migrations")]
+    async fn users_can_swap_logins(pool: PgPool) {
+        let mut tx = pool.begin().await.unwrap();
+        DB::upsert_user(&mut tx, 1, "alice", 0, None).await.unwrap();
+        DB::upsert_user(&mut tx, 2, "bob", 0, None).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        DB::upsert_user(&mut tx, 1, "bob", 0, None).await.unwrap();
+        DB::upsert_user(&mut tx, 2, "alice", 0, None).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(login(&pool, 1).await, "bob");
+        assert_eq!(login(&pool, 2).await, "alice");
+
+        // The handle of a renamed user is taken by a new one before the rename is synced
+        let mut tx = pool.begin().await.unwrap();
+        DB::upsert_user(&mut tx, 3, "bob", 0, None).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(login(&pool, 3).await, "bob");
+        assert_eq!(login(&pool, 1).await, "bob#1");
+    }
+}
@@ -20,6 +20,7 @@ pub struct LeaderboardRecord {
     pub monthly_streak_amount: i32,
     pub place: i64,
     pub permanent_bonus: i32,
+    pub rank: Option<String>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize, Default)]
@@ -56,9 +57,11 @@ pub struct UserRecord {
     pub first_contribution: chrono::NaiveDateTime,
     pub name: Option<String>,
     pub lifetime_percent: i32,
+    pub rank: Option<String>,
     pub period_data: Vec<UserPeriodRecord>,
     pub streaks: Vec<StreakRecord>,
     pub leaderboard_places: Vec<(String, u32)>,
+    pub adjustments: Vec<AdjustmentRecord>,
 }
 
 impl UserRecord {
@@ -69,9 +72,11 @@ impl UserRecord {
             first_contribution: chrono::Utc::now().naive_utc(),
             name: None,
             lifetime_percent: 0,
+            rank: None,
             period_data: vec![],
             streaks: vec![],
             leaderboard_places: vec![],
+            adjustments: vec![],
         }
     }
 
@@ -126,6 +131,20 @@ pub struct UserContributionRecord {
     pub merged_at: Option<chrono::NaiveDateTime>,
 }
 
+impl UserContributionRecord {
+    pub fn status(&self) -> &'static str {
+        if self.executed {
+            "Finished"
+        } else if self.score.is_none() {
+            "Waiting for score"
+        } else if self.merged_at.is_none() {
+            "Waiting for merge"
+        } else {
+            "Waiting for execution"
+        }
+    }
+}
+
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
 pub struct RepoRecord {
     pub organization: String,
@@ -167,5 +186,42 @@ pub struct HallOfFameRecord {
     pub login: String,
     pub full_name: Option<String>,
     pub permanent_bonus: i32,
+    pub rank: Option<String>,
     pub total_rating: i32,
 }
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct VoteRecord {
+    pub pull_request_id: i32,
+    pub author: GithubHandle,
+    pub voter: GithubHandle,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DiffStatsRecord {
+    pub id: i32,
+    pub organization: String,
+    pub repo: String,
+    pub number: i32,
+    pub author: GithubHandle,
+    pub additions: Option<i32>,
+    pub deletions: Option<i32>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ModerationFlagRecord {
+    pub id: i32,
+    pub kind: String,
+    pub subject: String,
+    pub details: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct AdjustmentRecord {
+    pub period_type: TimePeriodString,
+    pub changes: String,
+    pub reason: String,
+    pub adjusted_at: chrono::NaiveDateTime,
+}
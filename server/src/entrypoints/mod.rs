@@ -3,6 +3,7 @@ use utoipa::OpenApi;
 
 pub mod aliases;
 pub mod leaderboards;
+pub mod moderation;
 pub mod statistics;
 pub mod user;
 
@@ -20,7 +21,8 @@ use race_of_sloths_server::types;
         user::get_user,
         user::get_user_contributions,
         user::get_badge,
-        statistics::get_statistics
+        statistics::get_statistics,
+        moderation::get_moderation_queue
     ),
     components(schemas(
         types::PaginatedResponse<types::LeaderboardResponse>,
@@ -29,12 +31,16 @@ use race_of_sloths_server::types;
         types::PaginatedRepoResponse,
         types::PaginatedResponse<types::UserContributionResponse>,
         types::PaginatedUserContributionResponse,
+        types::PaginatedResponse<types::ModerationFlagResponse>,
+        types::PaginatedModerationFlagResponse,
+        types::ModerationFlagResponse,
         types::UserContributionResponse,
         types::LeaderboardResponse,
         types::RepoResponse,
         types::UserProfile,
         types::GithubMeta,
         types::Streak,
+        types::Adjustment,
         types::Statistics
     )),
     tags(
@@ -43,12 +49,13 @@ use race_of_sloths_server::types;
 )]
 pub struct ApiDoc;
 
-pub fn stage(font: String) -> AdHoc {
+pub fn stage(font: String, moderation_token: Option<String>) -> AdHoc {
     AdHoc::on_ignite("Installing entrypoints", |rocket| async {
         rocket
             .attach(user::stage(font))
             .attach(leaderboards::stage())
             .attach(aliases::stage())
             .attach(statistics::stage())
+            .attach(moderation::stage(moderation_token))
     })
 }
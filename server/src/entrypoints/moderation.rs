@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use race_of_sloths_server::{
+    db::DB,
+    types::{ModerationFlagResponse, PaginatedResponse},
+};
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    serde::json::Json,
+    Request, State,
+};
+use sha2::{Digest, Sha256};
+use shared::telegram::TelegramSubscriber;
+use subtle::ConstantTimeEq;
+
+/// Hash of the moderation token, so the comparison doesn't depend on the length of the provided one
+pub struct ModerationToken([u8; 32]);
+
+impl ModerationToken {
+    pub fn new(token: &str) -> Self {
+        Self(Sha256::digest(token.as_bytes()).into())
+    }
+
+    fn matches(&self, token: &str) -> bool {
+        Self::new(token).0.ct_eq(&self.0).into()
+    }
+}
+
+/// Moderation queue names suspected users, so it's available only with the token
+pub struct Moderator;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Moderator {
+    type Error = ();
+
+    // The response is the same whether the token is wrong or isn't configured at all
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let provided = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "));
+        let token = req.rocket().state::<ModerationToken>();
+        match (token, provided) {
+            (Some(token), Some(provided)) if token.matches(provided) => Outcome::Success(Moderator),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+#[utoipa::path(context_path = "/moderation", responses(
+    (status = 200, description = "Get items flagged by the anti-gaming analysis", body = PaginatedModerationFlagResponse)
+))]
+#[get("/queue?<page>&<limit>")]
+async fn get_moderation_queue(
+    _moderator: Moderator,
+    db: &State<DB>,
+    telegram: &State<Arc<TelegramSubscriber>>,
+    page: Option<u64>,
+    limit: Option<u64>,
+) -> Option<Json<PaginatedResponse<ModerationFlagResponse>>> {
+    let page = page.unwrap_or(0);
+    let limit = limit.unwrap_or(50);
+    let (records, total) = match db.get_moderation_flags(page as i64, limit as i64).await {
+        Err(e) => {
+            race_of_sloths_server::error(telegram, &format!("Failed to get moderation queue: {e}"));
+            return None;
+        }
+        Ok(value) => value,
+    };
+    Some(Json(PaginatedResponse::new(
+        records.into_iter().map(Into::into).collect(),
+        page + 1,
+        limit,
+        total,
+    )))
+}
+
+pub fn stage(token: Option<String>) -> rocket::fairing::AdHoc {
+    rocket::fairing::AdHoc::on_ignite("Installing entrypoints", |rocket| async {
+        let rocket = rocket.mount("/moderation", rocket::routes![get_moderation_queue]);
+        match token {
+            Some(token) => rocket.manage(ModerationToken::new(&token)),
+            None => rocket,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModerationToken;
+
+    #[test]
+    fn token_is_compared_by_hash() {
+        let token = ModerationToken::new("secret");
+        assert!(token.matches("secret"));
+        assert!(!token.matches("secret "));
+        assert!(!token.matches(""));
+    }
+}
@@ -4,7 +4,7 @@ use race_of_sloths_server::db::DB;
 use rocket::{serde::json::Json, State};
 use shared::telegram::TelegramSubscriber;
 
-use super::types::Statistics;
+use super::types::{ProviderUrls, Statistics};
 
 #[utoipa::path(context_path = "/info", responses(
     (status = 200, description = "Get application statistics", body = Statistics)
@@ -13,6 +13,7 @@ use super::types::Statistics;
 async fn get_statistics(
     telegram: &State<Arc<TelegramSubscriber>>,
     db: &State<DB>,
+    urls: &State<ProviderUrls>,
 ) -> Option<Json<Statistics>> {
     let statistics = db.statistics().await;
     let Ok(statistics) = statistics else {
@@ -22,7 +23,7 @@ async fn get_statistics(
         );
         return None;
     };
-    Some(Json(statistics.into()))
+    Some(Json(Statistics::new(statistics, urls)))
 }
 
 pub fn stage() -> rocket::fairing::AdHoc {
@@ -19,7 +19,7 @@ use rocket::{
 use shared::{telegram, TimePeriod};
 use std::ops::Add;
 
-use super::types::{PaginatedResponse, UserContributionResponse, UserProfile};
+use super::types::{PaginatedResponse, ProviderUrls, UserContributionResponse, UserProfile};
 
 pub struct Badge {
     svg: Option<String>,
@@ -308,6 +308,7 @@ async fn get_user_contributions(
     limit: Option<u64>,
     db: &State<DB>,
     telegram: &State<Arc<telegram::TelegramSubscriber>>,
+    urls: &State<ProviderUrls>,
 ) -> Option<Json<PaginatedResponse<UserContributionResponse>>> {
     let page = page.unwrap_or(0);
     let limit = limit.unwrap_or(50);
@@ -325,7 +326,10 @@ async fn get_user_contributions(
         Ok(value) => value,
     };
     Some(Json(PaginatedResponse::new(
-        repos.into_iter().map(Into::into).collect(),
+        repos
+            .into_iter()
+            .map(|record| UserContributionResponse::new(record, urls))
+            .collect(),
         page + 1,
         limit,
         total,
@@ -7,7 +7,7 @@ use anyhow::Context;
 use octocrab::{models::pulls::PullRequest, Octocrab};
 use rocket::fairing::AdHoc;
 use rocket_db_pools::Database;
-use shared::telegram::TelegramSubscriber;
+use shared::{telegram::TelegramSubscriber, Provider};
 use sqlx::{Postgres, Transaction};
 use tracing::instrument;
 
@@ -24,6 +24,14 @@ pub struct GithubClient {
     pub octocrab: Octocrab,
 }
 
+// Organizations and users from other providers are namespaced and can't be fetched from GitHub
+pub fn is_github_login(login: &str) -> bool {
+    matches!(
+        Provider::from_organization(login),
+        Some((Provider::GitHub, _))
+    )
+}
+
 impl GithubClient {
     pub fn new(github_token: String) -> anyhow::Result<Self> {
         let octocrab = octocrab::Octocrab::builder()
@@ -89,6 +97,20 @@ impl GithubClient {
         Ok(ret.collect())
     }
 
+    #[instrument(skip(self))]
+    pub async fn pull_request_diff_stats(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> anyhow::Result<(u32, u32)> {
+        let pr = self.octocrab.pulls(org, repo).get(number).await?;
+        Ok((
+            pr.additions.unwrap_or_default() as u32,
+            pr.deletions.unwrap_or_default() as u32,
+        ))
+    }
+
     #[instrument(skip(self))]
     pub async fn get_user(&self, username: &str) -> anyhow::Result<octocrab::models::UserProfile> {
         Ok(self.octocrab.users(username).profile().await?)
@@ -104,7 +126,10 @@ async fn fetch_repos_metadata(
     let repos = DB::get_repos_for_update(tx)
         .await
         .context("Failed to ger repos")?;
-    for repo in repos {
+    for repo in repos
+        .into_iter()
+        .filter(|repo| is_github_login(&repo.organization))
+    {
         let metadata = match github.repo_metadata(&repo.organization, &repo.repo).await {
             Ok(metadata) => metadata,
             Err(e) => {
@@ -139,7 +164,10 @@ async fn fetch_missing_user_organization_metadata(
     tx: &mut Transaction<'static, Postgres>,
 ) -> anyhow::Result<()> {
     let users = DB::get_users_for_update(tx).await.unwrap_or_default();
-    for user in users {
+    for user in users
+        .into_iter()
+        .filter(|user| is_github_login(&user.login))
+    {
         let profile = match github.get_user(&user.login).await {
             Ok(profile) => profile,
             Err(e) => {
@@ -160,7 +188,7 @@ async fn fetch_missing_user_organization_metadata(
     let orgs = DB::get_organizations_for_update(tx)
         .await
         .unwrap_or_default();
-    for org in orgs {
+    for org in orgs.into_iter().filter(|org| is_github_login(&org.login)) {
         let profile = match github.get_user(&org.login).await {
             Ok(profile) => profile,
             Err(e) => {
@@ -183,6 +211,47 @@ async fn fetch_missing_user_organization_metadata(
     Ok(())
 }
 
+// Diff size doesn't change much after the PR is included, so it's fetched once.
+// Each PR is committed on its own, so no transaction is kept open while waiting for GitHub
+#[instrument(skip(telegram, github, db))]
+async fn fetch_missing_pr_diff_stats(
+    telegram: &Arc<TelegramSubscriber>,
+    github: &GithubClient,
+    db: &DB,
+) -> anyhow::Result<()> {
+    let prs = db
+        .get_prs_diff_stats(true)
+        .await
+        .context("Failed to get PRs without diff stats")?;
+    for pr in prs
+        .into_iter()
+        .filter(|pr| is_github_login(&pr.organization))
+    {
+        let (additions, deletions) = match github
+            .pull_request_diff_stats(&pr.organization, &pr.repo, pr.number as u64)
+            .await
+        {
+            Ok(stats) => stats,
+            Err(e) => {
+                crate::error(
+                    telegram,
+                    &format!(
+                        "Failed to fetch diff stats for {}/{}/{}: {:#?}",
+                        pr.organization, pr.repo, pr.number, e
+                    ),
+                );
+                continue;
+            }
+        };
+        let mut tx = db.begin().await?;
+        DB::update_pr_diff_stats(&mut tx, pr.id, additions, deletions)
+            .await
+            .context("Failed to update PR diff stats")?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
 pub async fn fetch_github_data(
     telegram: &Arc<TelegramSubscriber>,
     github: &GithubClient,
@@ -202,6 +271,10 @@ pub async fn fetch_github_data(
 
     tx.commit().await?;
 
+    fetch_missing_pr_diff_stats(telegram, github, db)
+        .await
+        .context("Failed on fetching and updating PR diff stats")?;
+
     Ok(())
 }
 
@@ -6,6 +6,7 @@ pub mod contract_pull;
 pub mod db;
 pub mod github_pull;
 pub mod health_monitor;
+pub mod moderation;
 pub mod svg;
 pub mod types;
 pub mod weekly_stats;
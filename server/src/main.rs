@@ -12,7 +12,7 @@ use rocket_cors::AllowedOrigins;
 use shared::{near::NearClient, telegram};
 
 use race_of_sloths_server::{
-    contract_pull, db, github_pull, health_monitor::HealthMonitor, weekly_stats,
+    contract_pull, db, github_pull, health_monitor::HealthMonitor, moderation, types, weekly_stats,
 };
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
@@ -29,6 +29,9 @@ pub struct Env {
     telegram_token: String,
     telegram_chat_id: String,
     font: String,
+    moderation_token: Option<String>,
+    moderation_timeout_in_hours: Option<u64>,
+    gitlab_url: Option<String>,
 }
 
 // Allow robots to crawl the site
@@ -61,6 +64,8 @@ async fn rocket() -> _ {
     let near_sleep = Duration::from_secs(env.near_timeout_in_seconds.unwrap_or(20));
     let github_sleep = Duration::from_secs(env.github_timeout_in_minutes.unwrap_or(60) as u64 * 60);
     let weekly_sleep = Duration::from_secs(WEEK_IN_SECONDS);
+    let moderation_sleep =
+        Duration::from_secs(env.moderation_timeout_in_hours.unwrap_or(24) * 60 * 60);
     let atomic_bool = Arc::new(std::sync::atomic::AtomicBool::new(true));
     let prometheus = rocket_prometheus::PrometheusMetrics::new();
 
@@ -111,6 +116,7 @@ async fn rocket() -> _ {
             atomic_bool.clone(),
         ))
         .attach(weekly_stats::stage(weekly_sleep, atomic_bool.clone()))
+        .attach(moderation::stage(moderation_sleep, atomic_bool.clone()))
         .attach(rocket::fairing::AdHoc::on_shutdown(
             "Stop loading users from Near and Github metadata",
             |_| {
@@ -125,10 +131,11 @@ async fn rocket() -> _ {
         )
         .mount("/", routes![robots, favicon])
         .attach(prometheus.clone())
-        .attach(entrypoints::stage(env.font))
+        .attach(entrypoints::stage(env.font, env.moderation_token))
         .mount("/metrics", prometheus)
         .manage(telegram)
         .manage(health_monitor)
+        .manage(types::ProviderUrls::new(env.gitlab_url))
         .attach(AdHoc::on_response(
             "Telegram notification about failed resposnes",
             |req, resp: &mut rocket::Response<'_>| {
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+use anyhow::Context;
+use itertools::Itertools;
+use rocket::fairing::AdHoc;
+use rocket_db_pools::Database;
+use shared::{telegram::TelegramSubscriber, GithubHandle};
+use tracing::Level;
+
+use crate::db::{
+    types::{DiffStatsRecord, VoteRecord},
+    DB,
+};
+
+/// Both users scored the PRs of each other at least this many times
+const RECIPROCAL_MIN_VOTES: usize = 3;
+/// Scorers are compared only once they have given this many votes
+const OUTLIER_MIN_VOTES: usize = 5;
+/// How many standard deviations the average score of a scorer can be away from the others
+const OUTLIER_DEVIATIONS: f64 = 2.0;
+/// PRs changing this many lines or less are flagged
+const TINY_DIFF_LINES: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagKind {
+    ReciprocalScoring,
+    OutlierScorer,
+    TinyDiff,
+}
+
+impl FlagKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReciprocalScoring => "reciprocal_scoring",
+            Self::OutlierScorer => "outlier_scorer",
+            Self::TinyDiff => "tiny_diff",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Flag {
+    pub kind: FlagKind,
+    // The same subject is flagged only once per kind
+    pub subject: String,
+    pub details: String,
+}
+
+pub fn reciprocal_pairs(votes: &[VoteRecord]) -> Vec<Flag> {
+    let mut given: HashMap<(&GithubHandle, &GithubHandle), Vec<i32>> = HashMap::new();
+    for vote in votes.iter().filter(|vote| vote.voter != vote.author) {
+        given
+            .entry((&vote.voter, &vote.author))
+            .or_default()
+            .push(vote.score);
+    }
+
+    given
+        .iter()
+        .filter(|((voter, author), _)| voter < author)
+        .filter_map(|((voter, author), scores)| {
+            let returned = given.get(&(*author, *voter))?;
+            if scores.len() < RECIPROCAL_MIN_VOTES || returned.len() < RECIPROCAL_MIN_VOTES {
+                return None;
+            }
+            Some(Flag {
+                kind: FlagKind::ReciprocalScoring,
+                subject: format!("{voter}<->{author}"),
+                details: format!(
+                    "{voter} scored {} PRs of {author} (avg {:.1}), {author} scored {} PRs of {voter} (avg {:.1})",
+                    scores.len(),
+                    average(scores),
+                    returned.len(),
+                    average(returned),
+                ),
+            })
+        })
+        .sorted_by(|a, b| a.subject.cmp(&b.subject))
+        .collect()
+}
+
+// Scorers whose average is far from the averages of the other scorers
+pub fn outlier_scorers(votes: &[VoteRecord]) -> Vec<Flag> {
+    let averages: Vec<(&GithubHandle, usize, f64)> = votes
+        .iter()
+        .into_group_map_by(|vote| &vote.voter)
+        .into_iter()
+        .filter(|(_, votes)| votes.len() >= OUTLIER_MIN_VOTES)
+        .map(|(voter, votes)| {
+            let scores = votes.iter().map(|vote| vote.score).collect::<Vec<_>>();
+            (voter, scores.len(), average(&scores))
+        })
+        .collect();
+    if averages.len() < 2 {
+        return vec![];
+    }
+
+    let mean = averages.iter().map(|(_, _, avg)| avg).sum::<f64>() / averages.len() as f64;
+    let deviation = (averages
+        .iter()
+        .map(|(_, _, avg)| (avg - mean).powi(2))
+        .sum::<f64>()
+        / averages.len() as f64)
+        .sqrt();
+    if deviation == 0.0 {
+        return vec![];
+    }
+
+    averages
+        .into_iter()
+        .filter(|(_, _, avg)| (avg - mean).abs() > OUTLIER_DEVIATIONS * deviation)
+        .map(|(voter, count, avg)| Flag {
+            kind: FlagKind::OutlierScorer,
+            subject: voter.clone(),
+            details: format!(
+                "{voter} gave {count} votes with avg {avg:.1}, while scorers average {mean:.1}±{deviation:.1}"
+            ),
+        })
+        .sorted_by(|a, b| a.subject.cmp(&b.subject))
+        .collect()
+}
+
+pub fn tiny_diffs(prs: &[DiffStatsRecord]) -> Vec<Flag> {
+    prs.iter()
+        .filter_map(|pr| {
+            let lines = pr.additions? + pr.deletions?;
+            (lines <= TINY_DIFF_LINES).then(|| Flag {
+                kind: FlagKind::TinyDiff,
+                subject: format!("{}/{}/{}", pr.organization, pr.repo, pr.number),
+                details: format!("PR by {} changes {lines} lines", pr.author),
+            })
+        })
+        .collect()
+}
+
+fn average(scores: &[i32]) -> f64 {
+    scores.iter().sum::<i32>() as f64 / scores.len() as f64
+}
+
+async fn analyze(db: &DB, telegram: &TelegramSubscriber) -> anyhow::Result<()> {
+    let votes = db.get_votes().await.context("Failed to get votes")?;
+    let prs = db
+        .get_prs_diff_stats(false)
+        .await
+        .context("Failed to get PR diff stats")?;
+
+    let mut new_flags = vec![];
+    for flag in reciprocal_pairs(&votes)
+        .into_iter()
+        .chain(outlier_scorers(&votes))
+        .chain(tiny_diffs(&prs))
+    {
+        if db
+            .insert_moderation_flag(flag.kind.as_str(), &flag.subject, &flag.details)
+            .await
+            .context("Failed to store moderation flag")?
+        {
+            new_flags.push(flag);
+        }
+    }
+
+    if new_flags.is_empty() {
+        return Ok(());
+    }
+
+    let message = [format!(
+        "{} new items in the moderation queue:",
+        new_flags.len()
+    )]
+    .into_iter()
+    .chain(
+        new_flags
+            .iter()
+            .map(|flag| format!("- {}: {}", flag.kind.as_str(), flag.details)),
+    )
+    .join("\n");
+    telegram.send_to_telegram(&message, &Level::WARN);
+
+    Ok(())
+}
+
+pub fn stage(sleep_duration: Duration, atomic_bool: Arc<AtomicBool>) -> AdHoc {
+    AdHoc::on_liftoff("Anti-gaming analysis", move |rocket| {
+        Box::pin(async move {
+            // Get an actual DB connection
+            let db = DB::fetch(rocket)
+                .expect("Failed to get DB connection")
+                .clone();
+            let telegram: Arc<TelegramSubscriber> = rocket
+                .state()
+                .cloned()
+                .expect("Failed to get telegram client");
+
+            rocket::tokio::spawn(async move {
+                let mut interval = rocket::tokio::time::interval(sleep_duration);
+                while atomic_bool.load(std::sync::atomic::Ordering::Relaxed) {
+                    interval.tick().await;
+
+                    if let Err(e) = analyze(&db, &telegram).await {
+                        crate::error(&telegram, &format!("Anti-gaming analysis error: {e:#}"));
+                    }
+                }
+            });
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::types::{DiffStatsRecord, VoteRecord};
+
+    use super::{outlier_scorers, reciprocal_pairs, tiny_diffs, FlagKind};
+
+    fn vote(voter: &str, author: &str, score: i32) -> VoteRecord {
+        VoteRecord {
+            pull_request_id: 0,
+            author: author.to_string(),
+            voter: voter.to_string(),
+            score,
+        }
+    }
+
+    fn votes(voter: &str, author: &str, scores: &[i32]) -> Vec<VoteRecord> {
+        scores
+            .iter()
+            .map(|score| vote(voter, author, *score))
+            .collect()
+    }
+
+    fn pr(number: i32, additions: Option<i32>, deletions: Option<i32>) -> DiffStatsRecord {
+        DiffStatsRecord {
+            id: number,
+            organization: "org".to_string(),
+            repo: "repo".to_string(),
+            number,
+            author: "author".to_string(),
+            additions,
+            deletions,
+        }
+    }
+
+    #[test]
+    fn reciprocal_pairs_need_minimum_votes_both_ways() {
+        let mut all = votes("alice", "bob", &[13, 13, 13]);
+        all.extend(votes("bob", "alice", &[13, 13]));
+        assert!(reciprocal_pairs(&all).is_empty());
+
+        all.push(vote("bob", "alice", 13));
+        let flags = reciprocal_pairs(&all);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].kind, FlagKind::ReciprocalScoring);
+        assert_eq!(flags[0].subject, "alice<->bob");
+    }
+
+    #[test]
+    fn reciprocal_pairs_ignore_self_votes_and_one_way_scoring() {
+        let mut all = votes("alice", "alice", &[13, 13, 13]);
+        all.extend(votes("alice", "bob", &[5, 5, 5, 5]));
+        all.extend(votes("carol", "alice", &[5, 5, 5]));
+        assert!(reciprocal_pairs(&all).is_empty());
+    }
+
+    #[test]
+    fn outliers_need_minimum_votes() {
+        let mut all = votes("alice", "x", &[5, 5, 5, 5, 5]);
+        all.extend(votes("bob", "x", &[5, 5, 5, 5, 5]));
+        all.extend(votes("carol", "x", &[5, 5, 5, 5, 5]));
+        all.extend(votes("dave", "x", &[5, 5, 5, 5, 5]));
+        all.extend(votes("eve", "x", &[5, 5, 5, 5, 5]));
+        all.extend(votes("generous", "x", &[13, 13, 13, 13]));
+        assert!(outlier_scorers(&all).is_empty());
+
+        all.push(vote("generous", "x", 13));
+        let flags = outlier_scorers(&all);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].kind, FlagKind::OutlierScorer);
+        assert_eq!(flags[0].subject, "generous");
+    }
+
+    #[test]
+    fn outliers_need_deviation_and_other_scorers() {
+        let all = votes("alice", "x", &[13, 13, 13, 13, 13]);
+        assert!(outlier_scorers(&all).is_empty());
+
+        let mut all = votes("alice", "x", &[5, 5, 5, 5, 5]);
+        all.extend(votes("bob", "x", &[5, 5, 5, 5, 5]));
+        assert!(outlier_scorers(&all).is_empty());
+    }
+
+    #[test]
+    fn tiny_diffs_are_flagged_once_stats_are_known() {
+        let prs = [
+            pr(1, Some(1), Some(1)),
+            pr(2, Some(2), Some(1)),
+            pr(3, None, Some(0)),
+            pr(4, Some(0), None),
+        ];
+        let flags = tiny_diffs(&prs);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].kind, FlagKind::TinyDiff);
+        assert_eq!(flags[0].subject, "org/repo/1");
+    }
+}
@@ -5,10 +5,7 @@ use rocket::{tokio::fs::read_to_string, FromFormField};
 use shared::{telegram::TelegramSubscriber, TimePeriod};
 use usvg::{fontdb, Options, Tree, WriteOptions};
 
-use crate::{
-    db::types::{StreakRecord, UserCachedMetadata, UserContributionRecord, UserRecord},
-    types::UserContributionResponse,
-};
+use crate::db::types::{StreakRecord, UserCachedMetadata, UserContributionRecord, UserRecord};
 
 #[derive(Debug, Clone, Copy, FromFormField)]
 pub enum Mode {
@@ -259,15 +256,13 @@ async fn process_contribution(
     svg_icon: String,
     contribution: UserContributionRecord,
 ) -> anyhow::Result<String> {
-    let contribution = UserContributionResponse::from(contribution);
-
     let svg = match contribution.executed {
         true => "public/pr_state/finalized.svg",
         false => "public/pr_state/in-progress.svg",
     };
     let svg = read_to_string(svg).await?;
 
-    let contribution_text = match (contribution.total_rating, contribution.executed) {
+    let contribution_text = match (contribution.rating, contribution.executed) {
         (rating, true) => {
             format!("This is the way, sloth! You've got {rating} points!")
         }
@@ -280,7 +275,7 @@ async fn process_contribution(
     Ok(svg_icon
         .replace(
             "{pr-status-title}",
-            &format!("Your PR status: {}", contribution.status),
+            &format!("Your PR status: {}", contribution.status()),
         )
         .replace("{pr-status-text}", &contribution_text)
         .replace("{pr-status-svg}", &svg))
@@ -367,25 +362,26 @@ fn image_processing(
 }
 
 async fn rank_data(user_record: &UserRecord) -> (String, String, String) {
-    let (rank, rank_svg_file, title) = match user_record.lifetime_percent {
-        a if a >= 25 => ("Rust".to_string(), "rust.svg", "Rank"),
-        a if a >= 20 => ("Platinum".to_string(), "platinum.svg", "Rank"),
-        a if a >= 15 => ("Gold".to_string(), "gold.svg", "Rank"),
-        a if a >= 10 => ("Silver".to_string(), "silver.svg", "Rank"),
-        a if a >= 5 => ("Bronze".to_string(), "bronze.svg", "Rank"),
-        _ => {
+    let (rank, rank_svg_file, title) = match &user_record.rank {
+        Some(rank) => (rank.clone(), format!("{}.svg", rank.to_lowercase()), "Rank"),
+        None => {
             let age = user_record.first_contribution;
             let current_time = chrono::Utc::now().naive_utc();
             let days = (current_time - age).num_days() + 1;
             let day_suffix = if days > 1 { "days" } else { "day" };
-            (format!("{days} {day_suffix}"), "unranked.svg", "Sloth age")
+            (
+                format!("{days} {day_suffix}"),
+                "unranked.svg".to_string(),
+                "Sloth age",
+            )
         }
     };
-    (
-        rank,
-        read_to_string(format!("./public/ranks/{}", rank_svg_file))
+    // Tiers added in the contract might not have an icon yet
+    let rank_svg = match read_to_string(format!("./public/ranks/{}", rank_svg_file)).await {
+        Ok(svg) => svg,
+        Err(_) => read_to_string("./public/ranks/unranked.svg")
             .await
             .unwrap_or_default(),
-        title.to_string(),
-    )
+    };
+    (rank, rank_svg, title.to_string())
 }
@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
 use crate::db::types::{
-    HallOfFameRecord, LeaderboardRecord, RepoLeaderboardRecord, UserContributionRecord, UserRecord,
+    AdjustmentRecord, HallOfFameRecord, LeaderboardRecord, ModerationFlagRecord,
+    RepoLeaderboardRecord, UserContributionRecord, UserRecord,
 };
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
-use shared::TimePeriod;
+use shared::{Provider, TimePeriod};
 use utoipa::ToSchema;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, ToSchema)]
@@ -13,7 +14,8 @@ use utoipa::ToSchema;
     PaginatedLeaderboardResponse = PaginatedResponse<LeaderboardResponse>,
     PaginatedHallOfFameResponse = PaginatedResponse<HallOfFameResponse>,
     PaginatedRepoResponse = PaginatedResponse<RepoResponse>,
-    PaginatedUserContributionResponse = PaginatedResponse<UserContributionResponse>
+    PaginatedUserContributionResponse = PaginatedResponse<UserContributionResponse>,
+    PaginatedModerationFlagResponse = PaginatedResponse<ModerationFlagResponse>
 )]
 pub struct PaginatedResponse<T: Serialize> {
     pub records: Vec<T>,
@@ -51,9 +53,52 @@ impl GithubMeta {
     }
 }
 
+// Organizations are namespaced by the provider in the contract
+fn split_organization(organization: String) -> (Provider, String) {
+    match Provider::from_organization(&organization) {
+        Some((provider, name)) => (provider, name.to_string()),
+        None => (Provider::GitHub, organization),
+    }
+}
+
+/// Base URLs of the providers, GitLab can be a self-hosted instance
+#[derive(Clone, Debug)]
+pub struct ProviderUrls {
+    pub gitlab: String,
+}
+
+impl ProviderUrls {
+    pub fn new(gitlab: Option<String>) -> Self {
+        Self {
+            gitlab: gitlab
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| Provider::GITLAB_URL.to_string()),
+        }
+    }
+
+    pub fn base_url(&self, provider: Provider) -> &str {
+        match provider {
+            Provider::GitHub => provider.default_url(),
+            Provider::GitLab => &self.gitlab,
+        }
+    }
+}
+
+fn pull_request_link(
+    urls: &ProviderUrls,
+    organization: String,
+    repo: &str,
+    number: i32,
+) -> (Provider, String, String) {
+    let (source, organization) = split_organization(organization);
+    let link = source.pull_request_url(urls.base_url(source), &organization, repo, number as u64);
+    (source, organization, link)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct RepoResponse {
     pub name: String,
+    pub source: String,
     pub organization: GithubMeta,
     pub repo_language: Option<String>,
     pub stars: u32,
@@ -67,9 +112,11 @@ pub struct RepoResponse {
 
 impl From<RepoLeaderboardRecord> for RepoResponse {
     fn from(record: RepoLeaderboardRecord) -> Self {
+        let (source, organization) = split_organization(record.organization);
         Self {
             name: record.name,
-            organization: GithubMeta::new(record.organization, record.organization_full_name),
+            source: source.to_string(),
+            organization: GithubMeta::new(organization, record.organization_full_name),
             repo_language: record.primary_language,
             stars: record.stars.unwrap_or_default() as u32,
             forks: record.forks.unwrap_or_default() as u32,
@@ -99,16 +146,9 @@ pub struct LeaderboardResponse {
     pub scored_prs: u32,
 }
 
-fn get_rank(permanent_bonus: u32) -> String {
-    match permanent_bonus {
-        a if a >= 25 => "Rust",
-        a if a >= 20 => "Platinum",
-        a if a >= 15 => "Gold",
-        a if a >= 10 => "Silver",
-        a if a >= 5 => "Bronze",
-        _ => "Unranked",
-    }
-    .to_string()
+// Ranks are calculated by the contract
+fn rank_or_unranked(rank: Option<String>) -> String {
+    rank.unwrap_or_else(|| "Unranked".to_string())
 }
 
 impl From<LeaderboardRecord> for LeaderboardResponse {
@@ -139,7 +179,7 @@ impl From<LeaderboardRecord> for LeaderboardResponse {
             merged_prs: record.prs_merged as u32,
             score: record.total_score as u32,
             place: record.place as u32,
-            rank: get_rank(record.permanent_bonus as u32),
+            rank: rank_or_unranked(record.rank),
             scored_prs: record.prs_scored as u32,
         }
     }
@@ -228,6 +268,7 @@ pub struct UserProfile {
     pub leaderboard_places: HashMap<String, u32>,
     pub streaks: Vec<Streak>,
     pub first_contribution: NaiveDateTime,
+    pub adjustments: Vec<Adjustment>,
 }
 
 impl From<UserRecord> for UserProfile {
@@ -256,6 +297,27 @@ impl From<UserRecord> for UserProfile {
                 .collect(),
             leaderboard_places: record.leaderboard_places.into_iter().collect(),
             first_contribution: record.first_contribution,
+            adjustments: record.adjustments.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Manual correction of the user's statistics with the reason
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct Adjustment {
+    pub period: String,
+    pub changes: String,
+    pub reason: String,
+    pub adjusted_at: NaiveDateTime,
+}
+
+impl From<AdjustmentRecord> for Adjustment {
+    fn from(record: AdjustmentRecord) -> Self {
+        Self {
+            period: record.period_type,
+            changes: record.changes,
+            reason: record.reason,
+            adjusted_at: record.adjusted_at,
         }
     }
 }
@@ -263,6 +325,7 @@ impl From<UserRecord> for UserProfile {
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserContributionResponse {
     pub pull_request_link: String,
+    pub source: String,
     pub repository: String,
     pub organization: GithubMeta,
     pub executed: bool,
@@ -276,27 +339,17 @@ pub struct UserContributionResponse {
     pub merged_at: Option<NaiveDateTime>,
 }
 
-impl From<UserContributionRecord> for UserContributionResponse {
-    fn from(record: UserContributionRecord) -> Self {
-        let pull_request_link = format!(
-            "https://github.com/{}/{}/pull/{}",
-            record.organization_login, record.repo, record.number
-        );
-
-        let status = if record.executed {
-            "Finished"
-        } else if record.score.is_none() {
-            "Waiting for score"
-        } else if record.merged_at.is_none() {
-            "Waiting for merge"
-        } else {
-            "Waiting for execution"
-        };
+impl UserContributionResponse {
+    pub fn new(record: UserContributionRecord, urls: &ProviderUrls) -> Self {
+        let status = record.status();
+        let (source, organization, pull_request_link) =
+            pull_request_link(urls, record.organization_login, &record.repo, record.number);
 
         Self {
             pull_request_link,
+            source: source.to_string(),
             repository: record.repo,
-            organization: GithubMeta::new(record.organization_login, record.organization_full_name),
+            organization: GithubMeta::new(organization, record.organization_full_name),
             status: status.to_string(),
             score: record.score,
             created_at: record.included_at,
@@ -323,8 +376,8 @@ pub struct Statistics {
     pub hall_of_fame: Vec<GithubMeta>,
 }
 
-impl From<crate::db::types::Statistics> for Statistics {
-    fn from(value: crate::db::types::Statistics) -> Self {
+impl Statistics {
+    pub fn new(value: crate::db::types::Statistics, urls: &ProviderUrls) -> Self {
         let duration = value
             .fastest_merged
             .map(|x| x - value.fastest_included.unwrap_or_default())
@@ -349,12 +402,13 @@ impl From<crate::db::types::Statistics> for Statistics {
                 value.highest_sloth_rating.unwrap_or_default() as u32,
             ),
             shortest_merge_time: (
-                format!(
-                    "https://github.com/{}/{}/pull/{}",
+                pull_request_link(
+                    urls,
                     value.fastest_org_login,
-                    value.fastest_repo_name,
-                    value.fastest_pr_number.unwrap_or_default()
-                ),
+                    &value.fastest_repo_name,
+                    value.fastest_pr_number.unwrap_or_default(),
+                )
+                .2,
                 duration.to_string(),
             ),
             number_of_famed_sloths: hall_of_fame.len() as u32,
@@ -379,7 +433,28 @@ impl From<HallOfFameRecord> for HallOfFameResponse {
             user: GithubMeta::new(record.login, record.full_name),
             sloths_points: record.total_rating as u32,
             permanent_bonus: record.permanent_bonus as u32,
-            rank: get_rank(record.permanent_bonus as u32),
+            rank: rank_or_unranked(record.rank),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationFlagResponse {
+    pub id: u32,
+    pub kind: String,
+    pub subject: String,
+    pub details: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ModerationFlagRecord> for ModerationFlagResponse {
+    fn from(record: ModerationFlagRecord) -> Self {
+        Self {
+            id: record.id as u32,
+            kind: record.kind,
+            subject: record.subject,
+            details: record.details,
+            created_at: record.created_at,
         }
     }
 }
@@ -10,7 +10,10 @@ use rocket_db_pools::Database;
 use shared::{telegram::TelegramSubscriber, GithubHandle};
 use tracing::Level;
 
-use crate::{db::DB, github_pull::GithubClient};
+use crate::{
+    db::DB,
+    github_pull::{is_github_login, GithubClient},
+};
 
 async fn calculate_pr_stats(
     db: &DB,
@@ -22,7 +25,7 @@ async fn calculate_pr_stats(
     let projects = db.get_projects().await?;
     let mut project_stats = Vec::with_capacity(projects.len());
     let mut user_stats = std::collections::HashMap::<GithubHandle, (u32, u32)>::new();
-    for (org, repo) in projects {
+    for (org, repo) in projects.into_iter().filter(|(org, _)| is_github_login(org)) {
         let prs = github
             .pull_requests_for_period(&org, &repo, start_period)
             .await?;
@@ -0,0 +1,136 @@
+use near_sdk::Timestamp;
+
+use super::*;
+
+/// Signed changes of the period data. Omitted fields are not changed
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde", default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct UserPeriodDataDelta {
+    pub total_score: i64,
+    pub executed_prs: i64,
+    pub largest_score: i64,
+    pub prs_opened: i64,
+    pub prs_merged: i64,
+    pub total_rating: i64,
+    pub largest_rating_per_pr: i64,
+    pub prs_scored: i64,
+}
+
+impl UserPeriodDataDelta {
+    fn fields(&self) -> [(&'static str, i64); 8] {
+        [
+            ("total_score", self.total_score),
+            ("executed_prs", self.executed_prs),
+            ("largest_score", self.largest_score),
+            ("prs_opened", self.prs_opened),
+            ("prs_merged", self.prs_merged),
+            ("total_rating", self.total_rating),
+            ("largest_rating_per_pr", self.largest_rating_per_pr),
+            ("prs_scored", self.prs_scored),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().iter().all(|(_, delta)| *delta == 0)
+    }
+
+    // Returns None if any of the values would go out of range
+    pub fn apply(&self, data: &UserPeriodDataV2) -> Option<UserPeriodDataV2> {
+        let add = |value: u32, delta: i64| {
+            i64::from(value)
+                .checked_add(delta)
+                .and_then(|value| u32::try_from(value).ok())
+        };
+        Some(UserPeriodDataV2 {
+            total_score: add(data.total_score, self.total_score)?,
+            executed_prs: add(data.executed_prs, self.executed_prs)?,
+            largest_score: add(data.largest_score, self.largest_score)?,
+            prs_opened: add(data.prs_opened, self.prs_opened)?,
+            prs_merged: add(data.prs_merged, self.prs_merged)?,
+            total_rating: add(data.total_rating, self.total_rating)?,
+            largest_rating_per_pr: add(data.largest_rating_per_pr, self.largest_rating_per_pr)?,
+            prs_scored: add(data.prs_scored, self.prs_scored)?,
+        })
+    }
+}
+
+// Lists only the changed fields, e.g. `total_rating -50, prs_opened -1`
+impl std::fmt::Display for UserPeriodDataDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let changes = self
+            .fields()
+            .into_iter()
+            .filter(|(_, delta)| *delta != 0)
+            .map(|(name, delta)| format!("{name} {delta:+}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", changes.join(", "))
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Adjustment {
+    pub period: TimePeriodString,
+    pub delta: UserPeriodDataDelta,
+    pub reason: String,
+    pub moderator: AccountId,
+    pub adjusted_at: Timestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserPeriodDataDelta, UserPeriodDataV2};
+
+    #[test]
+    fn delta_is_applied_within_range() {
+        let data = UserPeriodDataV2 {
+            total_rating: 100,
+            prs_opened: 2,
+            ..Default::default()
+        };
+        let delta = UserPeriodDataDelta {
+            total_rating: -50,
+            prs_opened: -1,
+            ..Default::default()
+        };
+        let adjusted = delta.apply(&data).unwrap();
+        assert_eq!(adjusted.total_rating, 50);
+        assert_eq!(adjusted.prs_opened, 1);
+        assert_eq!(delta.to_string(), "prs_opened -1, total_rating -50");
+
+        let delta = UserPeriodDataDelta {
+            prs_opened: -3,
+            ..Default::default()
+        };
+        assert!(delta.apply(&data).is_none());
+
+        let delta = UserPeriodDataDelta {
+            total_rating: i64::MAX,
+            ..Default::default()
+        };
+        assert!(delta.apply(&data).is_none());
+    }
+}
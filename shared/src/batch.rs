@@ -0,0 +1,59 @@
+use near_sdk::Timestamp;
+
+use super::*;
+
+/// Calls of the bot that can be applied together with `sloth_batch`.
+/// The fields are the arguments of the method applying the action on its own
+#[derive(Debug, Clone, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SlothAction {
+    Include {
+        organization: String,
+        repo: String,
+        user: GithubHandle,
+        pr_number: u64,
+        created_at: Timestamp,
+        override_exclude: bool,
+        scale: Option<ScoringScale>,
+        github_id: Option<u64>,
+    },
+    Scored {
+        pr_id: PRId,
+        user: GithubHandle,
+        score: u32,
+        github_id: Option<u64>,
+    },
+    Merged {
+        pr_id: PRId,
+        merged_at: Timestamp,
+    },
+    Stale {
+        pr_id: PRId,
+    },
+    Finalize {
+        pr_id: PRId,
+        active_pr: Option<(bool, GithubHandle)>,
+        timestamp: Option<Timestamp>,
+        closed_issues: Option<Vec<u64>>,
+    },
+}
+
+impl SlothAction {
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            Self::Include { .. } => "sloth_include",
+            Self::Scored { .. } => "sloth_scored",
+            Self::Merged { .. } => "sloth_merged",
+            Self::Stale { .. } => "sloth_stale",
+            Self::Finalize { .. } => "sloth_finalize",
+        }
+    }
+}
+
+/// Outcome of a single action of the batch
+#[derive(Debug, Clone, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SlothActionResult {
+    Applied { events: Vec<Event> },
+    Rejected { reason: String },
+}
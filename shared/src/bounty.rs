@@ -0,0 +1,41 @@
+use near_sdk::Timestamp;
+
+use super::*;
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedBounty {
+    V1(Bounty),
+}
+
+impl From<VersionedBounty> for Bounty {
+    fn from(message: VersionedBounty) -> Self {
+        match message {
+            VersionedBounty::V1(x) => x,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Bounty {
+    pub organization: String,
+    pub repo: String,
+    pub issue_number: u64,
+    // Bonus score that is added to the PR that closes the issue
+    pub score: u32,
+    pub created_by: GithubHandle,
+    pub created_at: Timestamp,
+}
@@ -1,8 +1,11 @@
-use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    serde::{Deserialize, Serialize},
+    NearSchema,
+};
 
-use crate::StreakId;
+use crate::{PRId, PRv6, StreakId};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, NearSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub enum Event {
     NewSloth {
@@ -25,4 +28,25 @@ pub enum Event {
     Autoscored {
         score: u32,
     },
+    BountyClaimed {
+        issue_number: u64,
+        score: u32,
+    },
+    CoauthorExecutedWithRating {
+        github_handle: String,
+        rating: u32,
+        applied_multiplier: u32,
+    },
+    Revoked {
+        pr_id: PRId,
+        reason: String,
+    },
+    // The author reached a higher rank tier with the lifetime bonus applied on finalize
+    RankUp {
+        rank: String,
+    },
+    // Archived PRs are kept only in the event trail for the indexers
+    Archived {
+        pr: PRv6,
+    },
 }
@@ -1,10 +1,12 @@
-use crate::PRv2;
+use crate::{PRv6, Provider};
 use octocrab::models::AuthorAssociation;
 
 #[derive(Debug, Clone)]
 pub struct User {
     pub login: String,
     pub contributor_type: AuthorAssociation,
+    // GitHub numeric id that survives renames. Unknown for other providers and the stored PRs
+    pub id: Option<u64>,
 }
 
 impl User {
@@ -12,6 +14,14 @@ impl User {
         Self {
             login,
             contributor_type,
+            id: None,
+        }
+    }
+
+    pub fn with_id(self, id: u64) -> Self {
+        Self {
+            id: Some(id),
+            ..self
         }
     }
 
@@ -40,6 +50,19 @@ pub struct RepoInfo {
 }
 
 impl RepoInfo {
+    pub fn provider(&self) -> Provider {
+        Provider::from_organization(&self.owner)
+            .map(|(provider, _)| provider)
+            .unwrap_or_default()
+    }
+
+    // Organization name without the provider prefix
+    pub fn organization_name(&self) -> &str {
+        Provider::from_organization(&self.owner)
+            .map(|(_, name)| name)
+            .unwrap_or(&self.owner)
+    }
+
     pub fn from_issue(
         issue: octocrab::models::issues::Issue,
         repo: octocrab::models::Repository,
@@ -66,10 +89,16 @@ pub struct PrMetadata {
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub body: String,
     pub closed: bool,
+    // Drafts are included once they are ready for review
+    pub draft: bool,
+    // Last commit of the PR. Unknown for the PRs restored from the contract
+    pub head_sha: Option<String>,
+    // Label names. Unknown for the PRs restored from the contract
+    pub labels: Option<Vec<String>>,
 }
 
-impl From<PRv2> for PrMetadata {
-    fn from(pr: PRv2) -> Self {
+impl From<PRv6> for PrMetadata {
+    fn from(pr: PRv6) -> Self {
         let full_id = format!("{}/{}/{}", pr.organization, pr.repo, pr.number);
         Self {
             repo_info: RepoInfo {
@@ -90,6 +119,9 @@ impl From<PRv2> for PrMetadata {
                 pr.merged_at.or(pr.created_at).unwrap_or(pr.included_at) as i64,
             ),
             closed: false,
+            draft: false,
+            head_sha: None,
+            labels: None,
         }
     }
 }
@@ -127,11 +159,20 @@ impl TryFrom<octocrab::models::pulls::PullRequest> for PrMetadata {
                     full_id,
                 },
                 body,
-                author: User::new(user.login, author_association),
+                author: User::new(user.login, author_association).with_id(user.id.0),
                 created: created_at,
                 merged: pr.merged_at,
                 updated_at,
                 closed: pr.closed_at.is_some(),
+                draft: pr.draft.unwrap_or_default(),
+                head_sha: Some(pr.head.sha),
+                labels: Some(
+                    pr.labels
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|label| label.name)
+                        .collect(),
+                ),
             })
         } else {
             Err(anyhow::anyhow!("Missing required fields"))
@@ -6,8 +6,14 @@ use near_sdk::{
     AccountId, NearSchema,
 };
 
+mod adjustment;
+mod batch;
+mod bounty;
 mod event;
 mod pr;
+mod provider;
+mod rank;
+mod scale;
 mod streak;
 mod timeperiod;
 
@@ -22,8 +28,14 @@ pub mod telegram;
 #[cfg(feature = "client")]
 pub mod near;
 
+pub use adjustment::*;
+pub use batch::*;
+pub use bounty::*;
 pub use event::*;
 pub use pr::*;
+pub use provider::*;
+pub use rank::*;
+pub use scale::*;
 pub use streak::*;
 pub use timeperiod::*;
 
@@ -44,7 +56,17 @@ impl From<VersionedAccount> for AccountWithPermanentPercentageBonus {
     }
 }
 
-#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
 #[serde(crate = "near_sdk::serde")]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct FlatBonusStorage {
@@ -134,16 +156,63 @@ impl AccountWithPermanentPercentageBonus {
     }
 
     // Use bonus reward if result >= streak_min and remove it from the list
-    pub fn use_flat_bonus(&mut self, streak_id: StreakId, result: u32) -> u32 {
-        if let Some((index, _)) = self
+    pub fn use_flat_bonus(&mut self, streak_id: StreakId, result: u32) -> Option<FlatBonusStorage> {
+        let index = self
             .flat_bonus
             .iter()
-            .enumerate()
-            .find(|(_, bonus)| bonus.streak_id == streak_id && result >= bonus.streak_min)
-        {
-            self.flat_bonus.swap_remove(index).reward
-        } else {
-            0
+            .position(|bonus| bonus.streak_id == streak_id && result >= bonus.streak_min)?;
+        Some(self.flat_bonus.swap_remove(index))
+    }
+
+    // Flat bonuses that the account didn't have in `before`
+    pub fn flat_bonuses_since(&self, before: &Self) -> Vec<FlatBonusStorage> {
+        self.flat_bonus
+            .iter()
+            .filter(|bonus| !before.flat_bonus.contains(bonus))
+            .cloned()
+            .collect()
+    }
+
+    // Lifetime bonuses that grew since `before` with the percent they had there, zero if they are new
+    pub fn percent_bonuses_since(&self, before: &Self) -> Vec<(StreakId, u32)> {
+        self.permanent_percentage_bonus
+            .iter()
+            .filter_map(|bonus| {
+                let old = before
+                    .permanent_percentage_bonus
+                    .iter()
+                    .find(|old| old.streak_id == bonus.streak_id)
+                    .map(|old| old.percent)
+                    .unwrap_or_default();
+                (bonus.percent > old).then_some((bonus.streak_id, old))
+            })
+            .collect()
+    }
+
+    // Gives back the used flat bonuses and takes away the earned ones and the raised lifetime bonuses.
+    // Earned flat bonuses that are already used by another PR stay with it
+    pub fn revert_bonuses(
+        &mut self,
+        used: &[FlatBonusStorage],
+        earned: &[FlatBonusStorage],
+        raised: &[(StreakId, u32)],
+    ) {
+        for bonus in used {
+            self.add_flat_bonus(bonus.streak_id, bonus.reward, bonus.streak_min);
+        }
+        self.flat_bonus.retain(|bonus| !earned.contains(bonus));
+
+        for (streak_id, old) in raised {
+            if *old == 0 {
+                self.permanent_percentage_bonus
+                    .retain(|bonus| bonus.streak_id != *streak_id);
+            } else if let Some(bonus) = self
+                .permanent_percentage_bonus
+                .iter_mut()
+                .find(|bonus| bonus.streak_id == *streak_id)
+            {
+                bonus.percent = *old;
+            }
         }
     }
 
@@ -198,30 +267,27 @@ impl VersionedUserPeriodData {
         *self = Self::V2(data);
     }
 
-    pub fn pr_scored(&mut self, old_score: u32, new_score: u32) {
+    // The rating is split between participants, but the score is credited to each of them.
+    // The largest values are updated on finalization, as the votes can be retracted until then
+    pub fn pr_scored(&mut self, old_score: u32, new_score: u32, participants: u32, author: bool) {
         let mut data: UserPeriodDataV2 = self.clone().into();
         data.total_score += new_score;
         data.total_score -= old_score;
 
-        let rating = new_score * 10;
-        data.total_rating += rating;
-        data.total_rating -= old_score * 10;
-
-        if new_score > data.largest_score {
-            data.largest_score = new_score;
-        }
-
-        if rating > data.largest_rating_per_pr {
-            data.largest_rating_per_pr = rating;
-        }
+        data.total_rating += split_rating(new_score * 10, participants, author);
+        data.total_rating -= split_rating(old_score * 10, participants, author);
 
         *self = Self::V2(data);
     }
 
-    pub fn pr_executed(&mut self) {
+    pub fn pr_executed(&mut self, score: u32) {
         let mut data: UserPeriodDataV2 = self.clone().into();
         data.executed_prs += 1;
 
+        if score > data.largest_score {
+            data.largest_score = score;
+        }
+
         *self = Self::V2(data);
     }
 
@@ -237,11 +303,22 @@ impl VersionedUserPeriodData {
         *self = Self::V2(data);
     }
 
-    pub fn pr_closed(&mut self, score: u32) {
+    // Reverts a finalized PR, the values that can't be recalculated such as the largest score are kept
+    pub fn pr_revoked(&mut self, score: u32, rating: u32) {
+        let mut data: UserPeriodDataV2 = self.clone().into();
+        data.prs_opened = data.prs_opened.saturating_sub(1);
+        data.prs_merged = data.prs_merged.saturating_sub(1);
+        data.executed_prs = data.executed_prs.saturating_sub(1);
+        data.total_score = data.total_score.saturating_sub(score);
+        data.total_rating = data.total_rating.saturating_sub(rating);
+        *self = Self::V2(data);
+    }
+
+    pub fn pr_closed(&mut self, score: u32, participants: u32, author: bool) {
         let mut data: UserPeriodDataV2 = self.clone().into();
         data.prs_opened -= 1;
         data.total_score -= score;
-        data.total_rating -= score * 10;
+        data.total_rating -= split_rating(score * 10, participants, author);
         *self = Self::V2(data);
     }
 }
@@ -322,6 +399,9 @@ pub struct User {
     pub percentage_bonus: u32,
     pub period_data: Vec<(TimePeriodString, UserPeriodDataV2)>,
     pub streaks: Vec<(StreakId, StreakUserData)>,
+    // Name of the reached rank tier, if any
+    #[serde(default)]
+    pub rank: Option<String>,
 }
 
 impl User {
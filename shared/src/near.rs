@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::bail;
 use near_api::{signer::Signer, types::Data, Contract, NetworkConfig};
@@ -6,18 +9,34 @@ use near_primitives::{
     types::BlockReference,
     views::{FinalExecutionOutcomeView, FinalExecutionStatus},
 };
+use near_sdk::{Gas, Timestamp};
 use serde_json::json;
-use tracing::instrument;
+use tokio::sync::oneshot;
+use tracing::{instrument, warn};
 
-use super::github::PrMetadata;
+use super::github::{PrMetadata, RepoInfo, User as GithubUser};
 
 use crate::*;
 
+/// Actions sent by the concurrent tasks within this window are applied in one transaction
+const BATCH_WINDOW: Duration = Duration::from_secs(3);
+/// Keeps a batch of finalizations within the gas limit of a transaction
+const MAX_BATCH_SIZE: usize = 20;
+const BATCH_GAS_IN_TGAS: u64 = 300;
+
+type PendingAction = (SlothAction, oneshot::Sender<anyhow::Result<Vec<Event>>>);
+
+#[derive(Default)]
+struct Batcher {
+    pending: Mutex<Vec<PendingAction>>,
+}
+
 #[derive(Clone)]
 pub struct NearClient {
     network: NetworkConfig,
     signer: Arc<Signer>,
     contract: Contract,
+    batcher: Option<Arc<Batcher>>,
 }
 
 impl NearClient {
@@ -43,6 +62,7 @@ impl NearClient {
             network,
             signer,
             contract,
+            batcher: None,
         })
     }
 
@@ -51,72 +71,163 @@ impl NearClient {
         &self,
         pr: &PrMetadata,
         is_maintainer: bool,
+        scale: Option<ScoringScale>,
     ) -> anyhow::Result<Vec<Event>> {
+        self.send_action(SlothAction::Include {
+            organization: pr.repo_info.owner.clone(),
+            repo: pr.repo_info.repo.clone(),
+            user: pr.author.login.clone(),
+            pr_number: pr.repo_info.number,
+            created_at: pr.created.timestamp_nanos_opt().unwrap_or(0) as Timestamp,
+            override_exclude: is_maintainer,
+            scale,
+            github_id: pr.author.id,
+        })
+        .await
+    }
+
+    #[instrument(skip(self, pr, user), fields(pr = pr.repo_info.full_id, user = user.login, score))]
+    pub async fn send_scored(
+        &self,
+        pr: &PrMetadata,
+        user: &GithubUser,
+        score: u64,
+    ) -> anyhow::Result<Vec<Event>> {
+        self.send_action(SlothAction::Scored {
+            pr_id: pr.repo_info.full_id.clone(),
+            user: user.login.clone(),
+            score: score as u32,
+            github_id: user.id,
+        })
+        .await
+    }
+
+    #[instrument(skip(self), fields(pr = pr.repo_info.full_id, user))]
+    pub async fn send_unscored(&self, pr: &PrMetadata, user: &str) -> anyhow::Result<Vec<Event>> {
         let args = json!({
-            "organization": pr.repo_info.owner,
-            "repo": pr.repo_info.repo,
-            "pr_number": pr.repo_info.number,
-            "user": pr.author.login,
-            "created_at": pr.created.timestamp_nanos_opt().unwrap_or(0),
-            "override_exclude": is_maintainer,
+            "pr_id": pr.repo_info.full_id,
+            "user": user,
         });
 
         let result = self
             .contract
-            .call_function("sloth_include", args)?
+            .call_function("sloth_unscored", args)?
             .transaction()
             .with_signer(self.contract.0.clone(), self.signer.clone())
             .send_to(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call sloth_include: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_unscored: {:?}", e))?;
 
         process_execution_final_result(result)
     }
 
-    #[instrument(skip(self), fields(pr = pr.repo_info.full_id, user, score))]
-    pub async fn send_scored(
+    #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
+    pub async fn send_merge(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        let Some(merged) = pr.merged else {
+            bail!("PR is not merged")
+        };
+
+        self.send_action(SlothAction::Merged {
+            pr_id: pr.repo_info.full_id.clone(),
+            merged_at: merged.timestamp_nanos_opt().unwrap_or(0) as Timestamp,
+        })
+        .await
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
+    pub async fn send_coauthors(
         &self,
         pr: &PrMetadata,
-        user: &str,
-        score: u64,
+        coauthors: &[GithubHandle],
+        github_ids: &[Option<u64>],
     ) -> anyhow::Result<Vec<Event>> {
         let args = json!({
             "pr_id": pr.repo_info.full_id,
-            "user": user,
-            "score": score,
+            "coauthors": coauthors,
+            "github_ids": github_ids,
         });
 
         let result = self
             .contract
-            .call_function("sloth_scored", args)?
+            .call_function("sloth_coauthors", args)?
             .transaction()
             .with_signer(self.contract.0.clone(), self.signer.clone())
             .send_to(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call sloth_scored: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_coauthors: {:?}", e))?;
 
         process_execution_final_result(result)
     }
 
-    #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
-    pub async fn send_merge(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
-        if pr.merged.is_none() {
-            bail!("PR is not merged")
-        }
+    #[instrument(skip(self))]
+    pub async fn send_locale(
+        &self,
+        user: &GithubUser,
+        locale: Option<&str>,
+    ) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "user": user.login,
+            "locale": locale,
+            "github_id": user.id,
+        });
+
+        let result = self
+            .contract
+            .call_function("sloth_locale", args)?
+            .transaction()
+            .with_signer(self.contract.0.clone(), self.signer.clone())
+            .send_to(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_locale: {:?}", e))?;
+
+        process_execution_final_result(result)
+    }
 
+    #[instrument(skip(self, user), fields(user = user.login))]
+    pub async fn send_auto_include(
+        &self,
+        user: &GithubUser,
+        enabled: bool,
+    ) -> anyhow::Result<Vec<Event>> {
         let args = json!({
-            "pr_id": pr.repo_info.full_id,
-            "merged_at": pr.merged.unwrap().timestamp_nanos_opt().unwrap_or(0),
+            "user": user.login,
+            "enabled": enabled,
+            "github_id": user.id,
         });
 
         let result = self
             .contract
-            .call_function("sloth_merged", args)?
+            .call_function("sloth_auto_include", args)?
             .transaction()
             .with_signer(self.contract.0.clone(), self.signer.clone())
             .send_to(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call sloth_merged: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_auto_include: {:?}", e))?;
+
+        process_execution_final_result(result)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn send_rename(
+        &self,
+        github_id: u64,
+        old_handle: &str,
+        new_handle: &str,
+    ) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "github_id": github_id,
+            "old_handle": old_handle,
+            "new_handle": new_handle,
+        });
+
+        let result = self
+            .contract
+            .call_function("sloth_rename", args)?
+            .transaction()
+            .with_signer(self.contract.0.clone(), self.signer.clone())
+            .send_to(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_rename: {:?}", e))?;
 
         process_execution_final_result(result)
     }
@@ -184,13 +295,13 @@ impl NearClient {
     }
 
     #[instrument(skip(self))]
-    pub async fn unmerged_prs(&self, page: u64, limit: u64) -> anyhow::Result<Vec<PRv2>> {
+    pub async fn unmerged_prs(&self, from: Option<PRId>, limit: u64) -> anyhow::Result<Vec<PRv6>> {
         let args = json!({
-            "page": page,
+            "from": from,
             "limit": limit,
         });
 
-        let res: Data<Vec<PRv2>> = self
+        let res: Data<Vec<PRv6>> = self
             .contract
             .call_function("unmerged_prs", args)?
             .read_only()
@@ -201,12 +312,106 @@ impl NearClient {
     }
 
     #[instrument(skip(self))]
-    pub async fn unmerged_prs_all(&self) -> anyhow::Result<Vec<PRv2>> {
+    pub async fn linked_users(
+        &self,
+        from: UserId,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(UserId, u64, GithubHandle)>> {
+        let args = json!({
+            "from": from,
+            "limit": limit,
+        });
+
+        let res: Data<Vec<(UserId, u64, GithubHandle)>> = self
+            .contract
+            .call_function("linked_users", args)?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call linked_users: {:?}", e))?;
+        Ok(res.data)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn unmerged_prs_all(&self) -> anyhow::Result<Vec<PRv6>> {
+        let mut from = None;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        loop {
+            let prs = self.unmerged_prs(from, LIMIT).await?;
+            let Some(last) = prs.last() else {
+                break;
+            };
+            from = Some(last.pr_id());
+            res.extend(prs);
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn unfinalized_prs(
+        &self,
+        from: Option<(Timestamp, PRId)>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<PRv6>> {
+        let args = json!({
+            "from": from,
+            "limit": limit,
+        });
+
+        let res: Data<Vec<PRv6>> = self
+            .contract
+            .call_function("unfinalized_prs", args)?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call unfinalized_prs: {:?}", e))?;
+        Ok(res.data)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn unfinalized_prs_all(&self) -> anyhow::Result<Vec<PRv6>> {
+        let mut from = None;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        loop {
+            let prs = self.unfinalized_prs(from, LIMIT).await?;
+            let Some((last, ready_at)) = prs
+                .last()
+                .and_then(|pr| Some((pr, pr.ready_to_move_timestamp()?)))
+            else {
+                break;
+            };
+            from = Some((ready_at, last.pr_id()));
+            res.extend(prs);
+        }
+        Ok(res)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn stale_prs(&self, page: u64, limit: u64) -> anyhow::Result<Vec<(PRv6, Timestamp)>> {
+        let args = json!({
+            "page": page,
+            "limit": limit,
+        });
+
+        let res: Data<Vec<(PRv6, Timestamp)>> = self
+            .contract
+            .call_function("stale_prs", args)?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call stale_prs: {:?}", e))?;
+        Ok(res.data)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn stale_prs_all(&self) -> anyhow::Result<Vec<(PRv6, Timestamp)>> {
         let mut page = 0;
         const LIMIT: u64 = 100;
         let mut res = vec![];
         loop {
-            let prs = self.unmerged_prs(page, LIMIT).await?;
+            let prs = self.stale_prs(page, LIMIT).await?;
             if prs.is_empty() {
                 break;
             }
@@ -217,33 +422,37 @@ impl NearClient {
     }
 
     #[instrument(skip(self))]
-    pub async fn unfinalized_prs(&self, page: u64, limit: u64) -> anyhow::Result<Vec<PRv2>> {
+    pub async fn adjustments(
+        &self,
+        page: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(GithubHandle, Adjustment)>> {
         let args = json!({
             "page": page,
             "limit": limit,
         });
 
-        let res: Data<Vec<PRv2>> = self
+        let res: Data<Vec<(GithubHandle, Adjustment)>> = self
             .contract
-            .call_function("unfinalized_prs", args)?
+            .call_function("adjustments", args)?
             .read_only()
             .fetch_from(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call unfinalized_prs: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call adjustments: {:?}", e))?;
         Ok(res.data)
     }
 
     #[instrument(skip(self))]
-    pub async fn unfinalized_prs_all(&self) -> anyhow::Result<Vec<PRv2>> {
+    pub async fn adjustments_all(&self) -> anyhow::Result<Vec<(GithubHandle, Adjustment)>> {
         let mut page = 0;
         const LIMIT: u64 = 100;
         let mut res = vec![];
         loop {
-            let prs = self.unfinalized_prs(page, LIMIT).await?;
-            if prs.is_empty() {
+            let adjustments = self.adjustments(page, LIMIT).await?;
+            if adjustments.is_empty() {
                 break;
             }
-            res.extend(prs);
+            res.extend(adjustments);
             page += 1;
         }
         Ok(res)
@@ -251,21 +460,91 @@ impl NearClient {
 
     #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
     pub async fn send_stale(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
+        self.send_action(SlothAction::Stale {
+            pr_id: pr.repo_info.full_id.clone(),
+        })
+        .await
+    }
+
+    #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
+    pub async fn send_unstale(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
         let args = json!({
             "pr_id": pr.repo_info.full_id,
         });
 
         let result = self
             .contract
-            .call_function("sloth_stale", args)?
+            .call_function("sloth_unstale", args)?
             .transaction()
             .with_signer(self.contract.0.clone(), self.signer.clone())
             .send_to(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call sloth_stale: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_unstale: {:?}", e))?;
         process_execution_final_result(result)
     }
 
+    // Returns the archived PRs
+    #[instrument(skip(self))]
+    pub async fn send_archive(&self, before: Timestamp, limit: u32) -> anyhow::Result<Vec<PRv6>> {
+        let args = json!({
+            "before": before,
+            "limit": limit,
+        });
+
+        let result = self
+            .contract
+            .call_function("sloth_archive", args)?
+            .transaction()
+            .with_signer(self.contract.0.clone(), self.signer.clone())
+            .send_to(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_archive: {:?}", e))?;
+        Ok(process_execution_final_result(result)?
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Archived { pr } => Some(pr),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn revoked_prs(
+        &self,
+        page: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(PRv6, Revocation)>> {
+        let args = json!({
+            "page": page,
+            "limit": limit,
+        });
+
+        let res: Data<Vec<(PRv6, Revocation)>> = self
+            .contract
+            .call_function("revoked_prs", args)?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call revoked_prs: {:?}", e))?;
+        Ok(res.data)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn revoked_prs_all(&self) -> anyhow::Result<Vec<(PRv6, Revocation)>> {
+        let mut page = 0;
+        const LIMIT: u64 = 100;
+        let mut res = vec![];
+        loop {
+            let prs = self.revoked_prs(page, LIMIT).await?;
+            if prs.is_empty() {
+                break;
+            }
+            res.extend(prs);
+            page += 1;
+        }
+        Ok(res)
+    }
+
     #[instrument(skip(self, pr), fields(pr = pr.repo_info.full_id))]
     pub async fn send_exclude(&self, pr: &PrMetadata) -> anyhow::Result<Vec<Event>> {
         let args = json!({
@@ -288,21 +567,168 @@ impl NearClient {
         &self,
         pr_id: &str,
         active_pr: Option<(bool, GithubHandle)>,
+        closed_issues: Vec<u64>,
     ) -> anyhow::Result<Vec<Event>> {
+        self.send_action(SlothAction::Finalize {
+            pr_id: pr_id.to_string(),
+            active_pr,
+            timestamp: None,
+            closed_issues: Some(closed_issues),
+        })
+        .await
+    }
+
+    #[instrument(skip(self, actions), fields(actions = actions.len()))]
+    pub async fn send_batch(
+        &self,
+        actions: Vec<SlothAction>,
+    ) -> anyhow::Result<Vec<SlothActionResult>> {
         let result = self
             .contract
-            .call_function(
-                "sloth_finalize",
-                json!({
-                    "pr_id": pr_id,
-                    "active_pr": active_pr
-                }),
-            )?
+            .call_function("sloth_batch", json!({ "actions": actions }))?
+            .transaction()
+            .gas(Gas::from_tgas(BATCH_GAS_IN_TGAS))
+            .with_signer(self.contract.0.clone(), self.signer.clone())
+            .send_to(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_batch: {:?}", e))?;
+
+        let FinalExecutionStatus::SuccessValue(value) = &result.status else {
+            bail!("Execution failure: {:?}", result);
+        };
+        Ok(serde_json::from_slice(value)?)
+    }
+
+    /// Returns a client that sends the actions supported by `sloth_batch` together.
+    /// Each action waits for the batch window, so it's meant for jobs running many PRs at once
+    pub fn batched(&self) -> Self {
+        Self {
+            batcher: Some(Default::default()),
+            ..self.clone()
+        }
+    }
+
+    async fn send_action(&self, action: SlothAction) -> anyhow::Result<Vec<Event>> {
+        if let Some(batcher) = &self.batcher {
+            return self.send_batched(batcher, action).await;
+        }
+        self.send_single(action).await
+    }
+
+    async fn send_single(&self, action: SlothAction) -> anyhow::Result<Vec<Event>> {
+        let method = action.method_name();
+        // The only field of the serialized variant holds the arguments of the method
+        let args = match serde_json::to_value(&action)? {
+            serde_json::Value::Object(variant) => variant.into_iter().next().map(|(_, args)| args),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Unexpected arguments of {method}"))?;
+
+        let result = self
+            .contract
+            .call_function(method, args)?
+            .transaction()
+            .with_signer(self.contract.0.clone(), self.signer.clone())
+            .send_to(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call {method}: {:?}", e))?;
+        process_execution_final_result(result)
+    }
+
+    // The first action of the window waits for the others and sends them all
+    async fn send_batched(
+        &self,
+        batcher: &Batcher,
+        action: SlothAction,
+    ) -> anyhow::Result<Vec<Event>> {
+        let (sender, receiver) = oneshot::channel();
+        let first = {
+            let mut pending = batcher.pending.lock().unwrap();
+            pending.push((action, sender));
+            pending.len() == 1
+        };
+
+        if first {
+            tokio::time::sleep(BATCH_WINDOW).await;
+            let mut pending = std::mem::take(&mut *batcher.pending.lock().unwrap());
+            let mut batches = vec![];
+            while !pending.is_empty() {
+                batches.push(
+                    pending
+                        .drain(..pending.len().min(MAX_BATCH_SIZE))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            // Stack of the batches in the sending order
+            batches.reverse();
+            while let Some(mut batch) = batches.pop() {
+                if batch.len() == 1 {
+                    // A single call reports the reason if it's the action that breaks the batch
+                    let (action, sender) = batch.remove(0);
+                    let _ = sender.send(self.send_single(action).await);
+                    continue;
+                }
+
+                let (actions, senders): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+                match self.send_batch(actions.clone()).await {
+                    Ok(results) => {
+                        for ((sender, action), result) in
+                            senders.into_iter().zip(actions).zip(results)
+                        {
+                            let method = action.method_name();
+                            let _ = sender.send(match result {
+                                SlothActionResult::Applied { events } => Ok(events),
+                                SlothActionResult::Rejected { reason } => {
+                                    Err(anyhow::anyhow!("Failed to call {method}: {reason}"))
+                                }
+                            });
+                        }
+                    }
+                    // The whole transaction is reverted, e.g. an action panics or runs out of gas.
+                    // The halves are retried, so only the broken action fails in the end
+                    Err(e) => {
+                        warn!(
+                            "Splitting the failed batch of {} actions: {e:#}",
+                            actions.len()
+                        );
+                        let mut batch: Vec<PendingAction> =
+                            actions.into_iter().zip(senders).collect();
+                        let second = batch.split_off(batch.len() / 2);
+                        batches.push(second);
+                        batches.push(batch);
+                    }
+                }
+            }
+        }
+
+        receiver.await?
+    }
+
+    #[instrument(skip(self, repo_info), fields(issue = repo_info.full_id))]
+    pub async fn send_bounty(
+        &self,
+        repo_info: &RepoInfo,
+        user: &str,
+        score: u32,
+        scale: ScoringScale,
+    ) -> anyhow::Result<Vec<Event>> {
+        let args = json!({
+            "organization": repo_info.owner,
+            "repo": repo_info.repo,
+            "issue_number": repo_info.number,
+            "user": user,
+            "score": score,
+            "scale": scale,
+        });
+
+        let result = self
+            .contract
+            .call_function("sloth_bounty", args)?
             .transaction()
             .with_signer(self.contract.0.clone(), self.signer.clone())
             .send_to(&self.network)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to call sloth_finalize: {:?}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to call sloth_bounty: {:?}", e))?;
         process_execution_final_result(result)
     }
 
@@ -344,6 +770,30 @@ impl NearClient {
         Ok(res.data)
     }
 
+    #[instrument(skip(self))]
+    pub async fn user_locale(&self, user: &str) -> anyhow::Result<Option<String>> {
+        let res: Data<Option<String>> = self
+            .contract
+            .call_function("user_locale", json!({ "user": user }))?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call user_locale: {:?}", e))?;
+        Ok(res.data)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn user_auto_include(&self, user: &str) -> anyhow::Result<bool> {
+        let res: Data<bool> = self
+            .contract
+            .call_function("user_auto_include", json!({ "user": user }))?
+            .read_only()
+            .fetch_from(&self.network)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to call user_auto_include: {:?}", e))?;
+        Ok(res.data)
+    }
+
     pub async fn users_paged(
         &self,
         page: u64,
@@ -384,8 +834,8 @@ impl NearClient {
     }
 
     #[instrument(skip(self))]
-    pub async fn prs_paged(&self, page: u64, limit: u64) -> anyhow::Result<Vec<(PRv2, bool)>> {
-        let res: Data<Vec<(PRv2, bool)>> = self
+    pub async fn prs_paged(&self, page: u64, limit: u64) -> anyhow::Result<Vec<(PRv6, bool)>> {
+        let res: Data<Vec<(PRv6, bool)>> = self
             .contract
             .call_function(
                 "prs",
@@ -402,7 +852,7 @@ impl NearClient {
     }
 
     #[instrument(skip(self))]
-    pub async fn prs(&self) -> anyhow::Result<Vec<(PRv2, bool)>> {
+    pub async fn prs(&self) -> anyhow::Result<Vec<(PRv6, bool)>> {
         let mut page = 0;
         const LIMIT: u64 = 250;
         let mut res = vec![];
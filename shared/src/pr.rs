@@ -1,4 +1,4 @@
-use near_sdk::Timestamp;
+use near_sdk::{AccountId, Timestamp};
 
 use super::*;
 
@@ -7,6 +7,20 @@ pub type PRId = String;
 pub const SCORE_TIMEOUT_IN_SECONDS: Timestamp = 24 * 60 * 60;
 pub const SCORE_TIMEOUT_IN_NANOSECONDS: Timestamp = SCORE_TIMEOUT_IN_SECONDS * 1_000_000_000;
 
+pub const MAX_COAUTHORS: usize = 5;
+
+// Splits the base rating of the PR between all the participants (author and co-authors).
+// Shares are rounded down and the author receives the remainder, so they add up to the rating
+pub fn split_rating(rating: u32, participants: u32, author: bool) -> u32 {
+    let participants = participants.max(1);
+    let share = rating / participants;
+    if author {
+        share + rating % participants
+    } else {
+        share
+    }
+}
+
 #[derive(
     Debug,
     Clone,
@@ -36,6 +50,17 @@ pub struct PRInfo {
     pub merged: bool,
     pub executed: bool,
     pub excluded: bool,
+    pub coauthors: Vec<GithubHandle>,
+    pub ready_to_move_timestamp: Option<Timestamp>,
+    // Scale of the PR, or the default one if the PR is not included yet
+    #[serde(default)]
+    pub scale: ScoringScale,
+    // Nothing can be changed by the bot until the admin unpauses the contract
+    #[serde(default)]
+    pub paused_contract: bool,
+    // Nothing can be changed by the bot until the pending migrations are applied
+    #[serde(default)]
+    pub migrating_contract: bool,
 }
 
 impl PRInfo {
@@ -81,23 +106,27 @@ pub struct PRv2 {
 pub enum VersionedPR {
     V1(PRWithRating),
     V2(PRv2),
+    V3(PRv3),
+    V4(PRv4),
+    V5(PRv5),
+    V6(PRv6),
 }
 
 impl VersionedPR {
     pub fn is_merged(&self) -> bool {
-        let data: PRv2 = self.clone().into();
+        let data: PRv6 = self.clone().into();
 
         data.merged_at.is_some()
     }
 
     pub fn is_ready_to_move(&self, timestamp: Timestamp) -> bool {
-        let data: PRv2 = self.clone().into();
+        let data: PRv6 = self.clone().into();
 
         data.is_ready_to_move(timestamp)
     }
 }
 
-impl From<VersionedPR> for PRv2 {
+impl From<VersionedPR> for PRv6 {
     fn from(message: VersionedPR) -> Self {
         match message {
             VersionedPR::V1(x) => Self {
@@ -111,12 +140,248 @@ impl From<VersionedPR> for PRv2 {
                 merged_at: x.merged_at,
                 streak_bonus_rating: x.streak_bonus_rating,
                 percentage_multiplier: x.percentage_multiplier,
+                coauthors: vec![],
+                bounty_rating: 0,
+                scale: ScoringScale::Fibonacci,
+                autoscored: false,
+                ratings: vec![],
+                user_ids: vec![],
+            },
+            VersionedPR::V2(x) => Self {
+                organization: x.organization,
+                repo: x.repo,
+                number: x.number,
+                author: x.author,
+                score: x.score,
+                included_at: x.included_at,
+                created_at: x.created_at,
+                merged_at: x.merged_at,
+                streak_bonus_rating: x.streak_bonus_rating,
+                percentage_multiplier: x.percentage_multiplier,
+                coauthors: vec![],
+                bounty_rating: 0,
+                scale: ScoringScale::Fibonacci,
+                autoscored: false,
+                ratings: vec![],
+                user_ids: vec![],
+            },
+            VersionedPR::V3(x) => Self {
+                organization: x.organization,
+                repo: x.repo,
+                number: x.number,
+                author: x.author,
+                score: x.score,
+                included_at: x.included_at,
+                created_at: x.created_at,
+                merged_at: x.merged_at,
+                streak_bonus_rating: x.streak_bonus_rating,
+                percentage_multiplier: x.percentage_multiplier,
+                coauthors: x.coauthors,
+                bounty_rating: 0,
+                scale: ScoringScale::Fibonacci,
+                autoscored: false,
+                ratings: vec![],
+                user_ids: vec![],
+            },
+            VersionedPR::V4(x) => Self {
+                organization: x.organization,
+                repo: x.repo,
+                number: x.number,
+                author: x.author,
+                score: x.score,
+                included_at: x.included_at,
+                created_at: x.created_at,
+                merged_at: x.merged_at,
+                streak_bonus_rating: x.streak_bonus_rating,
+                percentage_multiplier: x.percentage_multiplier,
+                coauthors: x.coauthors,
+                bounty_rating: x.bounty_rating,
+                scale: ScoringScale::Fibonacci,
+                autoscored: false,
+                ratings: vec![],
+                user_ids: vec![],
+            },
+            VersionedPR::V5(x) => Self {
+                organization: x.organization,
+                repo: x.repo,
+                number: x.number,
+                author: x.author,
+                score: x.score,
+                included_at: x.included_at,
+                created_at: x.created_at,
+                merged_at: x.merged_at,
+                streak_bonus_rating: x.streak_bonus_rating,
+                percentage_multiplier: x.percentage_multiplier,
+                coauthors: x.coauthors,
+                bounty_rating: x.bounty_rating,
+                scale: x.scale,
+                autoscored: false,
+                ratings: vec![],
+                user_ids: vec![],
             },
-            VersionedPR::V2(x) => x,
+            VersionedPR::V6(x) => x,
         }
     }
 }
 
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRv3 {
+    pub organization: String,
+    pub repo: String,
+    pub number: u64,
+    pub author: GithubHandle,
+    pub score: Vec<Score>,
+    pub included_at: Timestamp,
+    pub created_at: Option<Timestamp>,
+    pub merged_at: Option<Timestamp>,
+    // Author's streak bonus and multiplier. Co-authors have their own, that are not stored in the PR
+    pub streak_bonus_rating: u32,
+    pub percentage_multiplier: u32,
+    pub coauthors: Vec<GithubHandle>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRv4 {
+    pub organization: String,
+    pub repo: String,
+    pub number: u64,
+    pub author: GithubHandle,
+    pub score: Vec<Score>,
+    pub included_at: Timestamp,
+    pub created_at: Option<Timestamp>,
+    pub merged_at: Option<Timestamp>,
+    // Author's streak bonus and multiplier. Co-authors have their own, that are not stored in the PR
+    pub streak_bonus_rating: u32,
+    pub percentage_multiplier: u32,
+    pub coauthors: Vec<GithubHandle>,
+    // Rating from the bounties of the issues closed by the PR. Claimed on finalization
+    pub bounty_rating: u32,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRv5 {
+    pub organization: String,
+    pub repo: String,
+    pub number: u64,
+    pub author: GithubHandle,
+    pub score: Vec<Score>,
+    pub included_at: Timestamp,
+    pub created_at: Option<Timestamp>,
+    pub merged_at: Option<Timestamp>,
+    // Author's streak bonus and multiplier. Co-authors have their own, that are not stored in the PR
+    pub streak_bonus_rating: u32,
+    pub percentage_multiplier: u32,
+    pub coauthors: Vec<GithubHandle>,
+    // Rating from the bounties of the issues closed by the PR. Claimed on finalization
+    pub bounty_rating: u32,
+    // Scale the votes were given in
+    #[serde(default)]
+    pub scale: ScoringScale,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PRv6 {
+    pub organization: String,
+    pub repo: String,
+    pub number: u64,
+    pub author: GithubHandle,
+    pub score: Vec<Score>,
+    pub included_at: Timestamp,
+    pub created_at: Option<Timestamp>,
+    pub merged_at: Option<Timestamp>,
+    // Author's streak bonus and multiplier. Co-authors have theirs in `ratings`
+    pub streak_bonus_rating: u32,
+    pub percentage_multiplier: u32,
+    pub coauthors: Vec<GithubHandle>,
+    // Rating from the bounties of the issues closed by the PR. Claimed on finalization
+    pub bounty_rating: u32,
+    // Scale the votes were given in
+    #[serde(default)]
+    pub scale: ScoringScale,
+    // The only vote is the autoscore given on finalization, it isn't rewarded as scoring
+    #[serde(default)]
+    pub autoscored: bool,
+    // What each participant received on finalization, so the revocation reverts exactly that.
+    // Empty for the PRs finalized before it was stored
+    #[serde(default)]
+    pub ratings: Vec<ParticipantRating>,
+    // Accounts of the participants and scorers by the handle they had on the PR.
+    // Handles can be taken by other users after a rename, the ids can't
+    #[serde(default)]
+    pub user_ids: Vec<(GithubHandle, UserId)>,
+}
+
+/// Rating a participant received for the finalized PR with the streak bonuses it used and earned
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ParticipantRating {
+    pub user_id: UserId,
+    pub rating: u32,
+    pub used_bonuses: Vec<FlatBonusStorage>,
+    pub earned_bonuses: Vec<FlatBonusStorage>,
+    // Lifetime bonuses raised by the PR with their previous percent, zero if the bonus is new
+    pub raised_bonuses: Vec<(StreakId, u32)>,
+}
+
 #[derive(
     Debug,
     Clone,
@@ -142,7 +407,7 @@ pub struct PRWithRating {
     pub percentage_multiplier: u32,
 }
 
-impl PRv2 {
+impl PRv6 {
     pub const fn new(
         organization: String,
         repo: String,
@@ -150,6 +415,7 @@ impl PRv2 {
         author: GithubHandle,
         included_at: Timestamp,
         created_at: Timestamp,
+        scale: ScoringScale,
     ) -> Self {
         Self {
             organization,
@@ -163,9 +429,47 @@ impl PRv2 {
             merged_at: None,
             streak_bonus_rating: 0,
             percentage_multiplier: 0,
+            coauthors: vec![],
+            bounty_rating: 0,
+            scale,
+            autoscored: false,
+            ratings: vec![],
+            user_ids: vec![],
         }
     }
 
+    // Author goes first
+    pub fn participants(&self) -> Vec<GithubHandle> {
+        std::iter::once(self.author.clone())
+            .chain(self.coauthors.iter().cloned())
+            .collect()
+    }
+
+    pub fn user_id(&self, handle: &str) -> Option<UserId> {
+        self.user_ids
+            .iter()
+            .find(|(user, _)| user == handle)
+            .map(|(_, user_id)| *user_id)
+    }
+
+    pub fn set_user_id(&mut self, handle: &str, user_id: UserId) {
+        self.user_ids.retain(|(user, _)| user != handle);
+        self.user_ids.push((handle.to_string(), user_id));
+    }
+
+    pub fn participants_amount(&self) -> u32 {
+        self.coauthors.len() as u32 + 1
+    }
+
+    // Base rating that the author or each of the co-authors receives
+    pub fn shared_base_rating(&self, author: bool) -> u32 {
+        split_rating(
+            self.score().unwrap_or_default() * 10,
+            self.participants_amount(),
+            author,
+        )
+    }
+
     // Returns the old score if the user already had already scored
     pub fn add_score(&mut self, user: GithubHandle, score: u32) -> Option<u32> {
         if let Some(user) = self.score.iter_mut().find(|s| s.user == user) {
@@ -178,6 +482,12 @@ impl PRv2 {
         }
     }
 
+    // Returns the removed score if the user has scored the PR
+    pub fn remove_score(&mut self, user: &str) -> Option<u32> {
+        let index = self.score.iter().position(|s| s.user == user)?;
+        Some(self.score.remove(index).score)
+    }
+
     pub fn add_merge_info(&mut self, merged_at: Timestamp) {
         self.merged_at = Some(merged_at);
     }
@@ -192,8 +502,19 @@ impl PRv2 {
     }
 
     pub fn rating(&self) -> u32 {
-        let score = self.score().unwrap_or_default() * 10 + self.streak_bonus_rating;
-        let percentage = (self.percentage_multiplier + 100) as f64;
+        self.participant_rating(true, self.streak_bonus_rating, self.percentage_multiplier)
+    }
+
+    pub fn participant_rating(
+        &self,
+        author: bool,
+        streak_bonus_rating: u32,
+        percentage_multiplier: u32,
+    ) -> u32 {
+        let score = self.shared_base_rating(author)
+            + split_rating(self.bounty_rating, self.participants_amount(), author)
+            + streak_bonus_rating;
+        let percentage = (percentage_multiplier + 100) as f64;
         ((score as f64 * percentage / 100.0).ceil()) as u32
     }
 
@@ -209,3 +530,61 @@ impl PRv2 {
         format!("{}/{}/{}", self.organization, self.repo, self.number)
     }
 }
+
+/// What the archived PRs of the user contributed, as the PRs themselves are removed from the storage
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ArchiveSummary {
+    pub executed_prs: u32,
+    pub total_score: u32,
+    pub total_rating: u32,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Revocation {
+    pub moderator: AccountId,
+    pub reason: String,
+    pub revoked_at: Timestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_rating;
+
+    #[test]
+    fn split_shares_add_up_to_rating() {
+        for rating in [0, 1, 13, 80, 130] {
+            for participants in 1..=6 {
+                let total = split_rating(rating, participants, true)
+                    + (participants - 1) * split_rating(rating, participants, false);
+                assert_eq!(total, rating, "{rating} split {participants} ways");
+            }
+        }
+        assert_eq!(split_rating(13, 3, true), 5);
+        assert_eq!(split_rating(13, 3, false), 4);
+    }
+}
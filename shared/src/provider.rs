@@ -0,0 +1,108 @@
+/// Code hosting the PR comes from.
+/// The contract stores the provider as a prefix of the organization name,
+/// so GitHub ids stay `org/repo/number` and GitLab ids are `gitlab:group/repo/number`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Provider {
+    #[default]
+    GitHub,
+    GitLab,
+}
+
+impl Provider {
+    pub const GITLAB_PREFIX: &'static str = "gitlab:";
+    pub const GITHUB_URL: &'static str = "https://github.com";
+    pub const GITLAB_URL: &'static str = "https://gitlab.com";
+
+    // Splits the stored organization into the provider and the organization name on it
+    pub fn from_organization(organization: &str) -> Option<(Self, &str)> {
+        match organization.split_once(':') {
+            None => Some((Self::GitHub, organization)),
+            Some(_) => organization
+                .strip_prefix(Self::GITLAB_PREFIX)
+                .filter(|name| !name.is_empty())
+                .map(|name| (Self::GitLab, name)),
+        }
+    }
+
+    pub fn organization(&self, name: &str) -> String {
+        match self {
+            Self::GitHub => name.to_string(),
+            Self::GitLab => format!("{}{name}", Self::GITLAB_PREFIX),
+        }
+    }
+
+    // Users of other providers are namespaced the same way as organizations, e.g. `gitlab:alice`
+    pub fn user(&self, login: &str) -> String {
+        self.organization(login)
+    }
+
+    // Login of the namespaced user on its provider, it's used to mention the user
+    pub fn login(handle: &str) -> &str {
+        Self::from_organization(handle)
+            .map(|(_, login)| login)
+            .unwrap_or(handle)
+    }
+
+    pub fn pull_request_url(&self, base_url: &str, name: &str, repo: &str, number: u64) -> String {
+        match self {
+            Self::GitHub => format!("{base_url}/{name}/{repo}/pull/{number}"),
+            Self::GitLab => format!("{base_url}/{name}/{repo}/-/merge_requests/{number}"),
+        }
+    }
+
+    pub fn user_url(&self, base_url: &str, handle: &str) -> String {
+        format!("{base_url}/{}", Self::login(handle))
+    }
+
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            Self::GitHub => Self::GITHUB_URL,
+            Self::GitLab => Self::GITLAB_URL,
+        }
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GitHub => write!(f, "github"),
+            Self::GitLab => write!(f, "gitlab"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Provider;
+
+    #[test]
+    fn organization_namespacing() {
+        assert_eq!(
+            Provider::from_organization("NEAR-DevHub"),
+            Some((Provider::GitHub, "NEAR-DevHub"))
+        );
+        assert_eq!(
+            Provider::from_organization("gitlab:near/sub"),
+            Some((Provider::GitLab, "near/sub"))
+        );
+        assert_eq!(Provider::from_organization("gitlab:"), None);
+        assert_eq!(Provider::from_organization("bitbucket:near"), None);
+        assert_eq!(Provider::GitLab.organization("near"), "gitlab:near");
+        assert_eq!(Provider::GitHub.organization("near"), "near");
+        assert_eq!(Provider::GitLab.user("alice"), "gitlab:alice");
+        assert_eq!(Provider::login("gitlab:alice"), "alice");
+        assert_eq!(Provider::login("alice"), "alice");
+        assert_eq!(
+            Provider::GitLab.pull_request_url(Provider::GITLAB_URL, "near", "bot", 3),
+            "https://gitlab.com/near/bot/-/merge_requests/3"
+        );
+        assert_eq!(
+            Provider::GitLab.user_url(Provider::GITLAB_URL, "gitlab:alice"),
+            "https://gitlab.com/alice"
+        );
+        assert_eq!(
+            Provider::GitHub.user_url(Provider::GITHUB_URL, "alice"),
+            "https://github.com/alice"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use super::*;
+
+/// Rank of the users whose lifetime percentage bonus is at least `min_bonus`
+#[derive(
+    Debug,
+    Clone,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct RankTier {
+    pub name: String,
+    pub min_bonus: u32,
+}
+
+impl RankTier {
+    pub fn new(name: &str, min_bonus: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            min_bonus,
+        }
+    }
+
+    pub fn default_tiers() -> Vec<Self> {
+        vec![
+            Self::new("Bronze", 5),
+            Self::new("Silver", 10),
+            Self::new("Gold", 15),
+            Self::new("Platinum", 20),
+            Self::new("Rust", 25),
+        ]
+    }
+
+    // Tiers have to be sorted by `min_bonus` in ascending order. Returns the index and the tier
+    pub fn find(tiers: &[Self], bonus: u32) -> Option<(usize, &Self)> {
+        tiers
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, tier)| bonus >= tier.min_bonus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RankTier;
+
+    #[test]
+    fn rank_is_the_highest_reached_tier() {
+        let tiers = RankTier::default_tiers();
+        assert_eq!(RankTier::find(&tiers, 0), None);
+        assert_eq!(RankTier::find(&tiers, 4), None);
+        assert_eq!(RankTier::find(&tiers, 5).unwrap().1.name, "Bronze");
+        assert_eq!(RankTier::find(&tiers, 19).unwrap().1.name, "Gold");
+        assert_eq!(RankTier::find(&tiers, 100), Some((4, &tiers[4])));
+    }
+}
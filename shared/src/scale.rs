@@ -0,0 +1,151 @@
+use super::*;
+
+/// Values reviewers score a PR with. Votes are stored as points, so ratings of the different scales are comparable.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Eq,
+    PartialEq,
+)]
+#[serde(crate = "near_sdk::serde", rename_all = "lowercase")]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum ScoringScale {
+    #[default]
+    Fibonacci,
+    Linear,
+    TShirt,
+}
+
+const FIBONACCI: [(&str, u32); 7] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("5", 5),
+    ("8", 8),
+    ("13", 13),
+];
+const LINEAR: [(&str, u32); 10] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+    ("10", 10),
+];
+// Spans the same points as the Fibonacci scale
+const TSHIRT: [(&str, u32); 5] = [("XS", 1), ("S", 2), ("M", 5), ("L", 8), ("XL", 13)];
+
+impl ScoringScale {
+    // Labels and points in ascending order
+    fn values(&self) -> &'static [(&'static str, u32)] {
+        match self {
+            Self::Fibonacci => &FIBONACCI,
+            Self::Linear => &LINEAR,
+            Self::TShirt => &TSHIRT,
+        }
+    }
+
+    pub fn is_valid(&self, points: u32) -> bool {
+        self.values().iter().any(|(_, p)| *p == points)
+    }
+
+    pub fn label(&self, points: u32) -> String {
+        self.values()
+            .iter()
+            .find(|(_, p)| *p == points)
+            .map(|(label, _)| label.to_string())
+            .unwrap_or_else(|| points.to_string())
+    }
+
+    pub fn labels(&self) -> Vec<&'static str> {
+        self.values().iter().map(|(label, _)| *label).collect()
+    }
+
+    // Returns the points and a flag if the input was corrected to the nearest valid value
+    pub fn parse(&self, input: &str) -> (u32, bool) {
+        let values = self.values();
+        let Some(input) = input.split_whitespace().next() else {
+            return (values[0].1, true);
+        };
+
+        if let Some((_, points)) = values
+            .iter()
+            .find(|(label, _)| label.eq_ignore_ascii_case(input))
+        {
+            return (*points, false);
+        }
+
+        match input.parse::<i64>() {
+            Ok(number) => {
+                let nearest = values
+                    .iter()
+                    .min_by_key(|(_, points)| (*points as i64 - number).abs())
+                    .map(|(_, points)| *points)
+                    .unwrap_or_default();
+                (nearest, true)
+            }
+            Err(_) => (values[0].1, true),
+        }
+    }
+}
+
+impl std::fmt::Display for ScoringScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fibonacci => write!(f, "Fibonacci"),
+            Self::Linear => write!(f, "linear"),
+            Self::TShirt => write!(f, "t-shirt size"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoringScale;
+
+    #[test]
+    fn fibonacci_parsing() {
+        let scale = ScoringScale::Fibonacci;
+        assert_eq!(scale.parse("5"), (5, false));
+        assert_eq!(scale.parse("0"), (0, false));
+        assert_eq!(scale.parse("9"), (8, true));
+        assert_eq!(scale.parse("7"), (8, true));
+        assert_eq!(scale.parse("-1"), (0, true));
+        assert_eq!(scale.parse("as"), (0, true));
+        assert_eq!(scale.parse(""), (0, true));
+    }
+
+    #[test]
+    fn linear_parsing() {
+        let scale = ScoringScale::Linear;
+        assert_eq!(scale.parse("7"), (7, false));
+        assert_eq!(scale.parse("0"), (1, true));
+        assert_eq!(scale.parse("100"), (10, true));
+        assert!(scale.is_valid(4));
+        assert!(!scale.is_valid(13));
+    }
+
+    #[test]
+    fn tshirt_parsing() {
+        let scale = ScoringScale::TShirt;
+        assert_eq!(scale.parse("m"), (5, false));
+        assert_eq!(scale.parse("XL please"), (13, false));
+        assert_eq!(scale.parse("7"), (8, true));
+        assert_eq!(scale.parse("XXL"), (1, true));
+        assert_eq!(scale.label(8), "L");
+        assert_eq!(scale.labels().join(", "), "XS, S, M, L, XL");
+        assert!(!scale.is_valid(3));
+    }
+}
@@ -45,6 +45,41 @@ impl TimePeriod {
         }
     }
 
+    // Period of the string made by `time_string`. Days are not stored, so they are not recognized
+    pub fn from_time_string(time_string: &str) -> Option<Self> {
+        // Numbers are written without leading zeros, except for the months
+        let number = |value: &str, range: std::ops::RangeInclusive<u32>| {
+            value
+                .parse::<u32>()
+                .ok()
+                .filter(|number| range.contains(number) && number.to_string() == value)
+        };
+
+        if time_string == "all-time" {
+            return Some(Self::AllTime);
+        }
+        if let Some((year, week)) = time_string.split_once('W') {
+            number(year, 1..=9999)?;
+            number(week, 1..=53)?;
+            return Some(Self::Week);
+        }
+        if let Some((year, quarter)) = time_string.split_once('Q') {
+            number(year, 1..=9999)?;
+            number(quarter, 1..=4)?;
+            return Some(Self::Quarter);
+        }
+        if time_string.len() == 6 && time_string.is_ascii() {
+            let (month, year) = time_string.split_at(2);
+            month
+                .parse::<u32>()
+                .ok()
+                .filter(|month| (1..=12).contains(month))?;
+            number(year, 1000..=9999)?;
+            return Some(Self::Month);
+        }
+        number(time_string, 1..=9999).map(|_| Self::Year)
+    }
+
     pub fn time_string(&self, timestamp: Timestamp) -> TimePeriodString {
         match self {
             Self::Day => timestamp_to_day_string(timestamp),
@@ -218,6 +253,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_time_string() {
+        assert_eq!(
+            TimePeriod::from_time_string("2023W25"),
+            Some(TimePeriod::Week)
+        );
+        assert_eq!(
+            TimePeriod::from_time_string("062023"),
+            Some(TimePeriod::Month)
+        );
+        assert_eq!(
+            TimePeriod::from_time_string("2023Q2"),
+            Some(TimePeriod::Quarter)
+        );
+        assert_eq!(TimePeriod::from_time_string("2023"), Some(TimePeriod::Year));
+        assert_eq!(
+            TimePeriod::from_time_string("all-time"),
+            Some(TimePeriod::AllTime)
+        );
+
+        assert_eq!(TimePeriod::from_time_string("20062023"), None);
+        assert_eq!(TimePeriod::from_time_string("2023W05"), None);
+        assert_eq!(TimePeriod::from_time_string("2023W54"), None);
+        assert_eq!(TimePeriod::from_time_string("2023Q5"), None);
+        assert_eq!(TimePeriod::from_time_string("132023"), None);
+        assert_eq!(TimePeriod::from_time_string("alltime"), None);
+        assert_eq!(TimePeriod::from_time_string(""), None);
+    }
+
     #[test]
     fn test_previous_period() {
         let timestamp = Utc